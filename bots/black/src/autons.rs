@@ -1,18 +1,17 @@
 use std::{char::DecodeUtf16Error, time::Duration};
 
-use autons::{Selector, simple::Route};
+use autons::{route, simple::Route};
 use evian::{
 	math::{Angle, Vec2},
-	prelude::{Arcade, TracksForwardTravel, TracksHeading, TracksPosition},
+	prelude::{TracksForwardTravel, TracksHeading, TracksPosition},
 };
 use evian_extra::{
 	control::ltv_unicycle::LTVUnicycleController, motion::ltv_unicycle::LTVUnicycleMotion,
 };
+use robot::{Robot, auton::AutonRobot};
 use subsystems::{intake::IntakeState, intake_unjamming, pnemuatics::PneumaticState};
 use vexide::{smart::motor::BrakeMode, time::sleep};
 
-use crate::robot::Robot;
-
 macro_rules! change_timeout {
 	($basic:expr, $timeout:expr, $code:block) => {
 		let saved_timeout = $basic.timeout;
@@ -22,20 +21,31 @@ macro_rules! change_timeout {
 	};
 }
 
+pub const DEFAULT_AUTON: &str = "Match auton";
+
+/// Every auton, in the order they're listed on the selector
+pub fn routes() -> [Route<Robot>; 6] {
+	[
+		route!("Do nothing", do_nothing),
+		route!("Match auton", match_auton),
+		route!("Skills auton", skills_or_whatever),
+		route!("Double park", double_park),
+		route!("PID Testing", pid_testing),
+		route!("LQR Testing", motion_profile),
+	]
+}
+
 pub async fn do_nothing(_robot: &mut Robot) {
 	println!("Doing absolutely nothing!!!");
 }
 
-pub async fn match_auton(robot: &mut Robot) {
+pub async fn match_auton(robot: &mut impl AutonRobot) {
 	// Basic setup.
-	_ = robot
-		.pneumatics
-		.extender
-		.set_state(PneumaticState::Extended);
-		_ = robot.pneumatics.outtake_adjuster.set_state(PneumaticState::Extended);
-		_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
-
-		robot.intake.run(IntakeState {
+	_ = robot.set_piston("extender", PneumaticState::Extended);
+		_ = robot.set_piston("outtake_adjuster", PneumaticState::Extended);
+		_ = robot.set_piston("flap", PneumaticState::Contracted);
+
+		robot.run_intake(IntakeState {
 		top: 1.0,
 		middle: 1.0,
 		bottom: 1.0,
@@ -44,36 +54,36 @@ pub async fn match_auton(robot: &mut Robot) {
 	let mut basic = crate::control::BASIC_CONTROLLER;
 
 	// Move to center goal position
-	basic.drive_distance(&mut robot.drivetrain, -45.5).await;
+	basic.drive_distance(robot.drivetrain(), -45.5).await;
 
 	// Turn towards center goal
 	basic
-		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(45.0))
+		.turn_to_heading(robot.drivetrain(), Angle::from_degrees(45.0))
 		.await;
 
 	// Align with center goal
-	_ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
-	sleep(Duration::from_millis(1000)).await;
-	//_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain().drive_arcade(-0.25, 0.0);
+	robot.sleep(Duration::from_millis(1000)).await;
+	//_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
-	//_ = robot.drivetrain.model.drive_arcade(0.17, 0.0);
+	//_ = robot.drivetrain.drive_arcade(0.17, 0.0);
 	//sleep(Duration::from_millis(300)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain().drive_arcade(0.0, 0.0);
 
 	// Robot intake runs and ejects the balls into the center goal.
 
-	//_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
-		_ = robot.pneumatics.outtake_adjuster.set_state(PneumaticState::Contracted);
+	//_ = robot.pneumatics.set("flap", PneumaticState::Extended);
+		_ = robot.set_piston("outtake_adjuster", PneumaticState::Contracted);
 
 	//robot.intake.run(IntakeState {
 	//	top: 1.0,
 	//	middle: 1.0,
 	//	bottom: 1.0,
 	//});
-	sleep(Duration::from_secs(2)).await;
-	robot.intake.run(IntakeState::full_brake());
-	_ = robot.pneumatics.outtake_adjuster.set_state(PneumaticState::Contracted);
-	//_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
+	robot.sleep(Duration::from_secs(2)).await;
+	robot.run_intake(IntakeState::full_brake());
+	_ = robot.set_piston("outtake_adjuster", PneumaticState::Contracted);
+	//_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
 
 	//basic
 	//	.drive_distance_at_heading(&mut robot.drivetrain, 25.0, Angle::from_degrees(45.0))
@@ -82,199 +92,176 @@ pub async fn match_auton(robot: &mut Robot) {
 	// Calculate distance from line
 	change_timeout!(basic, Some(Duration::from_secs(1)), {
 		basic
-			.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(45.0))
+			.turn_to_heading(robot.drivetrain(), Angle::from_degrees(45.0))
 			.await;
 	});
 
-	let dist_x = (robot.drivetrain.tracking.position().x - 31.0).abs();
-	let dist = (dist_x / (robot.drivetrain.tracking.heading()).cos()).abs();
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
+	let dist_x = (robot.drivetrain().tracking.position().x - 31.0).abs();
+	let dist = (dist_x / (robot.drivetrain().tracking.heading()).cos()).abs();
+	_ = robot.set_piston("front_bar", PneumaticState::Extended);
+	_ = robot.set_piston("outtake_adjuster", PneumaticState::Extended);
 	change_timeout!(basic, Some(Duration::from_secs(2)), {
-		basic.drive_distance(&mut robot.drivetrain, dist).await;
+		basic.drive_distance(robot.drivetrain(), dist).await;
 	});
 
 	// Turn to matchload
 	change_timeout!(basic, Some(Duration::from_millis(1500)), {
 		basic
-			.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(89.5))
+			.turn_to_heading(robot.drivetrain(), Angle::from_degrees(89.5))
 			.await;
 	});
 
 	// Load from matchload
 
 	//sleep(Duration::from_millis(750)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.0);
-	robot.intake.run(IntakeState {
+	_ = robot.drivetrain().drive_arcade(0.35, 0.0);
+	robot.run_intake(IntakeState {
 		bottom: 1.0,
 		middle: 1.0,
 		top: 0.0,
 	});
-	sleep(Duration::from_millis(2500)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
-	sleep(Duration::from_millis(350)).await;
-	// _ = robot.drivetrain.model.drive_arcade(0.25, 0.0);
+	robot.sleep(Duration::from_millis(2500)).await;
+	_ = robot.drivetrain().drive_arcade(-0.25, 0.0);
+	robot.sleep(Duration::from_millis(350)).await;
+	// _ = robot.drivetrain.drive_arcade(0.25, 0.0);
 	// sleep(Duration::from_millis(1000)).await;
-	// _ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
+	// _ = robot.drivetrain.drive_arcade(-0.25, 0.0);
 	// sleep(Duration::from_millis(350)).await;
 
 	let long_goal_point = Vec2::new(31.1, -33.5);
-	let pos = robot.drivetrain.tracking.position();
+	let pos = robot.drivetrain().tracking.position();
 	let angle =
 		Angle::atan2(long_goal_point.y - pos.y, long_goal_point.x - pos.x) + Angle::from_turns(0.5);
-	basic.turn_to_heading(&mut robot.drivetrain, angle).await;
+	basic.turn_to_heading(robot.drivetrain(), angle).await;
 
 	// Drive to long goal
-	let pos = robot.drivetrain.tracking.position();
+	let pos = robot.drivetrain().tracking.position();
 	change_timeout!(basic, Some(Duration::from_millis(1500)), {
 		basic
 			.drive_distance(
-				&mut robot.drivetrain,
+				robot.drivetrain(),
 				-(pos.distance(long_goal_point) - 1.0),
 			)
 			.await;
 	});
-	_ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
-	sleep(Duration::from_millis(1000)).await;
+	_ = robot.drivetrain().drive_arcade(-0.25, 0.0);
+	robot.sleep(Duration::from_millis(1000)).await;
 
 	// Outtake into long goal
 
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	intake_unjamming!(
-		robot,
-		IntakeState {
-			top: 1.0,
-			middle: 0.1,
-			bottom: 0.0,
-		},
-		r => async {
-			sleep(Duration::from_millis(500)).await;
-		}
-	);
-	intake_unjamming!(
-		robot,
-		IntakeState {
-			top: 1.0,
-			middle: 0.0,
-			bottom: 0.0,
-		},
-		r => async {
-			sleep(Duration::from_secs(2)).await;
-		}
-	);
+	_ = robot.set_piston("flap", PneumaticState::Extended);
+	_ = robot.set_piston("outtake_adjuster", PneumaticState::Extended);
+	robot
+		.unjam_intake(
+			IntakeState {
+				top: 1.0,
+				middle: 0.1,
+				bottom: 0.0,
+			},
+			Duration::from_millis(500),
+		)
+		.await;
+	robot
+		.unjam_intake(
+			IntakeState {
+				top: 1.0,
+				middle: 0.0,
+				bottom: 0.0,
+			},
+			Duration::from_secs(2),
+		)
+		.await;
 
 	// Go back, outtake red balls
 	change_timeout!(basic, Some(Duration::from_secs(1)), {
-		basic.drive_distance(&mut robot.drivetrain, 12.0).await;
+		basic.drive_distance(robot.drivetrain(), 12.0).await;
 	});
-	robot.intake.run(IntakeState {
+	robot.run_intake(IntakeState {
 		top: 1.0,
 		middle: 1.0,
 		bottom: 1.0,
 	});
-	sleep(Duration::from_secs(2)).await;
-	robot.intake.run(IntakeState {
+	robot.sleep(Duration::from_secs(2)).await;
+	robot.run_intake(IntakeState {
 		top: -1.0,
 		middle: -1.0,
 		bottom: -1.0,
 	});
-	sleep(Duration::from_millis(100)).await;
-	robot.intake.run(IntakeState {
+	robot.sleep(Duration::from_millis(100)).await;
+	robot.run_intake(IntakeState {
 		top: 0.0,
 		middle: 1.0,
 		bottom: 1.0,
 	});
 	change_timeout!(basic, Some(Duration::from_secs(1)), {
-		basic.drive_distance(&mut robot.drivetrain, 12.0).await;
+		basic.drive_distance(robot.drivetrain(), 12.0).await;
 	});
-	sleep(Duration::from_secs(1)).await;
+	robot.sleep(Duration::from_secs(1)).await;
 
-	robot.intake.run(IntakeState::full_brake());
+	robot.run_intake(IntakeState::full_brake());
 
-	let pos = robot.drivetrain.tracking.position();
+	let pos = robot.drivetrain().tracking.position();
 	let angle =
 		Angle::atan2(long_goal_point.y - pos.y, long_goal_point.x - pos.x) + Angle::from_turns(0.5);
 	while let error =
-		(robot.drivetrain.tracking.heading().wrapped_full() - Angle::from_degrees(90.0))
+		(robot.drivetrain().tracking.heading().wrapped_full() - Angle::from_degrees(90.0))
 		&& error.abs() > Angle::from_degrees(1.0)
 	{
-		_ = robot
-			.drivetrain
-			.model
-			.drive_arcade(0.0, 0.2 * error.signum());
-		sleep(Duration::from_millis(5)).await;
+		_ = robot.drivetrain().drive_arcade(0.0, 0.2 * error.signum());
+		robot.sleep(Duration::from_millis(5)).await;
 	}
-	robot.drivetrain.brake(BrakeMode::Hold);
+	robot.brake(BrakeMode::Hold);
 
 	// Leo's and Noah's test code, not perfect but will score a few points.
 	// Once code is revised the bot will go back to the tower and grab the balls again.
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
-	sleep(Duration::from_millis(800)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.0);
-	robot.intake.run(IntakeState::full_forward());
-	sleep(Duration::from_secs(3)).await;
+	_ = robot.set_piston("front_bar", PneumaticState::Extended);
+	_ = robot.set_piston("flap", PneumaticState::Contracted);
+	robot.sleep(Duration::from_millis(800)).await;
+	_ = robot.drivetrain().drive_arcade(0.35, 0.0);
+	robot.run_intake(IntakeState::full_forward());
+	robot.sleep(Duration::from_secs(3)).await;
 
-	_ = robot.drivetrain.model.drive_arcade(0.0, 1.0);
+	_ = robot.drivetrain().drive_arcade(0.0, 1.0);
 
-	sleep(Duration::from_millis(10)).await;
+	robot.sleep(Duration::from_millis(10)).await;
 
-	_ = robot.drivetrain.model.drive_arcade(-0.35, 0.0);
-	sleep(Duration::from_millis(1500)).await;
+	_ = robot.drivetrain().drive_arcade(-0.35, 0.0);
+	robot.sleep(Duration::from_millis(1500)).await;
 
 	// Robot drives up to the high beam and ejects its balls.
 
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
-	robot.intake.run(IntakeState {
+	_ = robot.set_piston("outtake_adjuster", PneumaticState::Extended);
+	_ = robot.set_piston("flap", PneumaticState::Extended);
+	robot.run_intake(IntakeState {
 		top: 1.0,
 		middle: 1.0,
 		bottom: 1.0,
 	});
-	sleep(Duration::from_secs(2)).await;
-	robot.intake.run(IntakeState {
+	robot.sleep(Duration::from_secs(2)).await;
+	robot.run_intake(IntakeState {
 		top: -1.0,
 		middle: -1.0,
 		bottom: -1.0,
 	});
-	sleep(Duration::from_millis(100)).await;
-	robot.intake.run(IntakeState {
+	robot.sleep(Duration::from_millis(100)).await;
+	robot.run_intake(IntakeState {
 		top: 1.0,
 		middle: 1.0,
 		bottom: 1.0,
 	});
-	sleep(Duration::from_secs(2)).await;
+	robot.sleep(Duration::from_secs(2)).await;
 }
 
-pub async fn double_park(robot: &mut Robot) {
+pub async fn double_park(robot: &mut impl AutonRobot) {
 	// Drive back to be technically parked
-	_ = robot.drivetrain.model.drive_arcade(-0.20, 0.0);
-	sleep(Duration::from_millis(100)).await;
-	_ = robot.drivetrain.brake(BrakeMode::Hold);
-	sleep(Duration::from_millis(1000)).await;
+	_ = robot.drivetrain().drive_arcade(-0.20, 0.0);
+	robot.sleep(Duration::from_millis(100)).await;
+	robot.brake(BrakeMode::Hold);
+	robot.sleep(Duration::from_millis(1000)).await;
 
 	// Park
-	_ = robot
-		.intake
-		.park_piston()
-		.set_state(PneumaticState::Extended);
+	_ = robot.set_park_piston(PneumaticState::Extended);
 }
 
 pub async fn skills_or_whatever(robot: &mut Robot) {
@@ -282,9 +269,7 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 	let mut basic = crate::control::BASIC_CONTROLLER;
 
 	basic.drive_distance(&mut robot.drivetrain, 18.0).await;
-	_ = robot
-		.pneumatics
-		.wing.as_mut().map(|w|w.set_state(PneumaticState::Extended));
+	_ = robot.pneumatics.set("wing", PneumaticState::Extended);
 	basic
 		.drive_distance_at_heading(&mut robot.drivetrain, 31.0, Angle::from_degrees(180.0))
 		.await;
@@ -298,26 +283,17 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 		middle: 1.0,
 		bottom: 1.0,
 	});
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 		
-	_ = robot
-		.pneumatics
-		.extender
-		.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
-	_ = robot.drivetrain.model.drive_arcade(0.2, 0.0);
+	_ = robot.pneumatics.set("extender", PneumaticState::Extended);
+	_ = robot.pneumatics.set("outtake_adjuster", PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
+	_ = robot.drivetrain.drive_arcade(0.2, 0.0);
 	sleep(Duration::from_secs(3)).await;
 
-	_ = robot.drivetrain.model.drive_arcade(-0.35, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.35, 0.0);
 	sleep(Duration::from_millis(300)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.2, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.2, 0.0);
 	sleep(Duration::from_millis(1000)).await;
 
 	robot.intake.run(IntakeState {
@@ -328,33 +304,24 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 
 	basic.drive_distance(&mut robot.drivetrain, -7.0).await;
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(180.0))
 		.await;
 
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.35, 0.0);
 	sleep(Duration::from_secs(1)).await;
 
 	// Put bar down and bring balls back from wall
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 
-	_ = robot.drivetrain.model.drive_arcade(-0.1, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.1, 0.0);
 	sleep(Duration::from_secs(1)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.5, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.5, 0.0);
 	sleep(Duration::from_millis(200)).await;
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 
 	let gooo = -43.0;
 	let dist_y = (robot.drivetrain.tracking.position().x - gooo).abs();
@@ -386,22 +353,19 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(90.0))
 		.await;
 
-	_ = robot.drivetrain.model.drive_arcade(-1.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(-1.0, 0.0);
 	sleep(Duration::from_millis(300)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.3, 0.0);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.drivetrain.drive_arcade(-0.3, 0.0);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_millis(3500)).await;
 	});
 	// Move to matchload and intake balls
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.01);
+	_ = robot.drivetrain.drive_arcade(0.35, 0.01);
 	sleep(Duration::from_secs(1)).await;
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
 	sleep(Duration::from_secs(1)).await;
 
 	robot.intake.run(IntakeState {
@@ -410,13 +374,13 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 		bottom: 1.0,
 	});
 
-	_ = robot.drivetrain.model.drive_arcade(0.2, 0.01);
+	_ = robot.drivetrain.drive_arcade(0.2, 0.01);
 	sleep(Duration::from_secs(4)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.35, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.35, 0.0);
 	sleep(Duration::from_millis(300)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.2, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.2, 0.0);
 	sleep(Duration::from_millis(1000)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.15, 0.01);
+	_ = robot.drivetrain.drive_arcade(-0.15, 0.01);
 	sleep(Duration::from_millis(350)).await;
 
 	// Move to long goal and outtake balls
@@ -430,14 +394,14 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(gooo))
 		.await;
-	_ = robot.drivetrain.model.drive_arcade(-0.5, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.5, 0.0);
 	sleep(Duration::from_secs(2)).await;
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
 
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_secs(1)).await;
 	});
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	intake_unjamming!(
 		robot,
@@ -453,34 +417,25 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 	);
 	basic.drive_distance(&mut robot.drivetrain, 0.0).await;
 	basic.drive_distance(&mut robot.drivetrain, 15.0).await;
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(180.0))
 		.await;
 
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.35, 0.0);
 	sleep(Duration::from_secs(1)).await;
 
 	// Put bar down and bring balls back from wall
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 	sleep(Duration::from_millis(750)).await;
 
-	_ = robot.drivetrain.model.drive_arcade(-0.1, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.1, 0.0);
 	sleep(Duration::from_secs(1)).await;
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 
-	_ = robot.drivetrain.model.drive_arcade(0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.3, 0.0);
 	sleep(Duration::from_millis(200)).await;
 	let gooo = 17.0;
 	let dist_y = (robot.drivetrain.tracking.position().x.abs() + gooo);
@@ -491,23 +446,17 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(90.0))
 		.await;
-	_ = robot.drivetrain.model.drive_arcade(0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.3, 0.0);
 	sleep(Duration::from_millis(500)).await;
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 	sleep(Duration::from_millis(500)).await;
 
-	_ = robot.drivetrain.model.drive_arcade(-0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.3, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.3, 0.0);
 	sleep(Duration::from_millis(300)).await;
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 
 	//go to point code
 	let x = -7.0;
@@ -529,7 +478,7 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(-45.0))
 		.await;
-	_ = robot.drivetrain.model.drive_arcade(0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.3, 0.0);
 	sleep(Duration::from_millis(1300)).await;
 	basic.drive_distance(&mut robot.drivetrain, -4.0).await;
 	intake_unjamming!(
@@ -544,9 +493,9 @@ pub async fn skills_or_whatever(robot: &mut Robot) {
 			sleep(Duration::from_secs(4)).await;
 		}
 	);
-	_ = robot.drivetrain.model.drive_arcade(-0.3, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.3, 0.0);
 	sleep(Duration::from_millis(1000)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 }
 
 pub async fn pid_testing(robot: &mut Robot) {
@@ -573,24 +522,124 @@ pub async fn motion_profile(robot: &mut Robot) {
 	println!("Finished");
 }
 
-#[allow(dead_code)]
-pub struct StubSelector<R> {
-	route: Option<Route<R>>,
-}
+#[cfg(test)]
+mod tests {
+	use sim::{Event, Mechanism, Sim, physics::DriveParams};
 
-#[allow(dead_code)]
-impl<R> StubSelector<R> {
-	pub fn new<const N: usize>(selected: &'static str, routes: [Route<R>; N]) -> Self {
-		Self {
-			route: routes.into_iter().find(|r| r.name == selected),
-		}
+	use super::*;
+
+	const fn intake(top: f64, middle: f64, bottom: f64) -> Mechanism {
+		Mechanism::Intake(IntakeState {
+			top,
+			middle,
+			bottom,
+		})
 	}
-}
 
-impl<R> Selector<R> for StubSelector<R> {
-	async fn run(&self, robot: &mut R) {
-		if let Some(route) = &self.route {
-			(route.callback)(robot).await;
-		}
+	fn piston(name: &str, state: PneumaticState) -> Mechanism {
+		Mechanism::Piston(name.to_string(), state)
+	}
+
+	#[test]
+	fn test_match_auton() {
+		use PneumaticState::{Contracted, Extended};
+
+		let sim = Sim::new(
+			DriveParams::default(),
+			Vec2::new(0.0, 0.0),
+			Angle::from_degrees(90.0),
+		);
+		let mut robot = sim.robot(crate::config::config().piston_names());
+		assert!(
+			sim.run(match_auton(&mut robot), Duration::from_secs(60))
+				.is_some()
+		);
+
+		// Squares up to 90° next to the long goal, then the spin before backing away leaves it
+		// about 6° clockwise of that as it backs off for the last 5.6s
+		let position = robot.drivetrain.tracking.position();
+		let heading = robot.drivetrain.tracking.heading().as_degrees();
+		assert!(heading > 82.5 && heading < 86.0, "{heading}");
+		assert!(position.x > 10.0 && position.x < 30.0, "{}", position.x);
+		assert!(position.y > -75.0 && position.y < -40.0, "{}", position.y);
+
+		let events = sim.events();
+		assert_eq!(
+			events
+				.iter()
+				.map(|event| event.mechanism.clone())
+				.collect::<Vec<_>>(),
+			[
+				piston("extender", Extended),
+				piston("outtake_adjuster", Extended),
+				piston("flap", Contracted),
+				intake(1.0, 1.0, 1.0),
+				// Center goal
+				piston("outtake_adjuster", Contracted),
+				Mechanism::Intake(IntakeState::full_brake()),
+				piston("outtake_adjuster", Contracted),
+				// Matchload
+				piston("front_bar", Extended),
+				piston("outtake_adjuster", Extended),
+				intake(0.0, 1.0, 1.0),
+				// Long goal
+				piston("flap", Extended),
+				piston("outtake_adjuster", Extended),
+				intake(1.0, 0.1, 0.0),
+				intake(1.0, 0.0, 0.0),
+				intake(1.0, 1.0, 1.0),
+				intake(-1.0, -1.0, -1.0),
+				intake(0.0, 1.0, 1.0),
+				Mechanism::Intake(IntakeState::full_brake()),
+				// High beam
+				piston("front_bar", Extended),
+				piston("flap", Contracted),
+				Mechanism::Intake(IntakeState::full_forward()),
+				piston("outtake_adjuster", Extended),
+				piston("flap", Extended),
+				intake(1.0, 1.0, 1.0),
+				intake(-1.0, -1.0, -1.0),
+				intake(1.0, 1.0, 1.0),
+			]
+		);
+
+		// Everything between the motions waits on simulated time
+		let gap = |i: usize| events[i].time - events[i - 1].time;
+		assert!(events[..4].iter().all(|event| event.time == Duration::ZERO));
+		assert_eq!(gap(5), Duration::from_secs(2));
+		assert_eq!(gap(13), Duration::from_millis(500));
+		assert_eq!(gap(15), Duration::from_secs(2));
+		assert_eq!(gap(16), Duration::from_millis(100));
+		assert_eq!(gap(20), Duration::from_millis(800));
+		assert_eq!(gap(21), Duration::from_millis(4510));
+		assert_eq!(gap(24), Duration::from_secs(2));
+		assert_eq!(gap(25), Duration::from_millis(100));
+		assert_eq!(sim.now() - events[25].time, Duration::from_secs(2));
+	}
+
+	#[test]
+	fn test_double_park() {
+		let sim = Sim::new(
+			DriveParams::default(),
+			Vec2::new(0.0, 0.0),
+			Angle::from_degrees(90.0),
+		);
+		let mut robot = sim.robot(crate::config::config().piston_names());
+		assert!(
+			sim.run(double_park(&mut robot), Duration::from_secs(15))
+				.is_some()
+		);
+
+		// Backs up about 0.4in before holding still
+		let position = robot.drivetrain.tracking.position();
+		assert!(position.x.abs() < 1e-6, "{}", position.x);
+		assert!(position.y < -0.3 && position.y > -0.45, "{}", position.y);
+		assert_eq!(
+			sim.events(),
+			[Event {
+				time: Duration::from_millis(1100),
+				mechanism: Mechanism::ParkPiston(PneumaticState::Extended),
+			}]
+		);
 	}
 }
@@ -1,76 +0,0 @@
-use std::{fs::File, io::Write, time::Duration};
-
-use ::autons::prelude::SelectCompete;
-use subsystems::ControllableSubsystem;
-use vexide::prelude::*;
-
-use crate::robot::Robot;
-
-impl SelectCompete for Robot {
-	async fn driver(&mut self) {
-		sleep(Duration::from_millis(100)).await;
-		_ = self.pneumatics.initialize().await;
-
-		println!("Driver!");
-		_ = self
-			.controller
-			.set_text(self.configuration.as_str(), 1, 1)
-			.await;
-
-		// let mut profiling = None;
-		let mut i: usize = 0;
-		loop {
-			if let Ok(controller) = self.controller.state() {
-				// if controller.button_right.is_now_pressed() {
-				// 	profiling = Some(File::create("profile.txt").unwrap());
-				// }
-
-				// if let Some(file) = &mut profiling {
-				// 	file.write(format!("{},{}\n", controller.left_stick, ).as_bytes()).unwrap();
-				// }
-
-				if controller.button_up.is_now_pressed() {
-					self.configuration = self.configuration.next();
-					_ = self
-						.controller
-						.set_text(self.configuration.as_str(), 1, 1)
-						.await
-				}
-
-				if controller.button_y.is_now_pressed() {
-					let imu = self.imu.clone();
-					vexide::task::spawn(async move {
-						_ = imu.lock().await.calibrate().await;
-						println!("IMU calibrated");
-					})
-					.detach();
-				}
-
-				// Run subsystems
-				self.drivetrain.control(&controller, self.configuration);
-				self.coprocessor.control(&controller, self.configuration);
-				self.intake.control(&controller, self.configuration);
-				self.pneumatics.control(&controller, self.configuration);
-
-				self.replay.record(
-					&controller,
-					&[
-						("drivetrain", &self.drivetrain),
-						("intake", &self.intake),
-						("pneumatics", &self.pneumatics),
-					],
-				);
-			} else if i.is_multiple_of(100) {
-				// Only print every second
-				println!("Warning: controller not connected");
-			}
-
-			i = i.wrapping_add(1);
-			sleep(Duration::from_millis(10)).await;
-		}
-	}
-
-	async fn disabled(&mut self) {
-		println!("Disabled!")
-	}
-}
@@ -1,15 +1,13 @@
 mod autons;
-mod compete;
+mod config;
 mod control;
-mod robot;
 
+use robot::Robot;
 use vexide::prelude::*;
 
-use crate::robot::Robot;
-
 #[vexide::main]
 async fn main(peripherals: Peripherals) {
-	let robot = Robot::new(peripherals).await;
+	let robot = Robot::new(peripherals, config::config()).await;
 
-	robot.start().await;
+	robot.start(autons::DEFAULT_AUTON, autons::routes()).await;
 }
@@ -1,19 +1,20 @@
 use std::time::{Duration, Instant};
 
-use autons::{Selector, simple::Route};
+use autons::{route, simple::Route};
 use coprocessor::requests::GetStdDevRequest;
 use evian::{
 	control::loops::BangBang,
 	math::{Angle, Vec2},
 	motion::Basic,
-	prelude::{Arcade, Tank, TracksHeading, TracksPosition},
+	prelude::{TracksHeading, TracksPosition},
 };
+use robot::{Robot, auton::AutonRobot};
 use shrewnit::{Degrees, Inches};
-use subsystems::{intake::IntakeState, intake_unjamming, pnemuatics::PneumaticState};
+use subsystems::{
+	commands::autopark, intake::IntakeState, intake_unjamming, pnemuatics::PneumaticState,
+};
 use vexide::{smart::motor::BrakeMode, time::sleep};
 
-use crate::robot::Robot;
-
 const FIELD_TILE_LENGTH: f64 = 24.0;
 
 macro_rules! change_timeout {
@@ -25,17 +26,28 @@ macro_rules! change_timeout {
 	};
 }
 
+pub const DEFAULT_AUTON: &str = "Match auton matchload";
+
+/// Every auton, in the order they're listed on the selector
+pub fn routes() -> [Route<Robot>; 6] {
+	[
+		route!("Do nothing", do_nothing),
+		route!("Match auton matchload", match_auton_matchload),
+		route!("Match auton neutral", match_auton_neutral),
+		route!("Skills double park", skills_doublepark),
+		route!("Skills main", skills_main),
+		route!("Autopark test", autopark_test),
+	]
+}
+
 async fn bang_bang_angle(robot: &mut Robot, angle: Angle) {
 	while let error = (robot.drivetrain.tracking.heading().wrapped_full() - angle.wrapped_full())
 		&& error.abs() > Angle::from_degrees(0.25)
 	{
-		_ = robot
-			.drivetrain
-			.model
-			.drive_arcade(0.0, 0.13 * error.signum());
+		_ = robot.drivetrain.drive_arcade(0.0, 0.13 * error.signum());
 		sleep(Duration::from_millis(5)).await;
 	}
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	robot.drivetrain.brake(BrakeMode::Hold);
 }
 
@@ -44,17 +56,14 @@ pub async fn do_nothing(_robot: &mut Robot) {
 }
 
 pub async fn autopark_test(robot: &mut Robot) {
-	robot.intake.run_autopark().await;
+	robot.run_command(autopark(robot.intake.clone())).await;
 }
 
 pub async fn match_auton_matchload(robot: &mut Robot) {
 	let matchload_line = 28.0;
 
 	// Basic setup.
-	_ = robot
-		.pneumatics
-		.extender
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("extender", PneumaticState::Extended);
 
 	let mut basic = crate::control::BASIC_CONTROLLER;
 	let default_timeout = basic.timeout;
@@ -70,11 +79,11 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 	basic.timeout = default_timeout;
 
 	// Align with center
-	_ = robot.drivetrain.model.drive_arcade(0.25, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.25, 0.0);
 	sleep(Duration::from_millis(1000)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.15, 0.0);
 	sleep(Duration::from_millis(450)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	// Outtake preload ball into center goal
 	robot.intake.run(IntakeState {
@@ -106,20 +115,14 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 		.await;
 
 	// Back up as a precaution for the front bar
-	_ = robot.drivetrain.model.drive_arcade(-0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.15, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	// Intake from matchload
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
+	_ = robot.pneumatics.set("outtake_adjuster", PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
 	sleep(Duration::from_millis(500)).await;
 
 	robot.intake.run(IntakeState::full_forward());
@@ -130,16 +133,16 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 		.await;
 	basic.timeout = default_timeout;
 
-	_ = robot.drivetrain.model.drive_arcade(0.25, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.25, 0.0);
 	sleep(Duration::from_millis(2500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	sleep(Duration::from_millis(250)).await;
 
 	// Move to long goal position
 	robot.intake.run(IntakeState::full_brake());
-	_ = robot.drivetrain.model.drive_arcade(-0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.15, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	let long_goal_point = Vec2::new(28.0, 32.48);
 	let pos = robot.drivetrain.tracking.position();
 	let angle =
@@ -155,14 +158,11 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 			.drive_distance(&mut robot.drivetrain, -(dist - 1.0))
 			.await;
 	});
-	_ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.25, 0.0);
 
 	// Eject 3 balls into long goal
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("outtake_adjuster", PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
 	intake_unjamming!(
 		robot,
 		IntakeState {
@@ -174,7 +174,7 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 			sleep(Duration::from_millis(500)).await;
 		}
 	);
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	intake_unjamming!(
 		robot,
 		IntakeState {
@@ -189,10 +189,7 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 
 	// Back up and eject balls out of the intake
 	basic.drive_distance(&mut robot.drivetrain, 12.0).await;
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("outtake_adjuster", PneumaticState::Contracted);
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_millis(1500)).await;
 		bang_bang_angle(r, Angle::from_degrees(90.0 + 180.0)).await;
@@ -200,18 +197,15 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 	robot.intake.run(IntakeState::full_forward());
 
 	// Go back to matchload and grab balls
-	_ = robot
-		.pneumatics
-		.outtake_adjuster
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
-	_ = robot.drivetrain.model.drive_arcade(0.25, 0.0);
+	_ = robot.pneumatics.set("outtake_adjuster", PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
+	_ = robot.drivetrain.drive_arcade(0.25, 0.0);
 	sleep(Duration::from_millis(2500)).await;
 
 	// Score the balls on long goal
-	_ = robot.drivetrain.model.drive_arcade(-0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.15, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	let long_goal_point = Vec2::new(28.0, 32.48);
 	let pos = robot.drivetrain.tracking.position();
 	let angle =
@@ -227,11 +221,11 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 			.drive_distance(&mut robot.drivetrain, -(dist - 1.0))
 			.await;
 	});
-	_ = robot.drivetrain.model.drive_arcade(-0.25, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.25, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_secs(2)).await;
 	});
@@ -239,10 +233,7 @@ pub async fn match_auton_matchload(robot: &mut Robot) {
 
 pub async fn match_auton_neutral(robot: &mut Robot) {
 	// Basic setup.
-	_ = robot
-		.pneumatics
-		.extender
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("extender", PneumaticState::Extended);
 
 	let mut basic = crate::control::BASIC_CONTROLLER;
 	let default_timeout = basic.timeout;
@@ -269,30 +260,24 @@ pub async fn match_auton_neutral(robot: &mut Robot) {
 		.drive_distance_at_heading(&mut robot.drivetrain, dist, Angle::from_degrees(90.0))
 		.await;
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(180.0))
 		.await;
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 	sleep(Duration::from_millis(500)).await;
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(0.0))
 		.await;
 }
 
-pub async fn skills_doublepark(robot: &mut Robot) {
-	robot.intake.run(IntakeState::full_forward());
-	_ = robot.drivetrain.model.drive_arcade(0.4, 0.0);
-	sleep(Duration::from_millis(1250)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
-	sleep(Duration::from_secs(3)).await;
+pub async fn skills_doublepark(robot: &mut impl AutonRobot) {
+	robot.run_intake(IntakeState::full_forward());
+	_ = robot.drivetrain().drive_arcade(0.4, 0.0);
+	robot.sleep(Duration::from_millis(1250)).await;
+	_ = robot.drivetrain().drive_arcade(0.0, 0.0);
+	robot.sleep(Duration::from_secs(3)).await;
 }
 
 pub async fn skills_main(robot: &mut Robot) {
@@ -344,18 +329,15 @@ pub async fn skills_main(robot: &mut Robot) {
 	// Matchload
 	sleep(Duration::from_millis(500)).await;
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
-		_ = r.pneumatics.front_bar.set_state(PneumaticState::Extended);
-		_ = r.pneumatics.extender.set_state(PneumaticState::Extended);
-		_ = r.pneumatics.flap.set_state(PneumaticState::Contracted);
-		_ = r
-			.pneumatics
-			.outtake_adjuster
-			.set_state(PneumaticState::Extended);
+		_ = r.pneumatics.set("front_bar", PneumaticState::Extended);
+		_ = r.pneumatics.set("extender", PneumaticState::Extended);
+		_ = r.pneumatics.set("flap", PneumaticState::Contracted);
+		_ = r.pneumatics.set("outtake_adjuster", PneumaticState::Extended);
 		sleep(Duration::from_secs(1)).await;
 
-		_ = r.drivetrain.model.drive_arcade(0.25, 0.0);
+		_ = r.drivetrain.drive_arcade(0.25, 0.0);
 		sleep(Duration::from_secs(1)).await;
-		_ = r.drivetrain.model.drive_arcade(0.5, 0.0);
+		_ = r.drivetrain.drive_arcade(0.5, 0.0);
 		sleep(Duration::from_secs(2)).await;
 	});
 
@@ -373,10 +355,7 @@ pub async fn skills_main(robot: &mut Robot) {
 	basic.drive_distance(&mut robot.drivetrain, -dist).await;
 
 	// Pull up bar before turning
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 
 	// Turn towards balls
 	basic
@@ -393,14 +372,11 @@ pub async fn skills_main(robot: &mut Robot) {
 	robot.intake.run(IntakeState::full_brake());
 
 	// Put bar down and bring balls back from wall
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 	sleep(Duration::from_secs(1)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.1, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.1, 0.0);
 	sleep(Duration::from_secs(1)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	// Back up to get room for intaking balls
 	let back_up_x = FIELD_TILE_LENGTH * 0.75;
@@ -418,9 +394,9 @@ pub async fn skills_main(robot: &mut Robot) {
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(-35.0))
 		.await;
-	_ = robot.drivetrain.model.drive_arcade(0.35, 0.025);
+	_ = robot.drivetrain.drive_arcade(0.35, 0.025);
 	sleep(Duration::from_secs(2)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	// Drive to original side of field
 	let close_gutter_y = 36.0;
@@ -456,23 +432,20 @@ pub async fn skills_main(robot: &mut Robot) {
 	bang_bang_angle(robot, angle).await;
 
 	// Move to long goal
-	_ = robot.drivetrain.model.drive_arcade(-0.35, 0.00);
+	_ = robot.drivetrain.drive_arcade(-0.35, 0.00);
 	sleep(Duration::from_secs(2)).await;
-	_ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.0, 0.0);
 
 	// Outtake balls
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
 
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_millis(3500)).await;
 	});
 
 	// Move to matchload and intake balls
-	_ = robot.drivetrain.model.drive_arcade(0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.15, 0.0);
 	sleep(Duration::from_millis(500)).await;
 
 	let long_goal_point = Vec2::new(24.7, 8.0);
@@ -481,29 +454,29 @@ pub async fn skills_main(robot: &mut Robot) {
 		Angle::atan2(long_goal_point.y - pos.y, long_goal_point.x - pos.x) + Angle::from_turns(0.0);
 	bang_bang_angle(robot, angle).await;
 
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("flap", PneumaticState::Contracted);
 	sleep(Duration::from_secs(1)).await;
 
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
-		_ = r.drivetrain.model.drive_arcade(0.25, 0.0);
+		_ = r.drivetrain.drive_arcade(0.25, 0.0);
 		sleep(Duration::from_secs(1)).await;
-		_ = r.drivetrain.model.drive_arcade(0.5, 0.0);
+		_ = r.drivetrain.drive_arcade(0.5, 0.0);
 		sleep(Duration::from_secs(2)).await;
 	});
 
 	// Move to long goal and outtake balls
-	_ = robot.drivetrain.model.drive_tank(-0.15, 0.0);
+	_ = robot.drivetrain.drive_tank(-0.15, 0.0);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_tank(0.0, 0.0);
+	_ = robot.drivetrain.drive_tank(0.0, 0.0);
 	let long_goal_point = Vec2::new(25.5, 34.5);
 	let pos = robot.drivetrain.tracking.position();
 	let angle =
 		Angle::atan2(long_goal_point.y - pos.y, long_goal_point.x - pos.x) + Angle::from_turns(0.5);
 	bang_bang_angle(robot, angle).await;
 
-	_ = robot.drivetrain.model.drive_arcade(-0.35, 0.00);
+	_ = robot.drivetrain.drive_arcade(-0.35, 0.00);
 	sleep(Duration::from_secs(2)).await;
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
 
 	intake_unjamming!(robot, IntakeState::full_forward(), r => async {
 		sleep(Duration::from_secs(1)).await;
@@ -522,10 +495,7 @@ pub async fn skills_main(robot: &mut Robot) {
 		}
 	);
 
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 	basic.drive_distance(&mut robot.drivetrain, 14.0).await;
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(180.0))
@@ -538,30 +508,24 @@ pub async fn skills_main(robot: &mut Robot) {
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(-90.0))
 		.await;
 
-	_ = robot.drivetrain.model.drive_arcade(0.15, 0.0);
+	_ = robot.drivetrain.drive_arcade(0.15, 0.0);
 	sleep(Duration::from_millis(350)).await;
 	robot.intake.run(IntakeState::full_brake());
 	sleep(Duration::from_millis(500)).await;
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Extended);
-	_ = robot.pneumatics.flap.set_state(PneumaticState::Extended);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Extended);
+	_ = robot.pneumatics.set("flap", PneumaticState::Extended);
 	sleep(Duration::from_millis(1000)).await;
 
 	basic.drive_distance(&mut robot.drivetrain, -12.0).await;
 	basic
 		.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees(90.0))
 		.await;
-	_ = robot
-		.pneumatics
-		.front_bar
-		.set_state(PneumaticState::Contracted);
+	_ = robot.pneumatics.set("front_bar", PneumaticState::Contracted);
 	sleep(Duration::from_millis(500)).await;
-	_ = robot.drivetrain.model.drive_arcade(-0.7, 0.0);
+	_ = robot.drivetrain.drive_arcade(-0.7, 0.0);
 
 	println!("Time elapsed: {}s", start_time.elapsed().as_secs_f64());
-	// _ = robot.drivetrain.model.drive_arcade(0.0, 0.0);
+	// _ = robot.drivetrain.drive_arcade(0.0, 0.0);
 	// robot.intake.run(IntakeState::full_brake());
 
 	let stddev = robot.coprocessor.send_request(GetStdDevRequest).await;
@@ -579,24 +543,37 @@ pub async fn skills_main(robot: &mut Robot) {
 	sleep(Duration::from_secs(1000)).await;
 }
 
-#[allow(dead_code)]
-pub struct StubSelector<R> {
-	route: Option<Route<R>>,
-}
+#[cfg(test)]
+mod tests {
+	use sim::{Event, Mechanism, Sim, physics::DriveParams};
 
-#[allow(dead_code)]
-impl<R> StubSelector<R> {
-	pub fn new<const N: usize>(selected: &'static str, routes: [Route<R>; N]) -> Self {
-		Self {
-			route: routes.into_iter().find(|r| r.name == selected),
-		}
-	}
-}
+	use super::*;
 
-impl<R> Selector<R> for StubSelector<R> {
-	async fn run(&self, robot: &mut R) {
-		if let Some(route) = &self.route {
-			(route.callback)(robot).await;
-		}
+	#[test]
+	fn test_skills_doublepark() {
+		let sim = Sim::new(
+			DriveParams::default(),
+			Vec2::new(0.0, 0.0),
+			Angle::from_degrees(90.0),
+		);
+		let mut robot = sim.robot(crate::config::config().piston_names());
+		assert!(
+			sim.run(skills_doublepark(&mut robot), Duration::from_secs(15))
+				.is_some()
+		);
+
+		// 24in/s for 1.25s, less getting up to speed, plus coasting to a stop
+		let position = robot.drivetrain.tracking.position();
+		assert!(position.x.abs() < 1e-6, "{}", position.x);
+		assert!(position.y > 29.0 && position.y < 31.0, "{}", position.y);
+		assert!((robot.drivetrain.tracking.heading().as_degrees() - 90.0).abs() < 1e-6);
+		assert_eq!(sim.now(), Duration::from_millis(4250));
+		assert_eq!(
+			sim.events(),
+			[Event {
+				time: Duration::ZERO,
+				mechanism: Mechanism::Intake(IntakeState::full_forward()),
+			}]
+		);
 	}
 }
@@ -0,0 +1,140 @@
+use std::time::Duration;
+
+use coprocessor::requests::OtosPosition;
+use robot::config::{MotorPort, PistonConfig, PortMap, RobotConfig};
+use shrewnit::{Degrees, Inches};
+use subsystems::{
+	bindings::Action,
+	drivetrain::assist::DriverAssist,
+	intake::{jam::JamConfig, park::ParkConfig},
+	pnemuatics::{PneumaticState, air::AirTank},
+};
+use vexide::prelude::Gearset;
+
+const PORTS: PortMap = PortMap {
+	left_drive: &[
+		MotorPort::reverse(7),
+		MotorPort::forward(8),
+		MotorPort::forward(9),
+		MotorPort::reverse(10),
+	],
+	right_drive: &[
+		MotorPort::forward(1),
+		MotorPort::reverse(2),
+		MotorPort::reverse(3),
+		MotorPort::forward(4),
+	],
+	intake_bottom: &[MotorPort::reverse(20)],
+	intake_middle: &[MotorPort::forward(16)],
+	intake_top: &[MotorPort::forward(18), MotorPort::reverse(12)],
+	park_sensor: 17,
+	imu: 5,
+	coprocessor: 6,
+	park_piston: 'F',
+};
+
+const PISTONS: &[PistonConfig] = &[
+	PistonConfig {
+		name: "front_bar",
+		label: "Front bar",
+		port: 'B',
+		high_mode: PneumaticState::Extended,
+		setup: |piston| piston.toggle(Action::FrontBar),
+	},
+	PistonConfig {
+		name: "extender",
+		label: "Extender",
+		port: 'C',
+		high_mode: PneumaticState::Extended,
+		setup: |piston| piston.default_state(PneumaticState::Extended),
+	},
+	PistonConfig {
+		name: "flap",
+		label: "Flap",
+		port: 'A',
+		high_mode: PneumaticState::Contracted,
+		setup: |piston| {
+			piston
+				.disabled_state(PneumaticState::Extended)
+				.hold(Action::LongGoal, PneumaticState::Extended)
+				.hold(Action::CenterGoalTop, PneumaticState::Contracted)
+				.rest(PneumaticState::Contracted)
+				.settle(Duration::from_millis(250))
+		},
+	},
+	PistonConfig {
+		name: "outtake_adjuster",
+		label: "Outtake adjuster",
+		port: 'D',
+		high_mode: PneumaticState::Extended,
+		setup: |piston| {
+			piston
+				.default_state(PneumaticState::Extended)
+				.hold(Action::LongGoal, PneumaticState::Extended)
+				.hold(Action::CenterGoalTop, PneumaticState::Contracted)
+				.rest(PneumaticState::Extended)
+		},
+	},
+	PistonConfig {
+		name: "wing",
+		label: "Wing",
+		port: 'E',
+		high_mode: PneumaticState::Extended,
+		setup: |piston| {
+			piston
+				.default_state(PneumaticState::Extended)
+				.disabled_state(PneumaticState::Extended)
+				.toggle(Action::Wing)
+		},
+	},
+];
+
+pub fn config() -> RobotConfig {
+	RobotConfig {
+		ports: PORTS,
+		pistons: PISTONS,
+		gearset: Gearset::Blue,
+		otos_offset: OtosPosition {
+			x: 0.0 * Inches,
+			y: -1.25 * Inches,
+			heading: 0.0 * Degrees,
+		},
+		profile: "Connor",
+		assist: DriverAssist::new(
+			crate::control::BASIC_CONTROLLER,
+			crate::control::SEEKING_CONTROLLER,
+		),
+		calibration_leds: false,
+		compensation: None,
+		tank: AirTank::default(),
+		park: ParkConfig::default(),
+		jam: JamConfig::default(),
+		size: 15.0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_ports_are_valid() {
+		assert_eq!(config().wiring().validate(), Ok(()));
+	}
+
+	/// Regenerate `WIRING.md` with `UPDATE_WIRING=1 cargo test -p bot-white`
+	#[test]
+	fn test_wiring_doc_is_up_to_date() {
+		let path = concat!(env!("CARGO_MANIFEST_DIR"), "/WIRING.md");
+		let markdown = config().wiring().to_markdown("White bot wiring");
+
+		if std::env::var_os("UPDATE_WIRING").is_some() {
+			std::fs::write(path, &markdown).unwrap();
+		}
+		assert_eq!(
+			std::fs::read_to_string(path).unwrap_or_default(),
+			markdown,
+			"WIRING.md is out of date, regenerate it with UPDATE_WIRING=1"
+		);
+	}
+}
@@ -0,0 +1,82 @@
+//! What an auton needs from the robot, so autons written against [`AutonRobot`] rather than
+//! [`Robot`] can also run in the simulation. The simulation drives vexide's clock, so evian's
+//! motions and vexide's `sleep` work there too.
+
+use std::time::Duration;
+
+use evian::{
+	drivetrain::model::Differential,
+	prelude::{Arcade, Tank, TracksForwardTravel, TracksHeading, TracksPosition, TracksVelocity},
+	tracking::Tracking,
+};
+use subsystems::{
+	commands,
+	copro::tracking::CoproTracking,
+	drivetrain::DrivetrainSubsystem,
+	intake::IntakeState,
+	pnemuatics::{PneumaticError, PneumaticState},
+};
+use vexide::{
+	smart::{PortError, motor::BrakeMode},
+	time::sleep,
+};
+use vexide_commands::prelude::CommandExt as _;
+
+use crate::Robot;
+
+pub trait AutonRobot {
+	type Model: Tank + Arcade;
+	type Tracking: Tracking + TracksPosition + TracksHeading + TracksVelocity + TracksForwardTravel;
+
+	fn drivetrain(&mut self) -> &mut DrivetrainSubsystem<Self::Model, Self::Tracking>;
+
+	/// Stops the drivetrain with `mode`
+	fn brake(&mut self, mode: BrakeMode);
+
+	fn run_intake(&mut self, state: IntakeState);
+
+	/// Runs the intake at `state` for `duration`, backing stages off whenever they jam
+	fn unjam_intake(&mut self, state: IntakeState, duration: Duration) -> impl Future<Output = ()>;
+
+	/// Moves a piston in the bank by name
+	fn set_piston(&mut self, name: &str, state: PneumaticState) -> Result<(), PneumaticError>;
+
+	fn set_park_piston(&mut self, state: PneumaticState) -> Result<(), PortError>;
+
+	/// Waits for `duration`, on whichever clock the robot runs on
+	fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+impl AutonRobot for Robot {
+	type Model = Differential;
+	type Tracking = CoproTracking;
+
+	fn drivetrain(&mut self) -> &mut DrivetrainSubsystem<Differential, CoproTracking> {
+		&mut self.drivetrain
+	}
+
+	fn brake(&mut self, mode: BrakeMode) {
+		self.drivetrain.brake(mode);
+	}
+
+	fn run_intake(&mut self, state: IntakeState) {
+		self.intake.run(state);
+	}
+
+	async fn unjam_intake(&mut self, state: IntakeState, duration: Duration) {
+		let command = commands::unjam_intake(self.intake.clone(), state).with_timeout(duration);
+		self.run_command(command).await;
+	}
+
+	fn set_piston(&mut self, name: &str, state: PneumaticState) -> Result<(), PneumaticError> {
+		self.pneumatics.set(name, state)
+	}
+
+	fn set_park_piston(&mut self, state: PneumaticState) -> Result<(), PortError> {
+		self.intake.set_park_piston(state)
+	}
+
+	fn sleep(&self, duration: Duration) -> impl Future<Output = ()> {
+		sleep(duration)
+	}
+}
@@ -0,0 +1,217 @@
+use std::{
+	future::join,
+	time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use autons::prelude::SelectCompete;
+use subsystems::{
+	ControllableSubsystem,
+	bindings::{Action, Bindings},
+	copro::CoproSubsystem,
+	drivetrain::thermal::{ThermalEvent, ThermalEventKind},
+	faults::{Fault, FaultRecorder},
+	pnemuatics::PneumaticsSubsystem,
+	replay::pose::PoseRecorder,
+};
+use vexide::prelude::*;
+
+use crate::{AUTON_IN_DRIVER, Robot, screen::ScreenQueue};
+
+/// The active profile's name, padded to overwrite a longer one on the controller screen
+fn profile_label(bindings: &Bindings) -> String {
+	format!("{:<6}", bindings.active().name)
+}
+
+/// Shown after the profile name while the drivetrain is reversed
+fn reverse_label(reverse: bool) -> &'static str {
+	if reverse { "REV" } else { "   " }
+}
+
+/// The estimated air pressure, for the controller's last line
+fn air_label(pneumatics: &PneumaticsSubsystem) -> String {
+	let low = if pneumatics.is_low() { " LOW" } else { "    " };
+	format!("Air {:>3.0}psi{low}", pneumatics.pressure())
+}
+
+/// A new fault, for the controller's bottom line
+fn fault_label(fault: &Fault) -> String {
+	format!("{:<19.19}", format!("! {} {}", fault.subsystem, fault.device))
+}
+
+/// A drivetrain thermal warning, for the controller's bottom line
+fn thermal_label(event: &ThermalEvent) -> String {
+	let scale = event.scale * 100.0;
+	let text = match event.kind {
+		ThermalEventKind::Derating => format!("Drive warm {scale:.0}%"),
+		ThermalEventKind::Overheated => format!("DRIVE HOT {scale:.0}%"),
+		ThermalEventKind::Cooled => "Drive cooled".to_string(),
+		ThermalEventKind::HighCurrent => format!("Drive {} pushing", event.motor.port),
+	};
+	format!("{text:<19.19}")
+}
+
+/// Logs which partner actions stop working while it's disconnected
+fn warn_partner(bindings: &Bindings, connected: bool) {
+	if connected {
+		println!("Partner controller connected");
+		return;
+	}
+
+	let unavailable = bindings.active().unavailable_without_partner();
+	if unavailable.is_empty() {
+		println!("Partner controller disconnected, its actions are on the primary");
+	} else {
+		let names = unavailable.iter().map(|a| a.name()).collect::<Vec<_>>();
+		println!(
+			"Partner controller disconnected, these are inactive until it reconnects: {}",
+			names.join(", ")
+		);
+	}
+}
+
+impl SelectCompete for Robot {
+	async fn driver(&mut self) {
+		if AUTON_IN_DRIVER.is_some() && let Some(auton) = &self.default_auton {
+			let attempted_auton: anyhow::Result<Duration> = try {
+				self.coprocessor
+					.calibrate()
+					.await
+					.context("OTOS calibration failed")?;
+				self.imu
+					.lock()
+					.await
+					.calibrate()
+					.await
+					.context("IMU calibration failed")?;
+
+				sleep(Duration::from_millis(500)).await;
+
+				let start = Instant::now();
+				(auton.callback)(self).await;
+				start.elapsed()
+			};
+
+			match attempted_auton {
+				Ok(duration) => println!("Auton finished in {:.02}s", duration.as_secs_f64()),
+				Err(e) => println!("Auton failed: {:?}", e),
+			}
+			std::process::exit(0);
+		}
+
+		sleep(Duration::from_millis(100)).await;
+		_ = self.pneumatics.initialize().await;
+		// Anything an auton left running would fight the driver for the intake
+		self.scheduler.cancel_all();
+
+		println!("Driver!");
+		// Each write takes the controller ~50ms, so they're queued rather than waited on
+		let mut screen = ScreenQueue::default();
+		screen.set_text(profile_label(&self.bindings), 1, 1);
+		screen.set_text(reverse_label(self.drivetrain.reverse), 1, 8);
+		let mut air = air_label(&self.pneumatics);
+		let mut pressure = self.pneumatics.pressure();
+		screen.set_text(&air, 2, 1);
+		#[cfg(feature = "ui")]
+		self.ui.show_air(&self.pneumatics);
+
+		let mut i: usize = 0;
+		let mut partner_connected = false;
+		loop {
+			let partner = self.partner.state().ok();
+			if partner_connected != partner.is_some() {
+				partner_connected = partner.is_some();
+				warn_partner(&self.bindings, partner_connected);
+			}
+
+			if let Ok(controller) = self.controller.state() {
+				if self
+					.bindings
+					.input(&controller, partner.as_ref())
+					.is_now_pressed(Action::NextProfile)
+				{
+					self.bindings.next();
+					#[cfg(feature = "ui")]
+					self.ui.show_profile(self.bindings.active());
+					screen.set_text(profile_label(&self.bindings), 1, 1);
+				}
+				let input = self.bindings.input(&controller, partner.as_ref());
+
+				if input.is_now_pressed(Action::Calibrate) {
+					let imu = self.imu.clone();
+					let port = (*self.coprocessor).clone();
+					let offsets = self.coprocessor.offsets();
+					vexide::task::spawn(async move {
+						let (copro, imu) = join!(CoproSubsystem::setup(&port, offsets), async {
+							imu.lock().await.calibrate().await
+						})
+						.await;
+
+						match copro {
+							Ok(_) => println!("Coprocessor calibrated"),
+							Err(e) => println!("Unable to calibrate coprocessor: {e:?}"),
+						}
+						match imu {
+							Ok(()) => println!("IMU calibrated"),
+							Err(e) => println!("Unable to calibrate IMU: {e:?}"),
+						}
+					})
+					.detach();
+				}
+
+				// Run subsystems
+				self.drivetrain.control(&input);
+				for event in self.drivetrain.check_thermals() {
+					if event.kind != ThermalEventKind::Cooled {
+						screen.rumble("-");
+					}
+					screen.set_text(thermal_label(&event), 3, 1);
+				}
+				if input.is_now_pressed(Action::ToggleReverse) {
+					screen.set_text(reverse_label(self.drivetrain.reverse), 1, 8);
+				}
+				self.coprocessor.control(&input);
+				self.intake.control(&input);
+				self.pneumatics.control(&input);
+				self.scheduler.tick(Some(&input.held())).await;
+				if self.pneumatics.pressure() != pressure {
+					pressure = self.pneumatics.pressure();
+					#[cfg(feature = "ui")]
+					self.ui.show_air(&self.pneumatics);
+
+					let label = air_label(&self.pneumatics);
+					if label != air {
+						air = label;
+						screen.set_text(&air, 2, 1);
+					}
+				}
+				if let Some(fault) = self.faults.take_new().pop() {
+					screen.rumble(". .");
+					screen.set_text(fault_label(&fault), 3, 1);
+				}
+
+				self.replay.record(
+					&input,
+					&[
+						("drivetrain", &self.drivetrain),
+						("intake", &self.intake),
+						("pneumatics", &self.pneumatics),
+						("pose", &PoseRecorder(&self.drivetrain.tracking)),
+						("faults", &FaultRecorder(&self.faults)),
+					],
+				);
+			} else if i.is_multiple_of(100) {
+				// Only print every second
+				println!("Warning: controller not connected");
+			}
+
+			screen.flush(&mut self.controller);
+			i = i.wrapping_add(1);
+			sleep(Duration::from_millis(10)).await;
+		}
+	}
+
+	async fn disabled(&mut self) {
+		println!("Disabled!")
+	}
+}
@@ -0,0 +1,147 @@
+//! What differs between the bots: where everything is plugged in, and a few settings.
+
+use coprocessor::requests::OtosPosition;
+use evian_extra::voltage::VoltageCompensation;
+use subsystems::{
+	drivetrain::assist::DriverAssist,
+	intake::{jam::JamConfig, park::ParkConfig},
+	pnemuatics::{Piston, PneumaticState, air::AirTank},
+	wiring::{DeviceKind, Port, Wire, WiringTable},
+};
+use vexide::prelude::{Direction, Gearset};
+
+/// A motor's smart port, and which way it spins forward
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MotorPort {
+	pub port: u8,
+	pub direction: Direction,
+}
+
+impl MotorPort {
+	pub const fn forward(port: u8) -> Self {
+		Self {
+			port,
+			direction: Direction::Forward,
+		}
+	}
+
+	pub const fn reverse(port: u8) -> Self {
+		Self {
+			port,
+			direction: Direction::Reverse,
+		}
+	}
+}
+
+/// Where every device other than the [pistons](RobotConfig::pistons) is plugged in. Smart ports
+/// are numbers (1 to 21), ADI ports are the letter printed on the brain (`'A'` to `'H'`).
+///
+/// [`Robot::new`](crate::Robot::new) checks [`RobotConfig::wiring`] before using any ports, so a
+/// typo fails at startup with the devices involved rather than partway through.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PortMap {
+	pub left_drive: &'static [MotorPort],
+	pub right_drive: &'static [MotorPort],
+	pub intake_bottom: &'static [MotorPort],
+	pub intake_middle: &'static [MotorPort],
+	pub intake_top: &'static [MotorPort],
+	/// The optical sensor used for parking and sorting
+	pub park_sensor: u8,
+	pub imu: u8,
+	pub coprocessor: u8,
+	pub park_piston: char,
+}
+
+impl PortMap {
+	/// Every device and where it's plugged in, other than the pistons in
+	/// [`RobotConfig::pistons`]
+	fn wires(&self) -> impl Iterator<Item = Wire> {
+		let motors = [
+			("Left drive", self.left_drive),
+			("Right drive", self.right_drive),
+			("Intake bottom", self.intake_bottom),
+			("Intake middle", self.intake_middle),
+			("Intake top", self.intake_top),
+		]
+		.into_iter()
+		.flat_map(|(name, motors)| {
+			motors.iter().map(move |motor| {
+				Wire::new(
+					Port::Smart(motor.port),
+					name,
+					DeviceKind::Motor(motor.direction),
+				)
+			})
+		});
+
+		motors.chain([
+			Wire::new(
+				Port::Smart(self.park_sensor),
+				"Park sensor",
+				DeviceKind::Optical,
+			),
+			Wire::new(Port::Smart(self.imu), "IMU", DeviceKind::Imu),
+			Wire::new(
+				Port::Smart(self.coprocessor),
+				"Coprocessor",
+				DeviceKind::Coprocessor,
+			),
+			Wire::new(Port::Adi(self.park_piston), "Park piston", DeviceKind::Piston),
+		])
+	}
+}
+
+/// One piston in the pneumatics bank
+#[derive(Clone, Copy)]
+pub struct PistonConfig {
+	/// What autons and replays call it
+	pub name: &'static str,
+	/// What it's called in the wiring table
+	pub label: &'static str,
+	pub port: char,
+	/// The state it's in while its port is high
+	pub high_mode: PneumaticState,
+	/// Sets its states and bindings, see [`Piston`]
+	pub setup: fn(Piston) -> Piston,
+}
+
+/// Everything [`Robot::new`](crate::Robot::new) needs to know about a bot
+pub struct RobotConfig {
+	pub ports: PortMap,
+	/// The pistons in [`Robot::pneumatics`](crate::Robot::pneumatics), in order
+	pub pistons: &'static [PistonConfig],
+	/// The gearset of every drive and intake motor
+	pub gearset: Gearset,
+	/// Where the OTOS sits relative to the robot's center
+	pub otos_offset: OtosPosition,
+	/// The bindings profile selected at startup
+	pub profile: &'static str,
+	/// Heading hold and auto-align, tuned for this bot
+	pub assist: DriverAssist,
+	/// Whether the coprocessor's LEDs show how IMU calibration went
+	pub calibration_leds: bool,
+	/// Scales drivetrain and intake output against the battery's voltage, or `None` to run at
+	/// the fractions as given
+	pub compensation: Option<VoltageCompensation>,
+	/// The air tank feeding [`Self::pistons`]
+	pub tank: AirTank,
+	pub park: ParkConfig,
+	pub jam: JamConfig,
+	/// How big the robot is drawn on the odometry page, in inches
+	pub size: f64,
+}
+
+impl RobotConfig {
+	/// Every device and where it's plugged in
+	pub fn wiring(&self) -> WiringTable {
+		WiringTable::new(self.ports.wires().chain(self.pistons.iter().map(|piston| {
+			Wire::new(Port::Adi(piston.port), piston.label, DeviceKind::Piston)
+		})))
+	}
+
+	/// The names of [`Self::pistons`], which is all autons can set
+	pub fn piston_names(&self) -> impl Iterator<Item = &'static str> + use<> {
+		let pistons = self.pistons;
+		pistons.iter().map(|piston| piston.name)
+	}
+}
@@ -0,0 +1,321 @@
+//! The robot both bots share: building it from a [`RobotConfig`], the driver loop, and running
+//! autons. A bot crate is just its config and its autons.
+
+#![feature(future_join, try_blocks)]
+
+pub mod auton;
+mod compete;
+pub mod config;
+mod screen;
+pub mod selector;
+
+use std::{rc::Rc, time::Duration};
+
+use autons::{prelude::SelectCompeteExt as _, simple::Route};
+use coprocessor::requests::{CalibrateRequest, PingRequest, RAINBOW_ROTATE, SetLedsRequest};
+use evian::drivetrain::model::Differential;
+#[cfg(feature = "ui")]
+use slintui::{
+	OdometryPageState, UpdatedStatus, autons::SlintSelector, slint::ComponentHandle as _,
+	ui::RobotUi,
+};
+use subsystems::{
+	bindings::{Action, Bindings, HeldActions},
+	checks::DeviceCheck,
+	commands::{self, driver_autopark, driver_unjam},
+	copro::{CoproSubsystem, tracking::CoproTracking},
+	drivetrain::{
+		DrivetrainSubsystem,
+		thermal::{ThermalConfig, ThermalGuard},
+	},
+	faults::FaultRegistry,
+	intake::{IntakeMotors, IntakeSubsystem},
+	pnemuatics::{AdiPneumatic, Piston, PneumaticState, PneumaticsSubsystem},
+	replay::ReplaySubsystem,
+};
+use vexide::{peripherals::DynamicPeripherals, prelude::*, smart::SmartPort, sync::Mutex};
+use vexide_commands::prelude::{Command, Scheduler};
+use vexide_motorgroup::MotorGroup;
+
+use crate::config::{MotorPort, RobotConfig};
+#[cfg(not(feature = "ui"))]
+use crate::selector::StubSelector;
+
+/// Set at build time to run an auton as soon as driver control starts, for testing without a
+/// field controller. Never set this for a competition build.
+const AUTON_IN_DRIVER: Option<&str> = option_env!("DO_NOT_USE_AT_COMP_AUTON_TEST");
+
+/// How often [`Robot::run_command`] ticks the scheduler
+const COMMAND_INTERVAL: Duration = Duration::from_millis(10);
+
+pub struct Robot {
+	#[cfg(feature = "ui")]
+	pub ui: RobotUi,
+	pub coprocessor: CoproSubsystem,
+	pub controller: Controller,
+	pub partner: Controller,
+	pub bindings: Bindings,
+	pub drivetrain: DrivetrainSubsystem<Differential, CoproTracking>,
+	pub intake: IntakeSubsystem,
+	pub pneumatics: PneumaticsSubsystem,
+	pub replay: ReplaySubsystem,
+	pub faults: FaultRegistry,
+	/// Runs commands on the intake, ticked by the driver loop and [`Self::run_command`]. Its
+	/// triggers read the driver's actions.
+	pub scheduler: Scheduler<HeldActions>,
+	pub imu: Rc<Mutex<InertialSensor>>,
+	pub(crate) default_auton: Option<Route<Robot>>,
+}
+
+fn smart_port(peripherals: &mut DynamicPeripherals, port: u8) -> SmartPort {
+	peripherals
+		.take_smart_port(port)
+		.unwrap_or_else(|| panic!("Smart port {port} is used twice, or doesn't exist"))
+}
+
+/// `port` has been through [`WiringTable::validate`](subsystems::wiring::WiringTable::validate),
+/// so it's `'A'` to `'H'`
+fn adi_out(peripherals: &mut DynamicPeripherals, port: char) -> AdiDigitalOut {
+	let index = (port as u8).wrapping_sub(b'A') + 1;
+	let port = peripherals
+		.take_adi_port(index)
+		.unwrap_or_else(|| panic!("ADI port {port} is used twice, or doesn't exist"));
+
+	AdiDigitalOut::new(port)
+}
+
+fn motors(
+	peripherals: &mut DynamicPeripherals,
+	ports: &[MotorPort],
+	gearset: Gearset,
+) -> Vec<Motor> {
+	ports
+		.iter()
+		.map(|motor| Motor::new(smart_port(peripherals, motor.port), gearset, motor.direction))
+		.collect()
+}
+
+impl Robot {
+	pub async fn new(peripherals: Peripherals, config: RobotConfig) -> Self {
+		let ports = config.ports;
+		let gearset = config.gearset;
+		let wiring = config.wiring();
+		if let Err(errors) = wiring.validate() {
+			for error in &errors {
+				eprintln!("Wiring: {error}");
+			}
+			panic!("The port map has {} problems, see above", errors.len());
+		}
+		let mut peripherals = DynamicPeripherals::new(peripherals);
+
+		let coprocessor = CoproSubsystem::new(
+			smart_port(&mut peripherals, ports.coprocessor),
+			config.otos_offset,
+		)
+		.await;
+		let imu = Rc::new(Mutex::new(InertialSensor::new(smart_port(
+			&mut peripherals,
+			ports.imu,
+		))));
+
+		{
+			let imu = imu.clone();
+			let copro = coprocessor.clone();
+			let leds = config.calibration_leds;
+			vexide::task::spawn(async move {
+				if imu.lock().await.calibrate().await.is_ok() {
+					println!("IMU calibrated");
+					if leds {
+						_ = copro
+							.send_request(SetLedsRequest::<{ RAINBOW_ROTATE }>)
+							.await;
+					}
+				} else {
+					println!("IMU calibration failed");
+					if leds {
+						_ = copro.send_request(SetLedsRequest::<0xFF0000>).await;
+					}
+				}
+			})
+			.detach();
+		}
+
+		#[cfg(feature = "ui")]
+		let ui = {
+			let display = peripherals.take_display().expect("Display was already taken");
+			let coprocessor_clone = coprocessor.clone();
+			let imu_clone = imu.clone();
+			let ui = RobotUi::new(display, coprocessor.data().clone(), move || {
+				let coprocessor_clone = coprocessor_clone.clone();
+				let imu_clone = imu_clone.clone();
+				vexide::task::spawn(async move {
+					use std::future::join;
+
+					_ = join!(coprocessor_clone.send_request(CalibrateRequest), async {
+						imu_clone.lock().await.calibrate().await
+					})
+					.await;
+				})
+				.detach()
+			});
+			ui.app()
+				.global::<OdometryPageState>()
+				.set_bot_size(config.size as f32);
+			ui.show_wiring(&wiring);
+
+			if let Ok(hash) = coprocessor.send_request(PingRequest).await {
+				if PingRequest::verify_hash(hash) {
+					ui.app()
+						.global::<OdometryPageState>()
+						.set_updated_status(UpdatedStatus::Updated);
+				} else {
+					ui.app()
+						.global::<OdometryPageState>()
+						.set_updated_status(UpdatedStatus::Outdated);
+				}
+			}
+
+			ui.on_device_check({
+				let devices = Rc::<[_]>::from(wiring.expected_devices());
+				let coprocessor = coprocessor.clone();
+				let imu = imu.clone();
+				move || {
+					let devices = devices.clone();
+					let coprocessor = coprocessor.clone();
+					let imu = imu.clone();
+					async move { DeviceCheck::run(&devices, &imu, &coprocessor).await }
+				}
+			});
+
+			ui
+		};
+
+		let controller = peripherals
+			.take_primary_controller()
+			.expect("Primary controller was already taken");
+		let partner = peripherals
+			.take_partner_controller()
+			.expect("Partner controller was already taken");
+		let mut bindings = Bindings::load_or_default();
+		bindings.select(config.profile);
+		#[cfg(feature = "ui")]
+		ui.show_profile(bindings.active());
+		let faults = FaultRegistry::new();
+		#[cfg(feature = "ui")]
+		ui.track_faults(faults.clone());
+		let mut drivetrain = DrivetrainSubsystem::new(
+			Differential::new(
+				motors(&mut peripherals, ports.left_drive, gearset),
+				motors(&mut peripherals, ports.right_drive, gearset),
+			),
+			CoproTracking::new(coprocessor.data().clone(), imu.clone()),
+		);
+		drivetrain.assist = Some(config.assist);
+		drivetrain.thermal = Some(ThermalGuard::new(ThermalConfig::default()));
+		drivetrain.compensation = config.compensation;
+		drivetrain.faults = faults.clone();
+		let intake = IntakeSubsystem::new(
+			IntakeMotors {
+				bottom: MotorGroup::new(motors(&mut peripherals, ports.intake_bottom, gearset)),
+				middle: MotorGroup::new(motors(&mut peripherals, ports.intake_middle, gearset)),
+				top: MotorGroup::new(motors(&mut peripherals, ports.intake_top, gearset)),
+			},
+			OpticalSensor::new(smart_port(&mut peripherals, ports.park_sensor)),
+			AdiPneumatic::new(
+				adi_out(&mut peripherals, ports.park_piston),
+				PneumaticState::Extended,
+			),
+		);
+		#[cfg(feature = "ui")]
+		ui.track_intake(intake.clone());
+		intake.set_faults(faults.clone());
+		intake.set_voltage_compensation(config.compensation);
+		intake.set_park_config(config.park);
+		intake.set_jam_config(config.jam);
+		let mut scheduler = Scheduler::new();
+		scheduler.while_true(commands::action(Action::Unjam), {
+			let intake = intake.clone();
+			move || driver_unjam(intake.clone())
+		});
+		scheduler.toggle_on_true(commands::action(Action::AutoPark), {
+			let intake = intake.clone();
+			move || driver_autopark(intake.clone())
+		});
+
+		let pistons = config
+			.pistons
+			.iter()
+			.map(|piston| {
+				(piston.setup)(Piston::new(
+					piston.name,
+					AdiPneumatic::new(adi_out(&mut peripherals, piston.port), piston.high_mode),
+				))
+			})
+			.collect::<Vec<_>>();
+		let pneumatics = PneumaticsSubsystem::new(pistons)
+			.with_tank(config.tank)
+		.with_faults(faults.clone());
+		let replay = ReplaySubsystem::new();
+
+		// Catch anything plugged into the wrong port now, rather than when it doesn't move
+		let mut check = DeviceCheck::default();
+		for device in wiring.expected_devices() {
+			check.port(&device);
+		}
+		for item in check.failures() {
+			faults.record("wiring", &item.name, format_args!("{}", item.detail));
+		}
+
+		Self {
+			#[cfg(feature = "ui")]
+			ui,
+			imu,
+			controller,
+			partner,
+			bindings,
+			drivetrain,
+			intake,
+			pneumatics,
+			replay,
+			faults,
+			scheduler,
+			coprocessor,
+			default_auton: None,
+		}
+	}
+
+	/// Runs `command` until it finishes, for autons. Anything it conflicts with is interrupted.
+	pub async fn run_command(&mut self, command: impl Command + 'static) {
+		let id = self.scheduler.schedule(command);
+		while self.scheduler.is_scheduled(id) {
+			self.scheduler.tick(None).await;
+			sleep(COMMAND_INTERVAL).await;
+		}
+	}
+
+	/// Runs the robot through a match, with `autons` to pick from (`default_auton` selected to
+	/// start with)
+	pub async fn start<const N: usize>(
+		mut self,
+		default_auton: &'static str,
+		autons: [Route<Robot>; N],
+	) -> ! {
+		let default_auton = AUTON_IN_DRIVER.unwrap_or(default_auton);
+		self.default_auton = autons.iter().find(|a| a.name == default_auton).cloned();
+
+		#[cfg(feature = "ui")]
+		{
+			let ui = self.ui.clone();
+
+			let selector = SlintSelector::new(ui.app(), default_auton, autons);
+			vexide::task::spawn(self.compete(selector)).detach();
+			ui.run_blocking();
+		}
+		#[cfg(not(feature = "ui"))]
+		{
+			self.compete(StubSelector::new(default_auton, autons)).await;
+		};
+
+		std::process::exit(0);
+	}
+}
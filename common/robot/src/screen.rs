@@ -0,0 +1,143 @@
+//! Writes to the controller's screen without holding up the driver loop.
+
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+
+use vexide::prelude::Controller;
+
+/// The controller only takes a write (text or rumble) this often, and waiting on one blocks
+const WRITE_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum ScreenWrite {
+	Text {
+		line: u8,
+		column: u8,
+		text: String,
+	},
+	Rumble(&'static str),
+}
+
+/// Controller writes waiting their turn, made one at a time by [`Self::flush`].
+///
+/// Only the latest text for each spot on the screen is kept, so a label that changes faster than
+/// it can be written doesn't build up a backlog.
+#[derive(Default)]
+pub struct ScreenQueue {
+	text: VecDeque<ScreenWrite>,
+	rumble: Option<&'static str>,
+	last_write: Option<Instant>,
+}
+
+impl ScreenQueue {
+	pub fn set_text(&mut self, text: impl Into<String>, line: u8, column: u8) {
+		let text = text.into();
+		let queued = self.text.iter_mut().find_map(|write| match write {
+			ScreenWrite::Text {
+				line: l,
+				column: c,
+				text: queued,
+			} if (*l, *c) == (line, column) => Some(queued),
+			_ => None,
+		});
+
+		match queued {
+			Some(queued) => *queued = text,
+			None => self.text.push_back(ScreenWrite::Text { line, column, text }),
+		}
+	}
+
+	pub fn rumble(&mut self, pattern: &'static str) {
+		self.rumble = Some(pattern);
+	}
+
+	/// Takes the next write to make, if it's been long enough since the last one. Rumbles go
+	/// first, since they're what gets the driver to look.
+	fn pop(&mut self, now: Instant) -> Option<ScreenWrite> {
+		if self
+			.last_write
+			.is_some_and(|last| now - last < WRITE_INTERVAL)
+		{
+			return None;
+		}
+
+		let write = self
+			.rumble
+			.take()
+			.map(ScreenWrite::Rumble)
+			.or_else(|| self.text.pop_front())?;
+		self.last_write = Some(now);
+		Some(write)
+	}
+
+	/// Puts back a write the controller turned away, to try again next time
+	fn retry(&mut self, write: ScreenWrite) {
+		match write {
+			ScreenWrite::Rumble(pattern) => {
+				self.rumble.get_or_insert(pattern);
+			}
+			ScreenWrite::Text { .. } => self.text.push_front(write),
+		}
+	}
+
+	/// Makes the next write if the controller's ready for it, without waiting
+	pub fn flush(&mut self, controller: &mut Controller) {
+		let Some(write) = self.pop(Instant::now()) else {
+			return;
+		};
+
+		let result = match &write {
+			ScreenWrite::Text { line, column, text } => controller.try_set_text(text, *line, *column),
+			ScreenWrite::Rumble(pattern) => controller.try_rumble(pattern),
+		};
+		if result.is_err() {
+			self.retry(write);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn text(line: u8, text: &str) -> ScreenWrite {
+		ScreenWrite::Text {
+			line,
+			column: 1,
+			text: text.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_keeps_latest_text() {
+		let mut queue = ScreenQueue::default();
+		let now = Instant::now();
+
+		queue.set_text("Air 100psi", 2, 1);
+		queue.set_text("! intake top", 3, 1);
+		queue.set_text("Air  95psi", 2, 1);
+
+		assert_eq!(queue.pop(now), Some(text(2, "Air  95psi")));
+		assert_eq!(queue.pop(now + WRITE_INTERVAL), Some(text(3, "! intake top")));
+		assert_eq!(queue.pop(now + WRITE_INTERVAL * 2), None);
+	}
+
+	#[test]
+	fn test_one_write_per_interval() {
+		let mut queue = ScreenQueue::default();
+		let now = Instant::now();
+
+		queue.set_text("Noah", 1, 1);
+		queue.rumble(". .");
+
+		assert_eq!(queue.pop(now), Some(ScreenWrite::Rumble(". .")));
+		assert_eq!(queue.pop(now + Duration::from_millis(10)), None);
+
+		let write = queue.pop(now + WRITE_INTERVAL).unwrap();
+		assert_eq!(write, text(1, "Noah"));
+		queue.retry(write);
+		assert_eq!(queue.pop(now + WRITE_INTERVAL * 2), Some(text(1, "Noah")));
+	}
+}
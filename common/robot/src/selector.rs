@@ -0,0 +1,22 @@
+use autons::{Selector, simple::Route};
+
+/// Always runs the same route, for when there's no screen to pick one on
+pub struct StubSelector<R> {
+	route: Option<Route<R>>,
+}
+
+impl<R> StubSelector<R> {
+	pub fn new<const N: usize>(selected: &'static str, routes: [Route<R>; N]) -> Self {
+		Self {
+			route: routes.into_iter().find(|r| r.name == selected),
+		}
+	}
+}
+
+impl<R> Selector<R> for StubSelector<R> {
+	async fn run(&self, robot: &mut R) {
+		if let Some(route) = &self.route {
+			(route.callback)(robot).await;
+		}
+	}
+}
@@ -6,6 +6,7 @@ use std::{
 use evian::control::loops::Feedback;
 use plotters::{backend::RGBPixel, prelude::*};
 use slint::{ComponentHandle, Image, Rgb8Pixel, SharedPixelBuffer};
+use subsystems::drivetrain::shaping::Shaping;
 
 use crate::App;
 
@@ -148,3 +149,44 @@ pub(crate) fn draw_plot<T: Clone + Into<f64>>(
 
 	Ok(Image::from_rgb8(buf))
 }
+
+/// Plots stick input against shaped output, with turning at full throttle if it's scaled down
+pub(crate) fn draw_curve(width: u32, height: u32, shaping: &Shaping) -> anyhow::Result<Image> {
+	const SAMPLES: i32 = 100;
+
+	let graph_size = (width, height);
+	let mut buf = SharedPixelBuffer::<Rgb8Pixel>::new(graph_size.0, graph_size.1);
+	let inputs = (-SAMPLES..=SAMPLES).map(|i| i as f64 / SAMPLES as f64);
+
+	{
+		let root =
+			BitMapBackend::<RGBPixel>::with_buffer_and_format(buf.make_mut_bytes(), graph_size)?
+				.into_drawing_area();
+
+		root.fill(&WHITE)?;
+
+		let mut chart = ChartBuilder::on(&root)
+			.margin(5)
+			.x_label_area_size(20)
+			.y_label_area_size(30)
+			.build_cartesian_2d(-1.0..1.0, -1.0..1.0)?;
+
+		chart.configure_mesh().draw()?;
+
+		chart.draw_series(LineSeries::new(inputs.clone().map(|x| (x, x)), &BLUE))?;
+		chart.draw_series(LineSeries::new(
+			inputs.clone().map(|x| (x, shaping.stick(x))),
+			&RED,
+		))?;
+		if shaping.turn_scaling > 0.0 {
+			chart.draw_series(LineSeries::new(
+				inputs.map(|x| (x, shaping.arcade(1.0, x).1)),
+				&GREEN,
+			))?;
+		}
+
+		root.present()?;
+	}
+
+	Ok(Image::from_rgb8(buf))
+}
@@ -1,12 +1,24 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::Instant};
 
 use plotters::style::FontStyle;
 use shrewnit::{Degrees, DegreesPerSecond, FeetPerSecond, Inches};
-use slint::ComponentHandle as _;
-use subsystems::copro::CoproData;
+use slint::{ComponentHandle as _, ModelRc, SharedString, VecModel};
+use subsystems::{
+	bindings::DriverProfile,
+	checks::DeviceCheck,
+	copro::CoproData,
+	faults::FaultRegistry,
+	intake::{IntakeSubsystem, sorting::Alliance},
+	pnemuatics::{PneumaticState, PneumaticsSubsystem},
+	wiring::{Port, WiringTable},
+};
 use vexide::{prelude::*, task::Task};
 
-use crate::{App, FONT, OdometryPageState, Pose};
+use crate::{
+	AirPageState, App, CheckRow, ChecksPageState, CurvesPageState, FONT, FaultRow,
+	FaultsPageState, IntakePageState, OdometryPageState, PistonUsage, Pose, SortColor,
+	WiringPageState, WiringRow, plotting::draw_curve,
+};
 
 pub struct RobotUi {
 	app: App,
@@ -51,6 +63,200 @@ impl RobotUi {
 		&self.app
 	}
 
+	/// Shows a profile's input shaping on the curves page
+	pub fn show_profile(&self, profile: &DriverProfile) {
+		let state = self.app.global::<CurvesPageState>();
+		let shaping = &profile.shaping;
+
+		state.set_profile(profile.name.as_str().into());
+		state.set_summary(
+			format!(
+				"  Deadband: {:.2}\n  Curve: {} ({:.2})\n  Slew: {}\n  Turn scaling: {:.2}",
+				shaping.deadband,
+				shaping.curve.name(),
+				shaping.curve_strength,
+				shaping
+					.slew_rate
+					.map_or("none".to_string(), |rate| format!("{rate:.1}/s")),
+				shaping.turn_scaling,
+			)
+			.into(),
+		);
+
+		if let Ok(plot) = draw_curve(
+			state.get_curve_width() as u32,
+			state.get_curve_height() as u32,
+			shaping,
+		) {
+			state.set_curve_source(plot);
+		}
+	}
+
+	/// Shows the intake's block counts on the intake page, and lets it pick the color to keep
+	pub fn track_intake(&self, intake: IntakeSubsystem) {
+		let state = self.app.global::<IntakePageState>();
+
+		state.set_keep(match intake.alliance() {
+			None => SortColor::Off,
+			Some(Alliance::Red) => SortColor::Red,
+			Some(Alliance::Blue) => SortColor::Blue,
+		});
+		state.on_keep_changed({
+			let intake = intake.clone();
+			move |color| {
+				intake.set_alliance(match color {
+					SortColor::Off => None,
+					SortColor::Red => Some(Alliance::Red),
+					SortColor::Blue => Some(Alliance::Blue),
+				})
+			}
+		});
+		state.on_reset_counts({
+			let intake = intake.clone();
+			move || intake.reset_counts()
+		});
+
+		let app = self.app.clone_strong();
+		vexide::task::spawn(async move {
+			loop {
+				let counts = intake.counts();
+				let state = app.global::<IntakePageState>();
+
+				state.set_total(counts.total as i32);
+				state.set_red(counts.red as i32);
+				state.set_blue(counts.blue as i32);
+				state.set_ejected(counts.ejected as i32);
+
+				sleep(Display::REFRESH_INTERVAL).await
+			}
+		})
+		.detach();
+	}
+
+	/// Shows the estimated air pressure and each piston's actuations on the air page
+	pub fn show_air(&self, pneumatics: &PneumaticsSubsystem) {
+		let state = self.app.global::<AirPageState>();
+
+		state.set_pressure(pneumatics.pressure() as f32);
+		state.set_full_pressure(pneumatics.tank.pressure as f32);
+		state.set_low(pneumatics.is_low());
+		state.set_pistons(
+			Rc::new(
+				pneumatics
+					.names()
+					.filter_map(|name| {
+						let piston = pneumatics.get(name)?;
+						Some(PistonUsage {
+							name: name.into(),
+							actuations: piston.actuations() as i32,
+							extended: piston.state().ok() == Some(PneumaticState::Extended),
+						})
+					})
+					.collect::<VecModel<_>>(),
+			)
+			.into(),
+		);
+	}
+
+	/// Lists every device that's had errors on the faults page, highlighting ones still failing
+	pub fn track_faults(&self, faults: FaultRegistry) {
+		self.app.global::<FaultsPageState>().on_clear({
+			let faults = faults.clone();
+			move || faults.clear()
+		});
+
+		let app = self.app.clone_strong();
+		vexide::task::spawn(async move {
+			let mut shown = None;
+			loop {
+				let now = Instant::now();
+				let rows = faults
+					.faults()
+					.iter()
+					.map(|fault| (fault.to_string(), fault.is_active(now)))
+					.collect::<Vec<_>>();
+
+				if shown.as_ref() != Some(&rows) {
+					app.global::<FaultsPageState>().set_faults(
+						Rc::new(
+							rows.iter()
+								.map(|(text, active)| FaultRow {
+									text: text.as_str().into(),
+									active: *active,
+								})
+								.collect::<VecModel<_>>(),
+						)
+						.into(),
+					);
+					shown = Some(rows);
+				}
+
+				sleep(Display::REFRESH_INTERVAL).await
+			}
+		})
+		.detach();
+	}
+
+	/// Runs `check` when the checks page's button is pressed, showing the results once it's done
+	pub fn on_device_check<F: Future<Output = DeviceCheck> + 'static>(
+		&self,
+		check: impl Fn() -> F + 'static,
+	) {
+		let app = self.app.as_weak();
+		self.app.global::<ChecksPageState>().on_run(move || {
+			let Some(app) = app.upgrade() else {
+				return;
+			};
+			let state = app.global::<ChecksPageState>();
+			if state.get_running() {
+				return;
+			}
+			state.set_running(true);
+
+			let check = check();
+			vexide::task::spawn(async move {
+				let results = check.await;
+				show_device_check(&app, &results);
+			})
+			.detach();
+		});
+	}
+
+	/// Shows where everything is plugged in on the wiring page, along with anything wrong with it
+	pub fn show_wiring(&self, wiring: &WiringTable) {
+		let state = self.app.global::<WiringPageState>();
+		let rows = |smart: bool| -> ModelRc<WiringRow> {
+			Rc::new(
+				wiring
+					.sorted()
+					.iter()
+					.filter(|wire| matches!(wire.port, Port::Smart(_)) == smart)
+					.map(|wire| WiringRow {
+						port: wire.port.to_string().into(),
+						device: wire.device.into(),
+						kind: wire.kind.to_string().into(),
+					})
+					.collect::<VecModel<_>>(),
+			)
+			.into()
+		};
+
+		state.set_smart(rows(true));
+		state.set_adi(rows(false));
+		state.set_problems(
+			Rc::new(
+				wiring
+					.validate()
+					.err()
+					.unwrap_or_default()
+					.iter()
+					.map(|error| SharedString::from(error.to_string()))
+					.collect::<VecModel<_>>(),
+			)
+			.into(),
+		);
+	}
+
 	pub fn run_blocking(&self) {
 		self.app.run().expect("Failed to run application");
 	}
@@ -78,3 +284,32 @@ impl RobotUi {
 		}
 	}
 }
+
+fn show_device_check(app: &App, check: &DeviceCheck) {
+	let state = app.global::<ChecksPageState>();
+	let failures = check.failures().count();
+
+	state.set_running(false);
+	state.set_summary(
+		match failures {
+			0 => "All good".to_string(),
+			1 => "1 problem".to_string(),
+			n => format!("{n} problems"),
+		}
+		.into(),
+	);
+	state.set_rows(
+		Rc::new(
+			check
+				.items
+				.iter()
+				.map(|item| CheckRow {
+					name: item.name.as_str().into(),
+					passed: item.passed,
+					detail: item.detail.as_str().into(),
+				})
+				.collect::<VecModel<_>>(),
+		)
+		.into(),
+	);
+}
@@ -0,0 +1,1106 @@
+//! Maps named driver actions to controller buttons and sticks, per driver profile.
+//!
+//! Profiles are loaded from an INI-like file on the SD card:
+//!
+//! ```ini
+//! # Anything not listed keeps its default binding
+//! [Noah]
+//! drive = arcade
+//! throttle = left_y
+//! steer = right_x
+//! wing = down
+//! deadband = 0.05
+//! curve = cubic
+//! curve_strength = 0.6
+//! # Hold shift (up) and press left
+//! snap_heading = shift.left
+//!
+//! [Connor]
+//! drive = tank
+//! right = -right_y
+//! replay_stop = none
+//! # Full power after 0.25s
+//! slew = 4.0
+//! # Hand the mechanisms to the partner controller
+//! intake = partner.r1
+//! long_goal = partner.l1
+//! ```
+//!
+//! Two actions on the same button in a profile is an error at load time, rather than something
+//! found out mid-match.
+//!
+//! Holding the `shift` button (up, unless a profile moves it) switches to a second layer of
+//! bindings, written `shift.x`. While it's held, a button with something on that layer only does
+//! that, so the layer can reuse buttons that already have an action.
+//!
+//! If the partner controller disconnects, its actions are read from the same button on the
+//! primary controller instead, unless the primary already uses that button for something else.
+//! Those actions are inactive until the partner reconnects.
+
+use std::{collections::HashMap, fmt::Display, fs, io, ops::RangeInclusive};
+
+use vexide::controller::{ButtonState, ControllerState};
+
+use crate::drivetrain::shaping::{Curve, Shaping};
+
+/// The file profiles are loaded from
+pub const BINDINGS_PATH: &str = "bindings.ini";
+
+/// Something the driver can do with a button
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+	Intake,
+	LongGoal,
+	CenterGoalTop,
+	CenterGoalBottom,
+	AutoPark,
+	ManualPark,
+	Unjam,
+	FrontBar,
+	Wing,
+	DisablePneumatics,
+	NextProfile,
+	Calibrate,
+	ReplayStop,
+	HeadingHold,
+	SnapHeading,
+	SavePose,
+	DriveToPose,
+	ToggleReverse,
+	/// Held to reach the `shift.` bindings
+	Shift,
+}
+
+impl Action {
+	pub const ALL: [Action; 19] = [
+		Action::Intake,
+		Action::LongGoal,
+		Action::CenterGoalTop,
+		Action::CenterGoalBottom,
+		Action::AutoPark,
+		Action::ManualPark,
+		Action::Unjam,
+		Action::FrontBar,
+		Action::Wing,
+		Action::DisablePneumatics,
+		Action::NextProfile,
+		Action::Calibrate,
+		Action::ReplayStop,
+		Action::HeadingHold,
+		Action::SnapHeading,
+		Action::SavePose,
+		Action::DriveToPose,
+		Action::ToggleReverse,
+		Action::Shift,
+	];
+
+	/// This action's bit in [`HeldActions`]
+	fn bit(self) -> u32 {
+		1 << self as u32
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Action::Intake => "intake",
+			Action::LongGoal => "long_goal",
+			Action::CenterGoalTop => "center_goal_top",
+			Action::CenterGoalBottom => "center_goal_bottom",
+			Action::AutoPark => "auto_park",
+			Action::ManualPark => "manual_park",
+			Action::Unjam => "unjam",
+			Action::FrontBar => "front_bar",
+			Action::Wing => "wing",
+			Action::DisablePneumatics => "disable_pneumatics",
+			Action::NextProfile => "next_profile",
+			Action::Calibrate => "calibrate",
+			Action::ReplayStop => "replay_stop",
+			Action::HeadingHold => "heading_hold",
+			Action::SnapHeading => "snap_heading",
+			Action::SavePose => "save_pose",
+			Action::DriveToPose => "drive_to_pose",
+			Action::ToggleReverse => "toggle_reverse",
+			Action::Shift => "shift",
+		}
+	}
+
+	/// The button this action is on unless a profile says otherwise
+	pub fn default_binding(self) -> ButtonBinding {
+		use {Button::*, Side::*};
+
+		let (side, shifted, button) = match self {
+			Action::Intake => (Primary, false, R1),
+			Action::LongGoal => (Primary, false, L1),
+			Action::CenterGoalTop => (Primary, false, L2),
+			Action::CenterGoalBottom => (Primary, false, R2),
+			Action::AutoPark => (Primary, false, Left),
+			Action::ManualPark => (Primary, false, Right),
+			Action::Unjam => (Primary, false, X),
+			Action::FrontBar => (Primary, false, B),
+			Action::Wing => (Primary, false, Down),
+			Action::DisablePneumatics => (Primary, false, Power),
+			Action::Calibrate => (Primary, false, Y),
+			Action::ToggleReverse => (Primary, false, A),
+			Action::Shift => (Primary, false, Up),
+			// Every button on the primary is taken, so the rest go on the shift layer
+			Action::NextProfile => (Primary, true, L1),
+			Action::HeadingHold => (Primary, true, R1),
+			Action::SnapHeading => (Primary, true, A),
+			Action::SavePose => (Primary, true, B),
+			Action::DriveToPose => (Primary, true, Y),
+			// Where it was before it had a binding, without unjamming as well
+			Action::ReplayStop => (Primary, true, X),
+		};
+
+		ButtonBinding {
+			side,
+			shifted,
+			button,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Button {
+	A,
+	B,
+	X,
+	Y,
+	Up,
+	Down,
+	Left,
+	Right,
+	L1,
+	L2,
+	R1,
+	R2,
+	Power,
+}
+
+impl Button {
+	pub const ALL: [Button; 13] = [
+		Button::A,
+		Button::B,
+		Button::X,
+		Button::Y,
+		Button::Up,
+		Button::Down,
+		Button::Left,
+		Button::Right,
+		Button::L1,
+		Button::L2,
+		Button::R1,
+		Button::R2,
+		Button::Power,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Button::A => "a",
+			Button::B => "b",
+			Button::X => "x",
+			Button::Y => "y",
+			Button::Up => "up",
+			Button::Down => "down",
+			Button::Left => "left",
+			Button::Right => "right",
+			Button::L1 => "l1",
+			Button::L2 => "l2",
+			Button::R1 => "r1",
+			Button::R2 => "r2",
+			Button::Power => "power",
+		}
+	}
+
+	pub fn state(self, controller: &ControllerState) -> &ButtonState {
+		match self {
+			Button::A => &controller.button_a,
+			Button::B => &controller.button_b,
+			Button::X => &controller.button_x,
+			Button::Y => &controller.button_y,
+			Button::Up => &controller.button_up,
+			Button::Down => &controller.button_down,
+			Button::Left => &controller.button_left,
+			Button::Right => &controller.button_right,
+			Button::L1 => &controller.button_l1,
+			Button::L2 => &controller.button_l2,
+			Button::R1 => &controller.button_r1,
+			Button::R2 => &controller.button_r2,
+			Button::Power => &controller.button_power,
+		}
+	}
+}
+
+/// Which controller a binding is on
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Side {
+	#[default]
+	Primary,
+	Partner,
+}
+
+impl Side {
+	/// Splits a `partner.` prefix off of a binding
+	fn parse(value: &str) -> (Side, &str) {
+		match value.strip_prefix("partner.") {
+			Some(rest) => (Side::Partner, rest),
+			None => (Side::Primary, value),
+		}
+	}
+
+	fn prefix(self) -> &'static str {
+		match self {
+			Side::Primary => "",
+			Side::Partner => "partner.",
+		}
+	}
+}
+
+/// A button on one of the controllers
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ButtonBinding {
+	pub side: Side,
+	/// Only pressed while that controller's shift button is held
+	pub shifted: bool,
+	pub button: Button,
+}
+
+impl Display for ButtonBinding {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let shift = if self.shifted { "shift." } else { "" };
+		write!(f, "{}{shift}{}", self.side.prefix(), self.button.name())
+	}
+}
+
+/// Something the driver does with a stick
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AxisAction {
+	/// Forwards/backwards in arcade drive
+	Throttle,
+	/// Turning in arcade drive
+	Steer,
+	/// The left side in tank drive
+	Left,
+	/// The right side in tank drive
+	Right,
+}
+
+impl AxisAction {
+	pub const ALL: [AxisAction; 4] = [
+		AxisAction::Throttle,
+		AxisAction::Steer,
+		AxisAction::Left,
+		AxisAction::Right,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			AxisAction::Throttle => "throttle",
+			AxisAction::Steer => "steer",
+			AxisAction::Left => "left",
+			AxisAction::Right => "right",
+		}
+	}
+
+	pub fn default_axis(self) -> Axis {
+		let stick = match self {
+			AxisAction::Throttle | AxisAction::Left => Stick::LeftY,
+			AxisAction::Steer => Stick::RightX,
+			AxisAction::Right => Stick::RightY,
+		};
+
+		Axis {
+			side: Side::Primary,
+			stick,
+			inverted: false,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Stick {
+	LeftX,
+	LeftY,
+	RightX,
+	RightY,
+}
+
+impl Stick {
+	pub const ALL: [Stick; 4] = [Stick::LeftX, Stick::LeftY, Stick::RightX, Stick::RightY];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Stick::LeftX => "left_x",
+			Stick::LeftY => "left_y",
+			Stick::RightX => "right_x",
+			Stick::RightY => "right_y",
+		}
+	}
+}
+
+/// A stick axis, optionally inverted (written `-left_y`, or `-partner.left_y`)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Axis {
+	pub side: Side,
+	pub stick: Stick,
+	pub inverted: bool,
+}
+
+impl Axis {
+	pub fn value(self, controller: &ControllerState) -> f64 {
+		let value = match self.stick {
+			Stick::LeftX => controller.left_stick.x(),
+			Stick::LeftY => controller.left_stick.y(),
+			Stick::RightX => controller.right_stick.x(),
+			Stick::RightY => controller.right_stick.y(),
+		};
+
+		if self.inverted { -value } else { value }
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DriveStyle {
+	Arcade,
+	Tank,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DriverProfile {
+	pub name: String,
+	pub drive: DriveStyle,
+	pub shaping: Shaping,
+	buttons: HashMap<Action, ButtonBinding>,
+	axes: HashMap<AxisAction, Axis>,
+}
+
+impl DriverProfile {
+	/// A profile with every action on its default binding
+	pub fn new(name: &str, drive: DriveStyle) -> Self {
+		Self {
+			name: name.to_string(),
+			drive,
+			shaping: Shaping::default(),
+			buttons: Action::ALL
+				.iter()
+				.map(|a| (*a, a.default_binding()))
+				.collect(),
+			axes: AxisAction::ALL
+				.iter()
+				.map(|a| (*a, a.default_axis()))
+				.collect(),
+		}
+	}
+
+	pub fn button(&self, action: Action) -> Option<ButtonBinding> {
+		self.buttons.get(&action).copied()
+	}
+
+	pub fn axis(&self, action: AxisAction) -> Option<Axis> {
+		self.axes.get(&action).copied()
+	}
+
+	/// Binds an action to a button, or unbinds it with `None`
+	pub fn bind(&mut self, action: Action, button: Option<ButtonBinding>) {
+		match button {
+			Some(button) => self.buttons.insert(action, button),
+			None => self.buttons.remove(&action),
+		};
+	}
+
+	pub fn bind_axis(&mut self, action: AxisAction, axis: Option<Axis>) {
+		match axis {
+			Some(axis) => self.axes.insert(action, axis),
+			None => self.axes.remove(&action),
+		};
+	}
+
+	/// Checks that no button has more than one action on it
+	pub fn check_conflicts(&self) -> Result<(), BindingsError> {
+		if let Some(shift) = self.button(Action::Shift) {
+			for action in Action::ALL {
+				if let Some(button) = self.button(action)
+					&& button.shifted
+					&& button.button == shift.button
+				{
+					return Err(BindingsError::Conflict {
+						profile: self.name.clone(),
+						button,
+						actions: (Action::Shift, action),
+					});
+				}
+			}
+		}
+
+		// Go in a fixed order so the error is the same every time
+		for (i, first) in Action::ALL.iter().enumerate() {
+			for second in &Action::ALL[i + 1..] {
+				if let Some(button) = self.button(*first)
+					&& self.button(*second) == Some(button)
+				{
+					return Err(BindingsError::Conflict {
+						profile: self.name.clone(),
+						button,
+						actions: (*first, *second),
+					});
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Whether a partner action can be read from the primary controller while the partner is
+	/// disconnected, which it can't if the primary already uses that button
+	pub fn falls_back(&self, action: Action) -> bool {
+		let Some(binding) = self.button(action) else {
+			return false;
+		};
+
+		binding.side == Side::Primary
+			|| !self.buttons.values().any(|b| {
+				*b == ButtonBinding {
+					side: Side::Primary,
+					..binding
+				}
+			})
+	}
+
+	/// Whether `action`'s button does `action` right now, given whether shift is held on its
+	/// controller. While shift is held, a button with anything on its controller's shift layer
+	/// only does that.
+	pub fn on_layer(&self, action: Action, shift_held: bool) -> bool {
+		let Some(binding) = self.button(action) else {
+			return false;
+		};
+		let layer = shift_held
+			&& (binding.shifted
+				|| self
+					.buttons
+					.values()
+					.any(|b| b.shifted && b.side == binding.side && b.button == binding.button));
+
+		binding.shifted == layer
+	}
+
+	/// Actions that stop working while the partner controller is disconnected
+	pub fn unavailable_without_partner(&self) -> Vec<Action> {
+		Action::ALL
+			.into_iter()
+			.filter(|a| self.button(*a).is_some() && !self.falls_back(*a))
+			.collect()
+	}
+
+	fn axis_falls_back(&self, axis: Axis) -> bool {
+		axis.side == Side::Primary
+			|| !self
+				.axes
+				.values()
+				.any(|a| a.side == Side::Primary && a.stick == axis.stick)
+	}
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+	Io(io::Error),
+	/// A line that isn't a `[profile]` header, a `key = value` pair or a comment
+	InvalidLine(usize),
+	/// A binding before the first `[profile]` header
+	OutsideProfile(usize),
+	UnknownKey { line: usize, key: String },
+	UnknownValue { line: usize, value: String },
+	DuplicateProfile(String),
+	/// The file didn't have any profiles
+	NoProfiles,
+	/// A curve strength outside [`Curve::strength_range`] for the profile's curve
+	CurveStrength {
+		profile: String,
+		curve: Curve,
+		strength: f64,
+	},
+	/// Two actions are bound to the same button
+	Conflict {
+		profile: String,
+		button: ButtonBinding,
+		actions: (Action, Action),
+	},
+}
+
+impl Display for BindingsError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(e) => write!(f, "couldn't read bindings: {e}"),
+			Self::InvalidLine(line) => write!(f, "line {line}: expected `[profile]` or `key = value`"),
+			Self::OutsideProfile(line) => write!(f, "line {line}: binding isn't under a `[profile]`"),
+			Self::UnknownKey { line, key } => write!(f, "line {line}: unknown action {key:?}"),
+			Self::UnknownValue { line, value } => write!(f, "line {line}: invalid value {value:?}"),
+			Self::DuplicateProfile(name) => write!(f, "profile {name:?} is defined twice"),
+			Self::NoProfiles => write!(f, "no profiles defined"),
+			Self::CurveStrength {
+				profile,
+				curve,
+				strength,
+			} => {
+				let range = curve.strength_range();
+				write!(
+					f,
+					"{profile}: curve_strength {strength} is outside {} to {} for a {} curve",
+					range.start(),
+					range.end(),
+					curve.name(),
+				)
+			}
+			Self::Conflict {
+				profile,
+				button,
+				actions: (first, second),
+			} => write!(
+				f,
+				"{profile}: {} and {} are both bound to {button}",
+				first.name(),
+				second.name(),
+			),
+		}
+	}
+}
+
+impl std::error::Error for BindingsError {}
+
+impl From<io::Error> for BindingsError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
+
+fn parse_value<T: Copy>(
+	all: &[T],
+	name: impl Fn(T) -> &'static str,
+	value: &str,
+	line: usize,
+) -> Result<Option<T>, BindingsError> {
+	if value == "none" {
+		return Ok(None);
+	}
+
+	all.iter()
+		.find(|v| name(**v) == value)
+		.copied()
+		.map(Some)
+		.ok_or_else(|| BindingsError::UnknownValue {
+			line,
+			value: value.to_string(),
+		})
+}
+
+fn parse_number(
+	value: &str,
+	range: RangeInclusive<f64>,
+	line: usize,
+) -> Result<f64, BindingsError> {
+	value
+		.parse::<f64>()
+		.ok()
+		.filter(|n| range.contains(n))
+		.ok_or_else(|| BindingsError::UnknownValue {
+			line,
+			value: value.to_string(),
+		})
+}
+
+/// Every driver profile, and which one is in use
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+	profiles: Vec<DriverProfile>,
+	active: usize,
+}
+
+impl Default for Bindings {
+	fn default() -> Self {
+		Self {
+			profiles: vec![
+				DriverProfile::new("Noah", DriveStyle::Arcade),
+				DriverProfile::new("Connor", DriveStyle::Tank),
+			],
+			active: 0,
+		}
+	}
+}
+
+impl Bindings {
+	pub fn parse(source: &str) -> Result<Self, BindingsError> {
+		let mut profiles: Vec<DriverProfile> = Vec::new();
+
+		for (i, line) in source.lines().enumerate() {
+			let line_number = i + 1;
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+				continue;
+			}
+
+			if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				let name = name.trim();
+				if profiles.iter().any(|p| p.name == name) {
+					return Err(BindingsError::DuplicateProfile(name.to_string()));
+				}
+
+				profiles.push(DriverProfile::new(name, DriveStyle::Arcade));
+				continue;
+			}
+
+			let Some((key, value)) = line.split_once('=') else {
+				return Err(BindingsError::InvalidLine(line_number));
+			};
+			let (key, value) = (key.trim(), value.trim());
+			let profile = profiles
+				.last_mut()
+				.ok_or(BindingsError::OutsideProfile(line_number))?;
+
+			if key == "drive" {
+				profile.drive = match value {
+					"arcade" => DriveStyle::Arcade,
+					"tank" => DriveStyle::Tank,
+					_ => {
+						return Err(BindingsError::UnknownValue {
+							line: line_number,
+							value: value.to_string(),
+						});
+					}
+				};
+			} else if key == "deadband" {
+				// Any higher and there'd be nothing left to rescale
+				profile.shaping.deadband = parse_number(value, 0.0..=0.9, line_number)?;
+			} else if key == "curve" {
+				profile.shaping.curve = parse_value(&Curve::ALL, Curve::name, value, line_number)?
+					.unwrap_or_default();
+			} else if key == "curve_strength" {
+				// Checked against the curve once the whole profile is read, since either can
+				// come first
+				profile.shaping.curve_strength = parse_number(value, 0.0..=10.0, line_number)?;
+			} else if key == "slew" {
+				profile.shaping.slew_rate = match value {
+					"none" => None,
+					_ => Some(parse_number(value, f64::EPSILON..=f64::MAX, line_number)?),
+				};
+			} else if key == "turn_scaling" {
+				profile.shaping.turn_scaling = parse_number(value, 0.0..=1.0, line_number)?;
+			} else if let Some(action) = Action::ALL.iter().find(|a| a.name() == key) {
+				let (side, button) = Side::parse(value);
+				let (shifted, button) = match button.strip_prefix("shift.") {
+					// Shift can't need itself
+					Some(_) if *action == Action::Shift => {
+						return Err(BindingsError::UnknownValue {
+							line: line_number,
+							value: value.to_string(),
+						});
+					}
+					Some(button) => (true, button),
+					None => (false, button),
+				};
+				let button = parse_value(&Button::ALL, Button::name, button, line_number)?
+					.map(|button| ButtonBinding {
+						side,
+						shifted,
+						button,
+					});
+				profile.bind(*action, button);
+			} else if let Some(action) = AxisAction::ALL.iter().find(|a| a.name() == key) {
+				let (inverted, stick) = match value.strip_prefix('-') {
+					Some(stick) => (true, stick),
+					None => (false, value),
+				};
+				let (side, stick) = Side::parse(stick);
+				let axis = parse_value(&Stick::ALL, Stick::name, stick, line_number)?.map(|stick| {
+					Axis {
+						side,
+						stick,
+						inverted,
+					}
+				});
+				profile.bind_axis(*action, axis);
+			} else {
+				return Err(BindingsError::UnknownKey {
+					line: line_number,
+					key: key.to_string(),
+				});
+			}
+		}
+
+		if profiles.is_empty() {
+			return Err(BindingsError::NoProfiles);
+		}
+		for profile in &profiles {
+			let Shaping {
+				curve,
+				curve_strength,
+				..
+			} = profile.shaping;
+			if !curve.strength_range().contains(&curve_strength) {
+				return Err(BindingsError::CurveStrength {
+					profile: profile.name.clone(),
+					curve,
+					strength: curve_strength,
+				});
+			}
+			profile.check_conflicts()?;
+		}
+
+		Ok(Self {
+			profiles,
+			active: 0,
+		})
+	}
+
+	pub fn load(path: &str) -> Result<Self, BindingsError> {
+		Self::parse(&fs::read_to_string(path)?)
+	}
+
+	/// Loads [`BINDINGS_PATH`], falling back to the built-in profiles if it's missing or invalid
+	pub fn load_or_default() -> Self {
+		match Self::load(BINDINGS_PATH) {
+			Ok(bindings) => bindings,
+			Err(BindingsError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+			Err(e) => {
+				eprintln!("Using default bindings, {BINDINGS_PATH} is invalid: {e}");
+				Self::default()
+			}
+		}
+	}
+
+	pub fn profiles(&self) -> &[DriverProfile] {
+		&self.profiles
+	}
+
+	pub fn active(&self) -> &DriverProfile {
+		&self.profiles[self.active]
+	}
+
+	/// Switches to the next profile, wrapping around
+	pub fn next(&mut self) {
+		self.active = (self.active + 1) % self.profiles.len();
+	}
+
+	/// Switches to a profile by name, returning whether it exists
+	pub fn select(&mut self, name: &str) -> bool {
+		if let Some(i) = self.profiles.iter().position(|p| p.name == name) {
+			self.active = i;
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Reads the controllers through the active profile. `partner` is `None` when it's disconnected
+	pub fn input<'a>(
+		&'a self,
+		primary: &'a ControllerState,
+		partner: Option<&'a ControllerState>,
+	) -> DriverInput<'a> {
+		DriverInput {
+			primary,
+			partner,
+			profile: self.active(),
+		}
+	}
+}
+
+/// The controllers, read through a driver profile
+pub struct DriverInput<'a> {
+	pub primary: &'a ControllerState,
+	pub partner: Option<&'a ControllerState>,
+	pub profile: &'a DriverProfile,
+}
+
+impl DriverInput<'_> {
+	/// The controller to read a binding from, falling back to the primary if the partner is gone
+	fn controller(&self, side: Side, falls_back: impl FnOnce() -> bool) -> Option<&ControllerState> {
+		match (side, self.partner) {
+			(Side::Primary, _) => Some(self.primary),
+			(Side::Partner, Some(partner)) => Some(partner),
+			(Side::Partner, None) => falls_back().then_some(self.primary),
+		}
+	}
+
+	fn button(&self, action: Action) -> Option<&ButtonState> {
+		let binding = self.profile.button(action)?;
+		let controller = self.controller(binding.side, || self.profile.falls_back(action))?;
+		let shift_held = self
+			.profile
+			.button(Action::Shift)
+			.is_some_and(|shift| shift.button.state(controller).is_pressed());
+
+		self.profile
+			.on_layer(action, shift_held)
+			.then(|| binding.button.state(controller))
+	}
+
+	pub fn is_pressed(&self, action: Action) -> bool {
+		self.button(action).is_some_and(|b| b.is_pressed())
+	}
+
+	pub fn is_released(&self, action: Action) -> bool {
+		!self.is_pressed(action)
+	}
+
+	pub fn is_now_pressed(&self, action: Action) -> bool {
+		self.button(action).is_some_and(|b| b.is_now_pressed())
+	}
+
+	pub fn is_now_released(&self, action: Action) -> bool {
+		self.button(action).is_some_and(|b| b.is_now_released())
+	}
+
+	/// Whether any bound stick is pushed further than `threshold`
+	pub fn sticks_moved(&self, threshold: f64) -> bool {
+		AxisAction::ALL
+			.iter()
+			.any(|a| self.axis(*a).abs() > threshold)
+	}
+
+	/// The value of a stick action, or 0.0 if it isn't bound
+	pub fn axis(&self, action: AxisAction) -> f64 {
+		self.profile
+			.axis(action)
+			.and_then(|axis| {
+				let controller = self.controller(axis.side, || self.profile.axis_falls_back(axis))?;
+				Some(axis.value(controller))
+			})
+			.unwrap_or(0.0)
+	}
+
+	/// Which actions are held, for code that outlives the controller states (like triggers)
+	pub fn held(&self) -> HeldActions {
+		HeldActions(
+			Action::ALL
+				.into_iter()
+				.filter(|action| self.is_pressed(*action))
+				.fold(0, |bits, action| bits | action.bit()),
+		)
+	}
+}
+
+/// The actions held on one tick, read through a driver profile by [`DriverInput::held`]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct HeldActions(u32);
+
+impl HeldActions {
+	pub fn contains(self, action: Action) -> bool {
+		self.0 & action.bit() != 0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_defaults_have_no_conflicts() {
+		for profile in Bindings::default().profiles() {
+			profile.check_conflicts().unwrap();
+		}
+	}
+
+	#[test]
+	fn test_parse_overrides_defaults() {
+		let bindings = Bindings::parse(
+			"# comment\n[Sam]\ndrive = tank\nright = -right_y\nwing = none\nreplay_stop = b\ntoggle_reverse = none\nfront_bar = a\n",
+		)
+		.unwrap();
+
+		let profile = bindings.active();
+		assert_eq!(profile.name, "Sam");
+		assert_eq!(profile.drive, DriveStyle::Tank);
+		assert_eq!(
+			profile.axis(AxisAction::Right),
+			Some(Axis {
+				side: Side::Primary,
+				stick: Stick::RightY,
+				inverted: true
+			})
+		);
+		assert_eq!(profile.button(Action::Wing), None);
+		assert_eq!(
+			profile.button(Action::ReplayStop),
+			Some(ButtonBinding {
+				side: Side::Primary,
+				shifted: false,
+				button: Button::B
+			})
+		);
+	}
+
+	#[test]
+	fn test_parse_shaping() {
+		let bindings = Bindings::parse(
+			"[Sam]\ndeadband = 0.05\ncurve = exponential\ncurve_strength = 2\nslew = 4\nturn_scaling = 0.3\n[Max]\n",
+		)
+		.unwrap();
+
+		assert_eq!(
+			bindings.active().shaping,
+			Shaping {
+				deadband: 0.05,
+				curve: Curve::Exponential,
+				curve_strength: 2.0,
+				slew_rate: Some(4.0),
+				turn_scaling: 0.3,
+			}
+		);
+		assert_eq!(bindings.profiles()[1].shaping, Shaping::default());
+
+		for bad in ["deadband = 1", "curve = squiggly", "slew = 0", "turn_scaling = lots"] {
+			assert!(matches!(
+				Bindings::parse(&format!("[Sam]\n{bad}")),
+				Err(BindingsError::UnknownValue { line: 2, .. })
+			));
+		}
+	}
+
+	#[test]
+	fn test_curve_strength_checked_per_curve() {
+		assert!(Bindings::parse("[Sam]\ncurve = cubic\ncurve_strength = 1\n").is_ok());
+		for order in [
+			"curve = cubic\ncurve_strength = 2",
+			"curve_strength = 2\ncurve = cubic",
+		] {
+			assert!(matches!(
+				Bindings::parse(&format!("[Sam]\n{order}\n")),
+				Err(BindingsError::CurveStrength {
+					curve: Curve::Cubic,
+					..
+				})
+			));
+		}
+	}
+
+	#[test]
+	fn test_partner_fallback() {
+		let bindings = Bindings::parse(
+			"[Sam]\nintake = partner.r1\nwing = partner.up\nfront_bar = partner.l2\ncenter_goal_top = none\nheading_hold = partner.down\nsnap_heading = partner.shift.b\n",
+		)
+		.unwrap();
+		let profile = bindings.active();
+
+		assert_eq!(
+			profile.button(Action::Intake),
+			Some(ButtonBinding {
+				side: Side::Partner,
+				shifted: false,
+				button: Button::R1
+			})
+		);
+		// r1 and l2 are free on the primary, up is still shift
+		assert!(profile.falls_back(Action::Intake));
+		assert!(profile.falls_back(Action::FrontBar));
+		// down is free now wing has moved, but shift.b is save_pose
+		assert!(profile.falls_back(Action::HeadingHold));
+		assert_eq!(
+			profile.unavailable_without_partner(),
+			[Action::Wing, Action::SnapHeading]
+		);
+	}
+
+	#[test]
+	fn test_shift_layer() {
+		let bindings = Bindings::default();
+		let profile = bindings.active();
+
+		// Everything works without a partner
+		assert!(profile.unavailable_without_partner().is_empty());
+		for action in [
+			Action::HeadingHold,
+			Action::SnapHeading,
+			Action::SavePose,
+			Action::DriveToPose,
+		] {
+			assert!(profile.falls_back(action));
+		}
+		// a reverses, or snaps with shift held, never both
+		assert!(profile.on_layer(Action::ToggleReverse, false));
+		assert!(!profile.on_layer(Action::SnapHeading, false));
+		assert!(!profile.on_layer(Action::ToggleReverse, true));
+		assert!(profile.on_layer(Action::SnapHeading, true));
+		// Buttons with nothing on the shift layer work either way
+		assert!(profile.on_layer(Action::CenterGoalTop, true));
+		assert!(profile.on_layer(Action::Shift, true));
+	}
+
+	#[test]
+	fn test_shift_layer_per_controller() {
+		let bindings = Bindings::parse("[Sam]\nsave_pose = partner.shift.down\n").unwrap();
+		let profile = bindings.active();
+
+		// The partner's shift layer doesn't take the primary's down
+		assert!(profile.on_layer(Action::Wing, true));
+		assert!(profile.on_layer(Action::SavePose, true));
+		assert!(!profile.on_layer(Action::SavePose, false));
+	}
+
+	#[test]
+	fn test_parse_shift() {
+		let bindings = Bindings::parse("[Sam]\nshift = power\nsave_pose = partner.shift.x\n").unwrap();
+		let save_pose = bindings.active().button(Action::SavePose).unwrap();
+		assert_eq!(save_pose.to_string(), "partner.shift.x");
+
+		assert!(matches!(
+			Bindings::parse("[Sam]\nshift = shift.x"),
+			Err(BindingsError::UnknownValue { line: 2, .. })
+		));
+		assert!(matches!(
+			Bindings::parse("[Sam]\nsave_pose = shift.up"),
+			Err(BindingsError::Conflict {
+				actions: (Action::Shift, Action::SavePose),
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn test_partner_buttons_dont_conflict_with_primary() {
+		assert!(Bindings::parse("[Sam]\nunjam = partner.a\n").is_ok());
+		assert!(matches!(
+			Bindings::parse("[Sam]\nunjam = partner.a\nwing = partner.a\n"),
+			Err(BindingsError::Conflict { .. })
+		));
+	}
+
+	#[test]
+	fn test_parse_conflict() {
+		let error = Bindings::parse("[Sam]\nunjam = a\n").unwrap_err();
+
+		assert!(matches!(
+			error,
+			BindingsError::Conflict {
+				button: ButtonBinding {
+					side: Side::Primary,
+					shifted: false,
+					button: Button::A
+				},
+				actions: (Action::Unjam, Action::ToggleReverse),
+				..
+			}
+		));
+	}
+
+	#[test]
+	fn test_parse_errors() {
+		assert!(matches!(
+			Bindings::parse("wing = a"),
+			Err(BindingsError::OutsideProfile(1))
+		));
+		assert!(matches!(
+			Bindings::parse("[Sam]\nwing = z"),
+			Err(BindingsError::UnknownValue { line: 2, .. })
+		));
+		assert!(matches!(
+			Bindings::parse("[Sam]\nfly = a"),
+			Err(BindingsError::UnknownKey { line: 2, .. })
+		));
+		assert!(matches!(
+			Bindings::parse("[Sam]\n[Sam]"),
+			Err(BindingsError::DuplicateProfile(_))
+		));
+		assert!(matches!(
+			Bindings::parse("# nothing\n"),
+			Err(BindingsError::NoProfiles)
+		));
+	}
+}
@@ -0,0 +1,162 @@
+//! A pre-match self test, checking every device is plugged in and working before the robot
+//! goes out.
+
+use coprocessor::{requests::PingRequest, vexide::CoprocessorSmartPort};
+use vexide::{
+	battery,
+	prelude::InertialSensor,
+	smart::{SmartDeviceType, SmartPort},
+	sync::Mutex,
+};
+
+/// Anything under this (in volts) probably won't last a match
+pub const MIN_BATTERY_VOLTAGE: f64 = 12.4;
+
+/// A device that should be on a smart port
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ExpectedDevice {
+	pub name: &'static str,
+	pub port: u8,
+	pub kind: SmartDeviceType,
+}
+
+impl ExpectedDevice {
+	pub const fn new(name: &'static str, port: u8, kind: SmartDeviceType) -> Self {
+		Self { name, port, kind }
+	}
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct CheckItem {
+	pub name: String,
+	pub passed: bool,
+	/// What was found, or what went wrong
+	pub detail: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DeviceCheck {
+	pub items: Vec<CheckItem>,
+}
+
+impl DeviceCheck {
+	/// Checks every device in `devices`, then the IMU, coprocessor and battery
+	pub async fn run(
+		devices: &[ExpectedDevice],
+		imu: &Mutex<InertialSensor>,
+		coprocessor: &CoprocessorSmartPort,
+	) -> Self {
+		let mut check = Self::default();
+
+		for device in devices {
+			check.port(device);
+		}
+		check.imu(imu).await;
+		check.coprocessor(coprocessor).await;
+		check.battery(MIN_BATTERY_VOLTAGE);
+
+		check
+	}
+
+	pub fn record(&mut self, name: impl Into<String>, result: Result<String, String>) {
+		let (passed, detail) = match result {
+			Ok(detail) => (true, detail),
+			Err(detail) => (false, detail),
+		};
+
+		self.items.push(CheckItem {
+			name: name.into(),
+			passed,
+			detail,
+		});
+	}
+
+	pub fn passed(&self) -> bool {
+		self.items.iter().all(|item| item.passed)
+	}
+
+	pub fn failures(&self) -> impl Iterator<Item = &CheckItem> {
+		self.items.iter().filter(|item| !item.passed)
+	}
+
+	/// Checks the right kind of device is plugged into a port
+	pub fn port(&mut self, device: &ExpectedDevice) {
+		// SAFETY: This only reads what's plugged in, it doesn't talk to the device, so it can't
+		// get in the way of whatever owns the port.
+		let port = unsafe { SmartPort::new(device.port) };
+
+		self.record(
+			format!("{} (port {})", device.name, device.port),
+			port_result(device.kind, port.device_type()),
+		);
+	}
+
+	pub async fn imu(&mut self, imu: &Mutex<InertialSensor>) {
+		let imu = imu.lock().await;
+
+		let result = match imu.is_calibrating() {
+			Ok(true) => Err("still calibrating".to_string()),
+			Ok(false) => imu
+				.heading()
+				.map(|_| "calibrated".to_string())
+				.map_err(|e| format!("{e}")),
+			Err(e) => Err(format!("{e}")),
+		};
+		self.record("IMU", result);
+	}
+
+	/// Checks the coprocessor answers, and is running the code this was built with
+	pub async fn coprocessor(&mut self, coprocessor: &CoprocessorSmartPort) {
+		let result = match coprocessor.send_request(PingRequest).await {
+			Ok(hash) if PingRequest::verify_hash(hash) => Ok("up to date".to_string()),
+			Ok(_) => Err("outdated, reflash it".to_string()),
+			Err(e) => Err(format!("not responding ({e})")),
+		};
+		self.record("Coprocessor", result);
+	}
+
+	pub fn battery(&mut self, min_voltage: f64) {
+		let result = match battery::voltage() {
+			voltage if voltage >= min_voltage => Ok(format!("{voltage:.1}V")),
+			voltage => Err(format!("{voltage:.1}V, swap it")),
+		};
+		self.record("Battery", result);
+	}
+}
+
+fn port_result(
+	expected: SmartDeviceType,
+	found: Option<SmartDeviceType>,
+) -> Result<String, String> {
+	match found {
+		Some(found) if found == expected => Ok(format!("{found:?}")),
+		Some(found) => Err(format!("expected {expected:?}, found {found:?}")),
+		None => Err("nothing plugged in".to_string()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_port_result() {
+		assert!(port_result(SmartDeviceType::Motor, Some(SmartDeviceType::Motor)).is_ok());
+		assert_eq!(
+			port_result(SmartDeviceType::Motor, Some(SmartDeviceType::Imu)),
+			Err("expected Motor, found Imu".to_string())
+		);
+		assert!(port_result(SmartDeviceType::Imu, None).is_err());
+	}
+
+	#[test]
+	fn test_passed_needs_every_item() {
+		let mut check = DeviceCheck::default();
+		check.record("Battery", Ok("12.9V".to_string()));
+		assert!(check.passed());
+
+		check.record("IMU", Err("still calibrating".to_string()));
+		assert!(!check.passed());
+		assert_eq!(check.failures().count(), 1);
+	}
+}
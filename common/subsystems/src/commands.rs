@@ -0,0 +1,116 @@
+//! Commands for driving subsystems from a [`vexide_commands`] scheduler, so autons can be composed
+//! (e.g. `unjam_intake(..).with_timeout(..).and_then(autopark(..))`) instead of written as
+//! `sleep` sequences, and driver actions can be bound to commands with [`action`] triggers.
+
+use vexide_commands::prelude::*;
+
+use crate::{
+	bindings::{Action, HeldActions},
+	intake::{IntakeState, IntakeSubsystem},
+};
+
+pub const INTAKE: SubsystemId = SubsystemId("intake");
+
+/// Active while `action` is held, through the driver's bindings
+pub fn action(action: Action) -> Trigger<HeldActions> {
+	Trigger::new(move |held: &HeldActions| held.contains(action))
+}
+
+impl Subsystem for IntakeSubsystem {
+	fn id(&self) -> SubsystemId {
+		INTAKE
+	}
+}
+
+/// Runs the intake at a state until interrupted
+pub fn run_intake(intake: IntakeSubsystem, state: IntakeState) -> impl Command {
+	Run::new(&[INTAKE], move || intake.run(state))
+}
+
+/// Runs the intake at a state with unjamming enabled until interrupted
+pub fn unjam_intake(intake: IntakeSubsystem, state: IntakeState) -> impl Command {
+	struct Unjam {
+		intake: IntakeSubsystem,
+		state: IntakeState,
+	}
+
+	#[async_trait::async_trait(?Send)]
+	impl Command for Unjam {
+		async fn initialize(&mut self) {
+			self.intake.enable_unjam();
+		}
+
+		async fn execute(&mut self) {
+			self.intake.run(self.state);
+		}
+
+		async fn end(&mut self, _interrupted: bool) {
+			self.intake.disable_unjam();
+		}
+
+		fn requirements(&self) -> &[SubsystemId] {
+			&[INTAKE]
+		}
+	}
+
+	Unjam { intake, state }
+}
+
+/// Parks, finishing once the park piston has fired or parking fails. Interrupting it cancels
+/// parking.
+pub fn autopark(intake: IntakeSubsystem) -> impl Command {
+	FutureCommand::new(&[INTAKE], move || {
+		let intake = intake.clone();
+		async move {
+			if let Err(e) = intake.run_autopark().await {
+				eprintln!("Autopark failed: {e}");
+			}
+		}
+	})
+}
+
+/// Unjams whatever the driver is running the intake at, until interrupted. It doesn't require the
+/// intake, so it runs alongside parking rather than cancelling it.
+pub fn driver_unjam(intake: IntakeSubsystem) -> impl Command {
+	struct DriverUnjam(IntakeSubsystem);
+
+	#[async_trait::async_trait(?Send)]
+	impl Command for DriverUnjam {
+		async fn initialize(&mut self) {
+			self.0.enable_unjam();
+		}
+
+		async fn end(&mut self, _interrupted: bool) {
+			self.0.disable_unjam();
+		}
+	}
+
+	DriverUnjam(intake)
+}
+
+/// Parks and stays parked until interrupted, then lets go. Finishes early if parking is stopped
+/// some other way, like the manual park button.
+pub fn driver_autopark(intake: IntakeSubsystem) -> impl Command {
+	struct DriverAutopark(IntakeSubsystem);
+
+	#[async_trait::async_trait(?Send)]
+	impl Command for DriverAutopark {
+		async fn initialize(&mut self) {
+			self.0.start_autopark();
+		}
+
+		async fn end(&mut self, _interrupted: bool) {
+			self.0.unpark();
+		}
+
+		fn finished(&self) -> bool {
+			self.0.park_phase().is_none()
+		}
+
+		fn requirements(&self) -> &[SubsystemId] {
+			&[INTAKE]
+		}
+	}
+
+	DriverAutopark(intake)
+}
@@ -13,9 +13,9 @@ use coprocessor::{
 	vexide::CoprocessorSmartPort,
 };
 use shrewnit::{Degrees, Length, Radians};
-use vexide::{controller::ControllerState, smart::SmartPort, time::sleep};
+use vexide::{smart::SmartPort, time::sleep};
 
-use crate::{ControllableSubsystem, ControllerConfiguration};
+use crate::{ControllableSubsystem, bindings::DriverInput};
 
 pub mod tracking;
 
@@ -65,6 +65,11 @@ impl CoproSubsystem {
 		&self.data
 	}
 
+	/// Where the OTOS sits relative to the robot's center, sent again each calibration
+	pub fn offsets(&self) -> OtosPosition {
+		self.offsets
+	}
+
 	pub async fn calibrate(
 		&self,
 	) -> Result<<PingRequest as CoprocessorRequest>::Response, std::io::Error> {
@@ -129,21 +134,10 @@ impl CoproSubsystem {
 }
 
 impl ControllableSubsystem for CoproSubsystem {
-	fn direct(&mut self, _state: &ciborium::Value) {}
-
-	fn control(&mut self, controller: &ControllerState, _configuration: ControllerConfiguration) {
-		if controller.button_y.is_now_pressed() {
-			let port_clone = self.port.clone();
-			let offsets = self.offsets;
-			vexide::task::spawn(async move {
-				match Self::setup(&port_clone, offsets).await {
-					Ok(_) => println!("Calibrated!"),
-					Err(e) => println!("Unable to calibrate: {e:?}"),
-				}
-			})
-			.detach();
-		}
-	}
+	type State = ();
+
+	/// Calibrating is bound alongside the IMU's, so the driver loop handles it
+	fn control(&mut self, _input: &DriverInput) {}
 }
 
 impl Deref for CoproSubsystem {
@@ -8,7 +8,7 @@ use evian::{
 	prelude::{TracksForwardTravel, TracksHeading, TracksPosition, TracksVelocity},
 	tracking::{Gyro, Tracking},
 };
-use shrewnit::{FeetPerSecond, Inches};
+use shrewnit::{Degrees, FeetPerSecond, Inches, Radians, RadiansPerSecond};
 use vexide::{prelude::InertialSensor, sync::Mutex};
 
 use crate::copro::CoproData;
@@ -35,7 +35,8 @@ impl Default for TrackingCache {
 /// implements standard methods for recieving odometry information.
 pub struct CoproTracking {
 	copro_data: Rc<RefCell<CoproData>>,
-	imu: Rc<Mutex<InertialSensor>>,
+	/// Where heading comes from, or the OTOS if there isn't one
+	imu: Option<Rc<Mutex<InertialSensor>>>,
 	cache: TrackingCache,
 }
 
@@ -50,9 +51,26 @@ impl CoproTracking {
 					angular_velocity: imu.angular_velocity().unwrap_or_default().into(),
 				})
 				.unwrap_or_default(),
-			imu,
+			imu: Some(imu),
 		}
 	}
+
+	/// Tracks heading with the OTOS as well as position, for when there's no IMU (like in the
+	/// simulation)
+	pub fn without_imu(copro_data: Rc<RefCell<CoproData>>) -> Self {
+		Self {
+			copro_data,
+			imu: None,
+			cache: TrackingCache::default(),
+		}
+	}
+
+	fn otos_heading(&self) -> Angle {
+		// The sensor uses 0.0 as forward, so adjust it to cartesian-style
+		let heading = self.copro_data.borrow().position.heading + 90.0 * Degrees;
+
+		Angle::from_radians(heading.to::<Radians>()).wrapped_full()
+	}
 }
 
 impl Tracking for CoproTracking {}
@@ -68,11 +86,11 @@ impl TracksHeading for CoproTracking {
 	// Clockwise is negative, until 180
 	// Counterclockwise is positive, until 180
 	fn heading(&self) -> vexide::math::Angle {
-		// let heading = self.copro_data.borrow().position.heading + 90.0 * Degrees; // The sensor uses 0.0 as forward, so adjust it to cartesian-style
+		let Some(imu) = &self.imu else {
+			return self.otos_heading();
+		};
 
-		// Angle::from_radians(heading.to::<Radians>()).wrapped_full()
-		self.imu
-			.try_lock()
+		imu.try_lock()
 			.and_then(|i| i.heading().ok())
 			.map(imu_to_cartesian_angle)
 			.inspect(|h| self.cache.heading.set(*h))
@@ -103,13 +121,16 @@ impl TracksVelocity for CoproTracking {
 	}
 
 	fn angular_velocity(&self) -> f64 {
-		// self.copro_data
-		// 	.borrow()
-		// 	.velocity
-		// 	.heading
-		// 	.to::<RadiansPerSecond>()
-		self.imu
-			.try_lock()
+		let Some(imu) = &self.imu else {
+			return self
+				.copro_data
+				.borrow()
+				.velocity
+				.heading
+				.to::<RadiansPerSecond>();
+		};
+
+		imu.try_lock()
 			.and_then(|i| i.angular_velocity().ok())
 			.inspect(|v| self.cache.angular_velocity.set(*v))
 			.unwrap_or(self.cache.angular_velocity.get())
@@ -0,0 +1,346 @@
+//! Driver assists for squaring up: heading hold, snapping to the nearest 45° and driving back to
+//! a saved pose.
+//!
+//! Snapping and driving to a pose take over the drivetrain until they finish, time out, or the
+//! driver moves a stick. Heading hold only corrects while the driver is driving straight, so
+//! turning overrides it straight away.
+//!
+//! Saved poses face the way the driver's front did, so one saved while driving reversed is
+//! driven back to facing the same way, whichever way round the drivetrain is by then.
+
+use std::{
+	f64::consts::FRAC_PI_4,
+	time::{Duration, Instant},
+};
+
+use evian::{
+	control::loops::{AngularPid, Feedback, Pid},
+	math::{Angle, Vec2},
+	motion::{Basic, Seeking},
+	prelude::{Tolerances, TracksHeading, TracksPosition, TracksVelocity},
+};
+
+use super::arcade;
+use crate::bindings::{Action, DriverInput};
+
+/// Stick values past this cancel an assist, and count as driving or turning for heading hold
+pub const ASSIST_THRESHOLD: f64 = 0.05;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SavedPose {
+	pub position: Vec2<f64>,
+	/// The heading of the driver's front, which is the back of the robot while reversed
+	pub heading: Angle,
+}
+
+enum Mode {
+	/// Turning in place to a heading
+	Turn {
+		heading: Angle,
+		controller: AngularPid,
+		tolerances: Tolerances,
+		started: Instant,
+	},
+	/// Driving to a saved pose, before turning to its heading
+	Seek {
+		target: SavedPose,
+		linear: Pid,
+		lateral: Pid,
+		tolerances: Tolerances,
+		started: Instant,
+	},
+}
+
+/// Converts between the robot's heading and the driver's front's (it goes both ways)
+pub fn flip_heading(heading: Angle, reversed: bool) -> Angle {
+	if reversed {
+		(heading + Angle::from_turns(0.5)).wrapped_full()
+	} else {
+		heading
+	}
+}
+
+/// The nearest multiple of 45° to a heading
+pub fn snap_heading(heading: Angle) -> Angle {
+	Angle::from_radians((heading.as_radians() / FRAC_PI_4).round() * FRAC_PI_4)
+}
+
+fn timed_out(started: Instant, timeout: Option<Duration>) -> bool {
+	timeout.is_some_and(|timeout| started.elapsed() > timeout)
+}
+
+/// Turning counterclockwise (like headings) by `turn`, as (left, right)
+fn turn_in_place(turn: f64) -> (f64, f64) {
+	arcade(0.0, -turn)
+}
+
+pub struct DriverAssist {
+	/// Controllers and tolerances for turning and heading hold
+	pub basic: Basic<Pid, AngularPid>,
+	/// Controllers and tolerances for driving to a pose
+	pub seeking: Seeking<Pid, Pid>,
+	/// Whether to hold heading while driving straight
+	pub heading_hold: bool,
+	mode: Option<Mode>,
+	held: Option<(Angle, AngularPid)>,
+	saved_pose: Option<SavedPose>,
+	last_update: Option<Instant>,
+}
+
+impl DriverAssist {
+	pub fn new(basic: Basic<Pid, AngularPid>, seeking: Seeking<Pid, Pid>) -> Self {
+		Self {
+			basic,
+			seeking,
+			heading_hold: false,
+			mode: None,
+			held: None,
+			saved_pose: None,
+			last_update: None,
+		}
+	}
+
+	/// Whether an assist has taken over the drivetrain
+	pub fn is_active(&self) -> bool {
+		self.mode.is_some()
+	}
+
+	pub fn cancel(&mut self) {
+		self.mode = None;
+	}
+
+	pub fn saved_pose(&self) -> Option<SavedPose> {
+		self.saved_pose
+	}
+
+	pub fn save_pose(&mut self, pose: SavedPose) {
+		self.saved_pose = Some(pose);
+	}
+
+	/// Starts turning to a heading
+	pub fn turn_to(&mut self, heading: Angle) {
+		self.mode = Some(Mode::Turn {
+			heading,
+			controller: self.basic.angular_controller.clone(),
+			tolerances: self.basic.angular_tolerances,
+			started: Instant::now(),
+		});
+	}
+
+	/// Starts driving to the saved pose, returning whether there is one
+	pub fn drive_to_saved(&mut self, reversed: bool) -> bool {
+		let Some(target) = self.saved_pose else {
+			return false;
+		};
+
+		self.mode = Some(Mode::Seek {
+			target: SavedPose {
+				heading: flip_heading(target.heading, reversed),
+				..target
+			},
+			linear: self.seeking.linear_controller.clone(),
+			lateral: self.seeking.lateral_controller.clone(),
+			tolerances: self.seeking.tolerances,
+			started: Instant::now(),
+		});
+		true
+	}
+
+	/// Handles the assist actions and works out what to drive with, given what the driver is
+	/// asking for as (left, right)
+	pub fn control<T: TracksPosition + TracksHeading + TracksVelocity>(
+		&mut self,
+		input: &DriverInput,
+		tracking: &T,
+		driver: (f64, f64),
+		reversed: bool,
+	) -> (f64, f64) {
+		let now = Instant::now();
+		let dt = self
+			.last_update
+			.replace(now)
+			.map(|last| now - last)
+			.unwrap_or_default();
+
+		if input.is_now_pressed(Action::HeadingHold) {
+			self.heading_hold = !self.heading_hold;
+			println!(
+				"Heading hold {}",
+				if self.heading_hold { "on" } else { "off" }
+			);
+		}
+		if input.is_now_pressed(Action::SavePose) {
+			let pose = SavedPose {
+				position: tracking.position(),
+				heading: flip_heading(tracking.heading(), reversed),
+			};
+			println!(
+				"Saved pose ({:.1}, {:.1}) at {:.1}°",
+				pose.position.x,
+				pose.position.y,
+				pose.heading.as_radians().to_degrees()
+			);
+			self.save_pose(pose);
+		}
+
+		if input.sticks_moved(ASSIST_THRESHOLD) {
+			if self.is_active() {
+				println!("Assist cancelled");
+				self.cancel();
+			}
+		} else if input.is_now_pressed(Action::SnapHeading) {
+			self.turn_to(snap_heading(tracking.heading()));
+		} else if input.is_now_pressed(Action::DriveToPose) && !self.drive_to_saved(reversed) {
+			println!("No pose saved to drive to");
+		}
+
+		self.update(tracking, driver, dt)
+	}
+
+	/// Runs whichever assist is active, or heading hold if none are
+	pub fn update<T: TracksPosition + TracksHeading + TracksVelocity>(
+		&mut self,
+		tracking: &T,
+		driver: (f64, f64),
+		dt: Duration,
+	) -> (f64, f64) {
+		let Some(mode) = self.mode.take() else {
+			return self.hold(tracking, driver, dt);
+		};
+		self.held = None;
+
+		let (next, output) = match mode {
+			Mode::Turn {
+				heading,
+				mut controller,
+				mut tolerances,
+				started,
+			} => {
+				let current = tracking.heading();
+				let error = (heading - current).wrapped_half();
+
+				if tolerances.check(error.as_radians(), tracking.angular_velocity())
+					|| timed_out(started, self.basic.timeout)
+				{
+					(None, (0.0, 0.0))
+				} else {
+					let turn = controller.update(current, heading, dt);
+					let next = Mode::Turn {
+						heading,
+						controller,
+						tolerances,
+						started,
+					};
+					(Some(next), turn_in_place(turn))
+				}
+			}
+			Mode::Seek {
+				target,
+				mut linear,
+				mut lateral,
+				mut tolerances,
+				started,
+			} => {
+				let local = target.position - tracking.position();
+
+				if tolerances.check(local.length(), tracking.linear_velocity()) {
+					self.turn_to(target.heading);
+					(self.mode.take(), (0.0, 0.0))
+				} else if timed_out(started, self.seeking.timeout) {
+					(None, (0.0, 0.0))
+				} else {
+					let mut distance = local.length();
+					let mut angle_error =
+						(tracking.heading() - Angle::atan2(local.y, local.x)).wrapped_half();
+					// Back up to targets behind the robot rather than turning around
+					if angle_error.abs() > Angle::from_degrees(90.0) {
+						distance = -distance;
+						angle_error = (angle_error + Angle::from_turns(0.5)).wrapped_half();
+					}
+
+					let throttle =
+						linear.update(-distance, 0.0, dt) * angle_error.as_radians().cos();
+					let steer = lateral.update(-angle_error.as_radians(), 0.0, dt);
+					let next = Mode::Seek {
+						target,
+						linear,
+						lateral,
+						tolerances,
+						started,
+					};
+					(Some(next), arcade(throttle, steer))
+				}
+			}
+		};
+
+		if next.is_none() {
+			println!("Assist finished");
+		}
+		self.mode = next;
+		output
+	}
+
+	/// Keeps the heading from when the driver started driving straight
+	fn hold<T: TracksHeading>(
+		&mut self,
+		tracking: &T,
+		(left, right): (f64, f64),
+		dt: Duration,
+	) -> (f64, f64) {
+		let throttle = (left + right) / 2.0;
+		let turn = (left - right) / 2.0;
+
+		if !self.heading_hold
+			|| throttle.abs() < ASSIST_THRESHOLD
+			|| turn.abs() >= ASSIST_THRESHOLD
+		{
+			self.held = None;
+			return (left, right);
+		}
+
+		let current = tracking.heading();
+		let (heading, controller) = self
+			.held
+			.get_or_insert_with(|| (current, self.basic.angular_controller.clone()));
+		let correction = controller.update(current, *heading, dt);
+
+		arcade(throttle, -correction)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_snap_heading() {
+		for (heading, snapped) in [
+			(10.0, 0.0),
+			(30.0, 45.0),
+			(100.0, 90.0),
+			(-160.0, -180.0),
+			(-22.0, 0.0),
+		] {
+			let result = snap_heading(Angle::from_degrees(heading));
+			assert!((result.as_radians() - snapped.to_radians()).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_flip_heading() {
+		let heading = Angle::from_degrees(30.0);
+
+		assert_eq!(flip_heading(heading, false), heading);
+		assert!((flip_heading(heading, true).as_radians() - 210_f64.to_radians()).abs() < 1e-9);
+		assert!(
+			(flip_heading(flip_heading(heading, true), true).as_radians() - heading.as_radians())
+				.abs() < 1e-9
+		);
+	}
+
+	#[test]
+	fn test_turn_in_place_is_counterclockwise() {
+		let (left, right) = turn_in_place(0.5);
+		assert!(left < 0.0 && right > 0.0);
+	}
+}
@@ -0,0 +1,213 @@
+pub mod assist;
+pub mod shaping;
+pub mod thermal;
+
+use std::{
+	ops::{Deref, DerefMut},
+	time::Instant,
+};
+
+use evian::{
+	drivetrain::model::{Differential, DrivetrainModel},
+	math::desaturate,
+	prelude::{Drivetrain, Tank, TracksHeading, TracksPosition, TracksVelocity},
+	tracking::Tracking,
+};
+use evian_extra::voltage::VoltageCompensation;
+use vexide::smart::{SmartDevice as _, motor::BrakeMode};
+
+use crate::{
+	ControllableSubsystem,
+	bindings::{Action, AxisAction, DriveStyle, DriverInput},
+	drivetrain::{
+		assist::DriverAssist,
+		shaping::SlewLimiter,
+		thermal::{MotorReading, ThermalEvent, ThermalGuard},
+	},
+	faults::FaultRegistry,
+	replay::interpolate::Interpolate,
+};
+
+fn arcade(throttle: f64, steer: f64) -> (f64, f64) {
+	desaturate([throttle + steer, throttle - steer], 1_f64).into()
+}
+
+pub struct DrivetrainSubsystem<M: Tank, T: Tracking> {
+	pub drivetrain: Drivetrain<M, T>,
+	/// Whether the back of the robot is the front, for both drive styles and the assists
+	pub reverse: bool,
+	/// The state of the drivetrain as (left, right), both should be desaturated before use
+	pub state: (f64, f64),
+	/// Heading hold and auto-align, if they're set up
+	pub assist: Option<DriverAssist>,
+	/// Turns output down as the motors heat up, if it's set up
+	pub thermal: Option<ThermalGuard>,
+	/// Scales output against the battery's voltage, if set, so open-loop driving in autons is
+	/// the same on any battery
+	pub compensation: Option<VoltageCompensation>,
+	pub faults: FaultRegistry,
+	slew: SlewLimiter,
+}
+
+impl<M: Tank, T: Tracking> DrivetrainSubsystem<M, T> {
+	pub fn new(drivetrain: M, tracking: T) -> Self {
+		Self {
+			drivetrain: Drivetrain::new(drivetrain, tracking),
+			reverse: false,
+			state: Default::default(),
+			assist: None,
+			thermal: None,
+			compensation: None,
+			faults: FaultRegistry::new(),
+			slew: SlewLimiter::default(),
+		}
+	}
+
+	pub fn run(&mut self) -> Result<(), <M as DrivetrainModel>::Error> {
+		let [left, right] = desaturate(self.state.into(), 1_f64);
+		let (left, right) = match &self.thermal {
+			Some(thermal) => thermal.apply((left, right)),
+			None => (left, right),
+		};
+
+		self.drive_tank(left, right)
+	}
+
+	/// Drives each side at a fraction of full power, compensated for the battery's voltage if
+	/// [`Self::compensation`] is set. Autons should drive through this rather than the model.
+	pub fn drive_tank(
+		&mut self,
+		left: f64,
+		right: f64,
+	) -> Result<(), <M as DrivetrainModel>::Error> {
+		let (left, right) = match &self.compensation {
+			Some(compensation) => compensation.apply_pair((left, right)),
+			None => (left, right),
+		};
+
+		self.drivetrain.model.drive_tank(left, right)
+	}
+
+	/// Like [`Self::drive_tank`], but with a throttle and steer
+	pub fn drive_arcade(
+		&mut self,
+		throttle: f64,
+		steer: f64,
+	) -> Result<(), <M as DrivetrainModel>::Error> {
+		let (left, right) = arcade(throttle, steer);
+		self.drive_tank(left, right)
+	}
+}
+
+impl<T: Tracking> DrivetrainSubsystem<Differential, T> {
+	/// Reads every motor's temperature and current for [`Self::thermal`], logging and returning
+	/// anything the driver should know about. Does nothing if it isn't set up or it isn't time
+	/// for another reading yet.
+	pub fn check_thermals(&mut self) -> Vec<ThermalEvent> {
+		let now = Instant::now();
+		let Some(thermal) = &mut self.thermal else {
+			return Vec::new();
+		};
+		if !thermal.is_due(now) {
+			return Vec::new();
+		}
+
+		let mut left = self.drivetrain.model.left.borrow_mut();
+		let mut right = self.drivetrain.model.right.borrow_mut();
+		let readings = left
+			.as_mut()
+			.iter()
+			.chain(right.as_mut().iter())
+			.map(|motor| MotorReading {
+				port: motor.port_number(),
+				temperature: motor.temperature().unwrap_or_default(),
+				current: motor.current().unwrap_or_default(),
+			})
+			.collect::<Vec<_>>();
+
+		let events = thermal.step(&readings, now);
+		for event in &events {
+			println!("Warning: {event}");
+		}
+		events
+	}
+
+	pub fn brake(&mut self, mode: BrakeMode) {
+		let mut left = self.model.left.borrow_mut();
+		let mut right = self.model.right.borrow_mut();
+		for motor in left.as_mut().iter_mut().chain(right.as_mut().iter_mut()) {
+			let port = motor.port_number();
+			self.faults
+				.check("drivetrain", &format!("port {port} motor"), motor.brake(mode));
+		}
+	}
+}
+
+impl<M: Tank, T: Tracking + TracksPosition + TracksHeading + TracksVelocity> ControllableSubsystem
+	for DrivetrainSubsystem<M, T>
+{
+	type State = (f64, f64);
+
+	fn state(&self) -> Option<Self::State> {
+		Some(self.state)
+	}
+
+	fn direct(&mut self, state: Self::State) {
+		self.state = state;
+
+		let result = self.run();
+		self.faults.check("drivetrain", "drive motors", result);
+	}
+
+	fn interpolate(&self, from: &Self::State, to: &Self::State, t: f64) -> Option<Self::State> {
+		Some(from.lerp(to, t))
+	}
+
+	fn control(&mut self, input: &DriverInput) {
+		if input.is_now_pressed(Action::ToggleReverse) {
+			self.reverse = !self.reverse;
+		}
+
+		let shaping = &input.profile.shaping;
+		let mut target = match input.profile.drive {
+			DriveStyle::Arcade => {
+				let (throttle, steer) = shaping.arcade(
+					input.axis(AxisAction::Throttle),
+					input.axis(AxisAction::Steer),
+				);
+
+				arcade(throttle * if self.reverse { -1.0 } else { 1.0 }, steer)
+			}
+			DriveStyle::Tank => {
+				let left = shaping.stick(input.axis(AxisAction::Left));
+				let right = shaping.stick(input.axis(AxisAction::Right));
+
+				match self.reverse {
+					false => (left, right),
+					true => (-right, -left),
+				}
+			}
+		};
+		if let Some(assist) = &mut self.assist {
+			target = assist.control(input, &self.drivetrain.tracking, target, self.reverse);
+		}
+		self.state = self.slew.limit(target, shaping.slew_rate);
+
+		let result = self.run();
+		self.faults.check("drivetrain", "drive motors", result);
+	}
+}
+
+impl<M: Tank, T: Tracking> Deref for DrivetrainSubsystem<M, T> {
+	type Target = Drivetrain<M, T>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.drivetrain
+	}
+}
+
+impl<M: Tank, T: Tracking> DerefMut for DrivetrainSubsystem<M, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.drivetrain
+	}
+}
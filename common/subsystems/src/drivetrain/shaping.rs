@@ -0,0 +1,244 @@
+//! Shapes raw stick values before they reach the motors.
+
+use std::{
+	ops::RangeInclusive,
+	time::{Duration, Instant},
+};
+
+/// Longest gap between updates the slew limiter will ramp over, so the first update after a
+/// pause doesn't jump straight to full power
+const MAX_SLEW_STEP: Duration = Duration::from_millis(50);
+
+/// How stick travel (after the deadband) maps to output
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Curve {
+	#[default]
+	Linear,
+	/// `s·x³ + (1 - s)·x`, with the strength `s` from 0.0 (linear) to 1.0 (fully cubic)
+	Cubic,
+	/// `(e^(s·|x|) - 1) / (e^s - 1)`, the larger the strength `s` the gentler the start
+	Exponential,
+}
+
+impl Curve {
+	pub const ALL: [Curve; 3] = [Curve::Linear, Curve::Cubic, Curve::Exponential];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Curve::Linear => "linear",
+			Curve::Cubic => "cubic",
+			Curve::Exponential => "exponential",
+		}
+	}
+
+	/// The strengths the curve stays monotonic over. Past 1.0 the cubic dips back towards 0
+	/// before reaching the end of the stick.
+	pub fn strength_range(self) -> RangeInclusive<f64> {
+		match self {
+			Curve::Cubic => 0.0..=1.0,
+			Curve::Linear | Curve::Exponential => 0.0..=10.0,
+		}
+	}
+
+	/// Maps `x` (-1.0 to 1.0), keeping its sign and the ends of the range where they are
+	pub fn apply(self, x: f64, strength: f64) -> f64 {
+		match self {
+			Curve::Linear => x,
+			Curve::Cubic => strength * x.powi(3) + (1.0 - strength) * x,
+			// Linear in the limit, and dividing by zero otherwise
+			Curve::Exponential if strength.abs() < f64::EPSILON => x,
+			Curve::Exponential => {
+				((strength * x.abs()).exp() - 1.0) / (strength.exp() - 1.0) * x.signum()
+			}
+		}
+	}
+}
+
+/// Per-profile input shaping. The default passes sticks straight through.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Shaping {
+	/// Stick values at or below this are treated as 0, the rest are rescaled to still reach 1.0
+	pub deadband: f64,
+	pub curve: Curve,
+	pub curve_strength: f64,
+	/// The most the output can speed up by per second, `None` for no limit
+	pub slew_rate: Option<f64>,
+	/// How much turning is reduced at full throttle in arcade drive (0.0 to 1.0)
+	pub turn_scaling: f64,
+}
+
+impl Default for Shaping {
+	fn default() -> Self {
+		Self {
+			deadband: 0.0,
+			curve: Curve::Linear,
+			curve_strength: 0.5,
+			slew_rate: None,
+			turn_scaling: 0.0,
+		}
+	}
+}
+
+impl Shaping {
+	/// Applies the deadband and curve to a single stick value
+	pub fn stick(&self, value: f64) -> f64 {
+		let magnitude = value.abs().min(1.0);
+		if magnitude <= self.deadband {
+			return 0.0;
+		}
+
+		let rescaled = (magnitude - self.deadband) / (1.0 - self.deadband);
+		self.curve
+			.apply(rescaled, self.curve_strength)
+			.copysign(value)
+	}
+
+	/// Shapes arcade sticks, returning (throttle, steer)
+	pub fn arcade(&self, throttle: f64, steer: f64) -> (f64, f64) {
+		let throttle = self.stick(throttle);
+		let steer = self.stick(steer) * (1.0 - self.turn_scaling * throttle.abs());
+
+		(throttle, steer)
+	}
+}
+
+/// Limits how fast each side of the drivetrain speeds up.
+///
+/// Slowing down (including letting go of the stick) isn't limited, and reversing direction
+/// drops to 0 straight away before ramping up the other way.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SlewLimiter {
+	last: (f64, f64),
+	last_update: Option<Instant>,
+}
+
+fn slew(current: f64, target: f64, max_delta: f64) -> f64 {
+	let from = if current * target <= 0.0 { 0.0 } else { current };
+
+	if target.abs() <= from.abs() {
+		target
+	} else {
+		from + (target - from).clamp(-max_delta, max_delta)
+	}
+}
+
+impl SlewLimiter {
+	/// Moves towards `target` by at most `max_delta` on each side
+	pub fn step(&mut self, target: (f64, f64), max_delta: f64) -> (f64, f64) {
+		self.last = (
+			slew(self.last.0, target.0, max_delta),
+			slew(self.last.1, target.1, max_delta),
+		);
+		self.last
+	}
+
+	/// Moves towards `target` at `rate` per second since the last update
+	pub fn limit(&mut self, target: (f64, f64), rate: Option<f64>) -> (f64, f64) {
+		let now = Instant::now();
+		let elapsed = self
+			.last_update
+			.replace(now)
+			.map(|last| now - last)
+			.unwrap_or_default()
+			.min(MAX_SLEW_STEP);
+
+		match rate {
+			Some(rate) => self.step(target, rate * elapsed.as_secs_f64()),
+			None => {
+				self.last = target;
+				target
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_default_is_passthrough() {
+		let shaping = Shaping::default();
+
+		for x in [-1.0, -0.3, 0.0, 0.05, 0.7, 1.0] {
+			assert_eq!(shaping.stick(x), x);
+		}
+		assert_eq!(shaping.arcade(1.0, 0.5), (1.0, 0.5));
+	}
+
+	#[test]
+	fn test_deadband_rescales() {
+		let shaping = Shaping {
+			deadband: 0.1,
+			..Default::default()
+		};
+
+		assert_eq!(shaping.stick(0.08), 0.0);
+		assert_eq!(shaping.stick(-0.1), 0.0);
+		assert!((shaping.stick(0.55) - 0.5).abs() < 1e-9);
+		assert_eq!(shaping.stick(-1.0), -1.0);
+	}
+
+	#[test]
+	fn test_curves_keep_ends() {
+		for curve in Curve::ALL {
+			for strength in [0.0, 0.5, 1.0, 3.0]
+				.into_iter()
+				.filter(|s| curve.strength_range().contains(s))
+			{
+				assert!((curve.apply(1.0, strength) - 1.0).abs() < 1e-9);
+				assert!((curve.apply(-1.0, strength) + 1.0).abs() < 1e-9);
+				assert_eq!(curve.apply(0.0, strength), 0.0);
+			}
+		}
+
+		// Both curves should be gentler than linear near the middle
+		assert!(Curve::Cubic.apply(0.5, 1.0) < 0.5);
+		assert!(Curve::Exponential.apply(0.5, 3.0) < 0.5);
+		assert!(Curve::Exponential.apply(-0.5, 3.0) > -0.5);
+	}
+
+	#[test]
+	fn test_curves_monotonic() {
+		for curve in Curve::ALL {
+			let range = curve.strength_range();
+			for i in 0..=20 {
+				let strength = range.start() + (range.end() - range.start()) * f64::from(i) / 20.0;
+				let mut last = curve.apply(-1.0, strength);
+				for j in -199..=200 {
+					let output = curve.apply(f64::from(j) / 200.0, strength);
+					assert!(
+						output >= last - 1e-12,
+						"{} at strength {strength} decreases at {}",
+						curve.name(),
+						f64::from(j) / 200.0
+					);
+					last = output;
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_turn_scaling() {
+		let shaping = Shaping {
+			turn_scaling: 0.5,
+			..Default::default()
+		};
+
+		assert_eq!(shaping.arcade(0.0, 1.0), (0.0, 1.0));
+		assert_eq!(shaping.arcade(-1.0, 1.0), (-1.0, 0.5));
+	}
+
+	#[test]
+	fn test_slew_only_limits_speeding_up() {
+		let mut limiter = SlewLimiter::default();
+
+		assert_eq!(limiter.step((1.0, -1.0), 0.25), (0.25, -0.25));
+		assert_eq!(limiter.step((1.0, -1.0), 0.25), (0.5, -0.5));
+		// Letting go stops straight away
+		assert_eq!(limiter.step((0.0, -0.2), 0.25), (0.0, -0.2));
+		// Reversing goes through 0
+		assert_eq!(limiter.step((0.0, 1.0), 0.25), (0.0, 0.25));
+	}
+}
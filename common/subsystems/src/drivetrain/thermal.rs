@@ -0,0 +1,303 @@
+//! Turns drivetrain output down as its motors heat up, so long driver runs (skills) don't cook
+//! them. Every motor is scaled by the same amount, from the hottest one, so derating doesn't
+//! make the robot pull to one side.
+
+use std::{
+	fmt::Display,
+	time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThermalConfig {
+	/// Temperature (in °C) at which output starts being turned down
+	pub derate_start: f64,
+	/// Temperature (in °C) at which output is down to `min_scale`
+	pub derate_end: f64,
+	/// The least output is turned down to (0.0 to 1.0)
+	pub min_scale: f64,
+	/// Current draw (in amps) that's worth warning about if it lasts
+	pub current: f64,
+	/// How long the current has to stay high for
+	pub current_time: Duration,
+	/// How often to read the motors, since temperatures change slowly
+	pub interval: Duration,
+}
+
+impl Default for ThermalConfig {
+	fn default() -> Self {
+		Self {
+			derate_start: 45.0,
+			derate_end: 55.0,
+			min_scale: 0.4,
+			current: 2.3,
+			current_time: Duration::from_secs(2),
+			interval: Duration::from_millis(100),
+		}
+	}
+}
+
+impl ThermalConfig {
+	/// How much to scale output by for a motor at `temperature`
+	pub fn scale(&self, temperature: f64) -> f64 {
+		let t = (temperature - self.derate_start) / (self.derate_end - self.derate_start);
+		1.0 - t.clamp(0.0, 1.0) * (1.0 - self.min_scale)
+	}
+}
+
+/// One reading of a drivetrain motor
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct MotorReading {
+	pub port: u8,
+	/// °C
+	pub temperature: f64,
+	/// Amps
+	pub current: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThermalEventKind {
+	/// The hottest motor is warm enough to start turning output down
+	Derating,
+	/// The hottest motor is hot enough that output is as low as it goes
+	Overheated,
+	/// Every motor has cooled back down, so output isn't limited anymore
+	Cooled,
+	/// A motor has been drawing a lot of current for a while, probably from pushing
+	HighCurrent,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThermalEvent {
+	pub kind: ThermalEventKind,
+	pub motor: MotorReading,
+	/// What output is scaled by now
+	pub scale: f64,
+}
+
+impl Display for ThermalEvent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let MotorReading {
+			port,
+			temperature,
+			current,
+		} = self.motor;
+		let scale = self.scale * 100.0;
+
+		match self.kind {
+			ThermalEventKind::Derating => write!(
+				f,
+				"drive motor on port {port} is at {temperature:.0}°C, limiting output to {scale:.0}%"
+			),
+			ThermalEventKind::Overheated => write!(
+				f,
+				"drive motor on port {port} is at {temperature:.0}°C, output is down to {scale:.0}%"
+			),
+			ThermalEventKind::Cooled => write!(f, "drive motors have cooled down"),
+			ThermalEventKind::HighCurrent => write!(
+				f,
+				"drive motor on port {port} has been drawing {current:.1}A, let off pushing"
+			),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Level {
+	Normal,
+	Derating,
+	Overheated,
+}
+
+#[derive(Default)]
+pub struct ThermalGuard {
+	pub config: ThermalConfig,
+	scale: f64,
+	level: Option<Level>,
+	/// When each motor (by port) started drawing high current, and whether it's been warned about
+	high_current: Vec<(u8, Instant, bool)>,
+	last_read: Option<Instant>,
+}
+
+impl ThermalGuard {
+	pub fn new(config: ThermalConfig) -> Self {
+		Self {
+			config,
+			scale: 1.0,
+			..Default::default()
+		}
+	}
+
+	/// What drivetrain output is scaled by right now
+	pub fn scale(&self) -> f64 {
+		if self.level.is_none() { 1.0 } else { self.scale }
+	}
+
+	pub fn apply(&self, (left, right): (f64, f64)) -> (f64, f64) {
+		(left * self.scale(), right * self.scale())
+	}
+
+	/// Whether it's time for another reading
+	pub fn is_due(&self, now: Instant) -> bool {
+		self.last_read
+			.is_none_or(|last| now.duration_since(last) >= self.config.interval)
+	}
+
+	/// Updates the derating from a reading of every motor, returning anything worth telling the
+	/// driver about
+	pub fn step(&mut self, readings: &[MotorReading], now: Instant) -> Vec<ThermalEvent> {
+		self.last_read = Some(now);
+		let mut events = Vec::new();
+
+		let Some(hottest) = readings
+			.iter()
+			.max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+			.copied()
+		else {
+			return events;
+		};
+
+		self.scale = self.config.scale(hottest.temperature);
+		let level = if hottest.temperature >= self.config.derate_end {
+			Level::Overheated
+		} else if hottest.temperature > self.config.derate_start {
+			Level::Derating
+		} else {
+			Level::Normal
+		};
+
+		let kind = match (self.level.unwrap_or(Level::Normal), level) {
+			(from, to) if from == to => None,
+			(_, Level::Overheated) => Some(ThermalEventKind::Overheated),
+			(Level::Normal, Level::Derating) => Some(ThermalEventKind::Derating),
+			(_, Level::Normal) => Some(ThermalEventKind::Cooled),
+			// Cooling from overheated to derating isn't worth a warning
+			(_, Level::Derating) => None,
+		};
+		if let Some(kind) = kind {
+			events.push(ThermalEvent {
+				kind,
+				motor: hottest,
+				scale: self.scale,
+			});
+		}
+		self.level = Some(level);
+
+		// Only keep motors that are still drawing a lot
+		self.high_current.retain(|(port, ..)| {
+			readings
+				.iter()
+				.any(|r| r.port == *port && r.current >= self.config.current)
+		});
+		for reading in readings.iter().filter(|r| r.current >= self.config.current) {
+			match self.high_current.iter_mut().find(|(port, ..)| *port == reading.port) {
+				None => self.high_current.push((reading.port, now, false)),
+				Some((_, since, warned)) => {
+					if !*warned && now.duration_since(*since) >= self.config.current_time {
+						*warned = true;
+						events.push(ThermalEvent {
+							kind: ThermalEventKind::HighCurrent,
+							motor: *reading,
+							scale: self.scale,
+						});
+					}
+				}
+			}
+		}
+
+		events
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn motor(port: u8, temperature: f64) -> MotorReading {
+		MotorReading {
+			port,
+			temperature,
+			current: 1.0,
+		}
+	}
+
+	#[test]
+	fn test_scale() {
+		let config = ThermalConfig::default();
+
+		assert_eq!(config.scale(30.0), 1.0);
+		assert_eq!(config.scale(45.0), 1.0);
+		assert!((config.scale(50.0) - 0.7).abs() < 1e-9);
+		assert!((config.scale(55.0) - 0.4).abs() < 1e-9);
+		assert!((config.scale(70.0) - 0.4).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_derates_from_hottest_motor() {
+		let mut guard = ThermalGuard::new(ThermalConfig::default());
+		let now = Instant::now();
+
+		assert!(guard.step(&[motor(1, 30.0), motor(2, 35.0)], now).is_empty());
+		assert_eq!(guard.apply((1.0, -1.0)), (1.0, -1.0));
+
+		let events = guard.step(&[motor(1, 30.0), motor(2, 50.0)], now);
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].kind, ThermalEventKind::Derating);
+		assert_eq!(events[0].motor.port, 2);
+
+		let (left, right) = guard.apply((1.0, -1.0));
+		assert!((left - 0.7).abs() < 1e-9);
+		assert!((right + 0.7).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_events_on_changes_only() {
+		let mut guard = ThermalGuard::new(ThermalConfig::default());
+		let now = Instant::now();
+
+		let kinds = [50.0, 52.0, 60.0, 61.0, 50.0, 40.0]
+			.map(|temperature| {
+				guard
+					.step(&[motor(1, temperature)], now)
+					.iter()
+					.map(|event| event.kind)
+					.collect::<Vec<_>>()
+			})
+			.concat();
+
+		assert_eq!(
+			kinds,
+			[
+				ThermalEventKind::Derating,
+				ThermalEventKind::Overheated,
+				ThermalEventKind::Cooled,
+			]
+		);
+	}
+
+	#[test]
+	fn test_warns_about_sustained_current_once() {
+		let mut guard = ThermalGuard::new(ThermalConfig::default());
+		let start = Instant::now();
+		let pushing = [MotorReading {
+			port: 3,
+			temperature: 30.0,
+			current: 2.5,
+		}];
+
+		assert!(guard.step(&pushing, start).is_empty());
+		assert!(
+			guard
+				.step(&pushing, start + Duration::from_secs(1))
+				.is_empty()
+		);
+
+		let events = guard.step(&pushing, start + Duration::from_secs(2));
+		assert_eq!(events.len(), 1);
+		assert_eq!(events[0].kind, ThermalEventKind::HighCurrent);
+		assert!(
+			guard
+				.step(&pushing, start + Duration::from_secs(3))
+				.is_empty()
+		);
+	}
+}
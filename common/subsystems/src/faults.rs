@@ -0,0 +1,260 @@
+//! Keeps track of device errors that used to be thrown away with `_ =`, so a disconnected motor
+//! or sensor shows up somewhere.
+//!
+//! A [`FaultRegistry`] is a cheap handle, clones share the same faults. Subsystems record errors
+//! into it against the device that caused them, and the bot reports new faults to the driver.
+
+use std::{
+	cell::RefCell,
+	collections::BTreeMap,
+	fmt::{Debug, Display},
+	rc::Rc,
+	time::{Duration, Instant},
+};
+
+use crate::{ControllableSubsystem, bindings::DriverInput};
+
+/// How long after its last error a device still counts as faulted
+const ACTIVE_WINDOW: Duration = Duration::from_millis(500);
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Fault {
+	pub subsystem: &'static str,
+	pub device: String,
+	/// The most recent error
+	pub error: String,
+	pub first_seen: Instant,
+	pub last_seen: Instant,
+	/// How many errors the device has had
+	pub count: u32,
+}
+
+impl Fault {
+	pub fn is_active(&self, now: Instant) -> bool {
+		now.duration_since(self.last_seen) < ACTIVE_WINDOW
+	}
+}
+
+impl Display for Fault {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"{} {}: {} (x{})",
+			self.subsystem, self.device, self.error, self.count
+		)
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Health {
+	/// Nothing has gone wrong
+	Healthy,
+	/// A device had errors, but has been working since
+	Recovered,
+	/// A device is having errors right now
+	Faulted,
+}
+
+#[derive(Default)]
+struct Inner {
+	faults: Vec<Fault>,
+	/// Faults that started (or came back) since [`FaultRegistry::take_new`] was last called
+	new: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+pub struct FaultRegistry {
+	inner: Rc<RefCell<Inner>>,
+}
+
+impl FaultRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn record(&self, subsystem: &'static str, device: &str, error: impl Debug) {
+		self.record_at(subsystem, device, error, Instant::now());
+	}
+
+	fn record_at(&self, subsystem: &'static str, device: &str, error: impl Debug, now: Instant) {
+		let mut inner = self.inner.borrow_mut();
+		let error = format!("{error:?}");
+
+		let index = match inner
+			.faults
+			.iter()
+			.position(|fault| fault.subsystem == subsystem && fault.device == device)
+		{
+			Some(index) => {
+				let fault = &mut inner.faults[index];
+				let came_back = !fault.is_active(now);
+
+				fault.error = error;
+				fault.last_seen = now;
+				fault.count += 1;
+
+				if !came_back {
+					return;
+				}
+				index
+			}
+			None => {
+				inner.faults.push(Fault {
+					subsystem,
+					device: device.to_string(),
+					error,
+					first_seen: now,
+					last_seen: now,
+					count: 1,
+				});
+				inner.faults.len() - 1
+			}
+		};
+
+		eprintln!("Fault: {}", inner.faults[index]);
+		if !inner.new.contains(&index) {
+			inner.new.push(index);
+		}
+	}
+
+	/// Records the error if there is one, returning the value otherwise
+	pub fn check<T, E: Debug>(
+		&self,
+		subsystem: &'static str,
+		device: &str,
+		result: Result<T, E>,
+	) -> Option<T> {
+		result
+			.inspect_err(|e| self.record(subsystem, device, e))
+			.ok()
+	}
+
+	/// Every device that's had errors, in the order they first happened
+	pub fn faults(&self) -> Vec<Fault> {
+		self.inner.borrow().faults.clone()
+	}
+
+	/// Faults that started (or came back after the device recovered) since this was last called
+	pub fn take_new(&self) -> Vec<Fault> {
+		let mut inner = self.inner.borrow_mut();
+		let new = std::mem::take(&mut inner.new);
+
+		new.into_iter().map(|i| inner.faults[i].clone()).collect()
+	}
+
+	pub fn health(&self, subsystem: &str) -> Health {
+		self.health_at(subsystem, Instant::now())
+	}
+
+	fn health_at(&self, subsystem: &str, now: Instant) -> Health {
+		let inner = self.inner.borrow();
+		let mut faults = inner
+			.faults
+			.iter()
+			.filter(|fault| fault.subsystem == subsystem)
+			.peekable();
+
+		if faults.peek().is_none() {
+			Health::Healthy
+		} else if faults.any(|fault| fault.is_active(now)) {
+			Health::Faulted
+		} else {
+			Health::Recovered
+		}
+	}
+
+	pub fn clear(&self) {
+		*self.inner.borrow_mut() = Inner::default();
+	}
+}
+
+/// Records each device's error count alongside the subsystem states, so faults end up in the
+/// recording. Like [`PoseRecorder`](crate::replay::pose::PoseRecorder), it can't be replayed.
+pub struct FaultRecorder<'a>(pub &'a FaultRegistry);
+
+impl ControllableSubsystem for FaultRecorder<'_> {
+	/// Error counts by `"subsystem device"`
+	type State = BTreeMap<String, u32>;
+
+	fn control(&mut self, _input: &DriverInput) {}
+
+	fn state(&self) -> Option<Self::State> {
+		let inner = self.0.inner.borrow();
+		if inner.faults.is_empty() {
+			return None;
+		}
+
+		Some(
+			inner
+				.faults
+				.iter()
+				.map(|fault| (format!("{} {}", fault.subsystem, fault.device), fault.count))
+				.collect(),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_counts_per_device() {
+		let faults = FaultRegistry::new();
+		let now = Instant::now();
+
+		faults.record_at("intake", "top motors", "disconnected", now);
+		faults.record_at("intake", "top motors", "disconnected", now);
+		faults.record_at("intake", "park piston", "disconnected", now);
+
+		let recorded = faults.faults();
+		assert_eq!(recorded.len(), 2);
+		assert_eq!(recorded[0].count, 2);
+		assert_eq!(recorded[1].device, "park piston");
+	}
+
+	#[test]
+	fn test_take_new_reports_once() {
+		let faults = FaultRegistry::new();
+		let now = Instant::now();
+
+		faults.record_at("drivetrain", "drive motors", "disconnected", now);
+		faults.record_at("drivetrain", "drive motors", "disconnected", now);
+		assert_eq!(faults.take_new().len(), 1);
+		assert!(faults.take_new().is_empty());
+
+		// Comes back after the device was working for a while
+		faults.record_at(
+			"drivetrain",
+			"drive motors",
+			"disconnected",
+			now + Duration::from_secs(1),
+		);
+		assert_eq!(faults.take_new().len(), 1);
+	}
+
+	#[test]
+	fn test_health() {
+		let faults = FaultRegistry::new();
+		let now = Instant::now();
+
+		assert_eq!(faults.health_at("intake", now), Health::Healthy);
+
+		faults.record_at("intake", "top motors", "disconnected", now);
+		assert_eq!(faults.health_at("intake", now), Health::Faulted);
+		assert_eq!(faults.health_at("pneumatics", now), Health::Healthy);
+		assert_eq!(
+			faults.health_at("intake", now + Duration::from_secs(1)),
+			Health::Recovered
+		);
+	}
+
+	#[test]
+	fn test_check() {
+		let faults = FaultRegistry::new();
+
+		assert_eq!(faults.check("intake", "sensor", Ok::<_, ()>(5)), Some(5));
+		assert_eq!(faults.check("intake", "sensor", Err::<i32, _>(())), None);
+		assert_eq!(faults.faults().len(), 1);
+	}
+}
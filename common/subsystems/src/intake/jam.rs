@@ -0,0 +1,331 @@
+//! Notices when an intake stage has jammed, from it drawing current without turning, and backs
+//! it off to clear the jam.
+
+use std::{
+	fmt::Display,
+	time::{Duration, Instant},
+};
+
+use super::IntakeState;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stage {
+	Top,
+	Middle,
+	Bottom,
+}
+
+impl Stage {
+	pub const ALL: [Stage; 3] = [Stage::Top, Stage::Middle, Stage::Bottom];
+
+	/// This stage's part of an intake state
+	pub fn of(self, state: &IntakeState) -> f64 {
+		match self {
+			Stage::Top => state.top,
+			Stage::Middle => state.middle,
+			Stage::Bottom => state.bottom,
+		}
+	}
+
+	/// What faults from this stage's motors are recorded as
+	pub fn device(self) -> &'static str {
+		match self {
+			Stage::Top => "top motors",
+			Stage::Middle => "middle motors",
+			Stage::Bottom => "bottom motors",
+		}
+	}
+
+	fn of_mut(self, state: &mut IntakeState) -> &mut f64 {
+		match self {
+			Stage::Top => &mut state.top,
+			Stage::Middle => &mut state.middle,
+			Stage::Bottom => &mut state.bottom,
+		}
+	}
+}
+
+impl Display for Stage {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			Stage::Top => "top",
+			Stage::Middle => "middle",
+			Stage::Bottom => "bottom",
+		})
+	}
+}
+
+/// What to do once a jam is detected
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Recovery {
+	/// Only log jams
+	None,
+	/// Run every stage in reverse at `power`
+	ReversePulse { power: f64, duration: Duration },
+	/// Run only the jammed stage in reverse at `power`, leaving the others as they were
+	StageBackoff { power: f64, duration: Duration },
+}
+
+impl Recovery {
+	fn duration(self) -> Duration {
+		match self {
+			Recovery::None => Duration::ZERO,
+			Recovery::ReversePulse { duration, .. } | Recovery::StageBackoff { duration, .. } => {
+				duration
+			}
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct JamConfig {
+	/// Current draw (in amps) at or above which a stage might be jammed
+	pub current: f64,
+	/// Speed (in RPM) below which a stage isn't turning
+	pub velocity: f64,
+	/// Stages commanded at less than this (0.0 to 1.0) aren't expected to turn
+	pub min_command: f64,
+	/// How long a stage has to look jammed for before it counts
+	pub detect_time: Duration,
+	pub recovery: Recovery,
+}
+
+impl Default for JamConfig {
+	fn default() -> Self {
+		Self {
+			current: 1.8,
+			velocity: 30.0,
+			min_command: 0.3,
+			detect_time: Duration::from_millis(200),
+			// The same as the old timed jiggle, just only when it's needed
+			recovery: Recovery::ReversePulse {
+				power: 0.3,
+				duration: Duration::from_millis(300),
+			},
+		}
+	}
+}
+
+/// How a stage is doing, as of one reading of its motors
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct StageReading {
+	/// Amps
+	pub current: f64,
+	/// RPM
+	pub velocity: f64,
+	/// What the stage is being run at (-1.0 to 1.0)
+	pub command: f64,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct JamEvent {
+	pub stage: Stage,
+	pub reading: StageReading,
+	pub recovery: Recovery,
+}
+
+impl Display for JamEvent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"intake {} stage jammed ({:.1}A at {:.0}rpm, running at {:.2})",
+			self.stage, self.reading.current, self.reading.velocity, self.reading.command
+		)?;
+
+		match self.recovery {
+			Recovery::None => Ok(()),
+			Recovery::ReversePulse { duration, .. } => {
+				write!(f, ", reversing for {}ms", duration.as_millis())
+			}
+			Recovery::StageBackoff { duration, .. } => {
+				write!(f, ", backing off for {}ms", duration.as_millis())
+			}
+		}
+	}
+}
+
+#[derive(Default)]
+pub struct JamDetector {
+	pub config: JamConfig,
+	stalled_since: [Option<Instant>; 3],
+	/// The jam being recovered from, and when recovery ends
+	recovering: Option<(JamEvent, Instant)>,
+	jams: u32,
+}
+
+impl JamDetector {
+	/// How many jams have been detected
+	pub fn jams(&self) -> u32 {
+		self.jams
+	}
+
+	pub fn is_recovering(&self, now: Instant) -> bool {
+		self.recovering.is_some_and(|(_, until)| now < until)
+	}
+
+	/// Checks each stage (in [`Stage::ALL`] order), returning a jam if one was just detected
+	pub fn step(&mut self, readings: [StageReading; 3], now: Instant) -> Option<JamEvent> {
+		if self.is_recovering(now) {
+			return None;
+		}
+		self.recovering = None;
+
+		for (stage, reading) in Stage::ALL.into_iter().zip(readings) {
+			let stalled = reading.command.abs() >= self.config.min_command
+				&& reading.current >= self.config.current
+				&& reading.velocity.abs() < self.config.velocity;
+			let since = &mut self.stalled_since[stage as usize];
+
+			match (stalled, *since) {
+				(false, _) => *since = None,
+				(true, None) => *since = Some(now),
+				(true, Some(start)) if now - start >= self.config.detect_time => {
+					let event = JamEvent {
+						stage,
+						reading,
+						recovery: self.config.recovery,
+					};
+
+					self.jams += 1;
+					self.stalled_since = Default::default();
+					self.recovering = Some((event, now + self.config.recovery.duration()));
+					return Some(event);
+				}
+				(true, Some(_)) => {}
+			}
+		}
+
+		None
+	}
+
+	/// What to actually run the intake at, which backs off while recovering from a jam
+	pub fn apply(&self, mut state: IntakeState, now: Instant) -> IntakeState {
+		let Some((event, until)) = self.recovering else {
+			return state;
+		};
+		if now >= until {
+			return state;
+		}
+
+		match event.recovery {
+			Recovery::None => state,
+			Recovery::ReversePulse { power, .. } => {
+				// Back the way each stage was going, or as if intaking if it was stopped
+				for stage in Stage::ALL {
+					let value = stage.of_mut(&mut state);
+					*value = -power * if *value < 0.0 { -1.0 } else { 1.0 };
+				}
+				state
+			}
+			Recovery::StageBackoff { power, .. } => {
+				let value = event.stage.of_mut(&mut state);
+				*value = -power * event.reading.command.signum();
+				state
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const JAMMED: StageReading = StageReading {
+		current: 2.3,
+		velocity: 5.0,
+		command: 1.0,
+	};
+	const TURNING: StageReading = StageReading {
+		current: 2.3,
+		velocity: 400.0,
+		command: 1.0,
+	};
+
+	#[test]
+	fn test_detects_after_detect_time() {
+		let mut detector = JamDetector::default();
+		let start = Instant::now();
+		let readings = [TURNING, JAMMED, TURNING];
+
+		assert_eq!(detector.step(readings, start), None);
+		assert_eq!(
+			detector.step(readings, start + Duration::from_millis(100)),
+			None
+		);
+
+		let event = detector
+			.step(readings, start + Duration::from_millis(200))
+			.unwrap();
+		assert_eq!(event.stage, Stage::Middle);
+		assert_eq!(detector.jams(), 1);
+	}
+
+	#[test]
+	fn test_ignores_stopped_and_turning_stages() {
+		let mut detector = JamDetector::default();
+		let start = Instant::now();
+		let stopped = StageReading {
+			command: 0.0,
+			..JAMMED
+		};
+
+		for ms in [0, 250, 500] {
+			assert_eq!(
+				detector.step([TURNING, stopped, TURNING], start + Duration::from_millis(ms)),
+				None
+			);
+		}
+	}
+
+	#[test]
+	fn test_recovery_overrides_state() {
+		let start = Instant::now();
+		let mut detector = JamDetector::default();
+		let readings = [JAMMED, TURNING, TURNING];
+		detector.step(readings, start);
+		detector.step(readings, start + Duration::from_millis(200));
+
+		let during = start + Duration::from_millis(300);
+		assert_eq!(
+			detector.apply(IntakeState::full_forward(), during),
+			IntakeState::full_forward() * -0.3
+		);
+		assert_eq!(detector.step(readings, during), None);
+
+		let after = start + Duration::from_millis(600);
+		assert_eq!(
+			detector.apply(IntakeState::full_forward(), after),
+			IntakeState::full_forward()
+		);
+	}
+
+	#[test]
+	fn test_stage_backoff_only_moves_jammed_stage() {
+		let start = Instant::now();
+		let mut detector = JamDetector {
+			config: JamConfig {
+				recovery: Recovery::StageBackoff {
+					power: 0.5,
+					duration: Duration::from_millis(250),
+				},
+				..Default::default()
+			},
+			..Default::default()
+		};
+		let readings = [TURNING, TURNING, JAMMED];
+		detector.step(readings, start);
+		detector.step(readings, start + Duration::from_millis(200));
+
+		assert_eq!(
+			detector.apply(
+				IntakeState::full_forward(),
+				start + Duration::from_millis(300)
+			),
+			IntakeState {
+				top: 1.0,
+				middle: 1.0,
+				bottom: -0.5,
+			}
+		);
+	}
+}
@@ -0,0 +1,635 @@
+pub mod jam;
+pub mod park;
+pub mod sorting;
+
+use std::{
+	cell::RefCell,
+	fmt::Debug,
+	ops::Mul,
+	pin::pin,
+	rc::{Rc, Weak},
+	time::{Duration, Instant},
+};
+
+use evian_extra::voltage::VoltageCompensation;
+use futures_util::{
+	future::{Either, select},
+	pending,
+};
+use serde::{Deserialize, Serialize};
+use vexide::{
+	prelude::{DistanceSensor, OpticalSensor},
+	smart::{PortError, motor::BrakeMode},
+	time::sleep,
+};
+use vexide_motorgroup::MotorGroup;
+
+use crate::{
+	ControllableSubsystem,
+	bindings::{Action, DriverInput},
+	faults::FaultRegistry,
+	intake::{
+		jam::{JamConfig, JamDetector, Stage, StageReading},
+		park::{ParkConfig, ParkError, ParkPhase},
+		sorting::{Alliance, BlockCounts, Sample, Sorter},
+	},
+	pnemuatics::{AdiPneumatic, PneumaticState},
+	replay::interpolate::Interpolate,
+};
+
+/// How often the sensors are read and the intake updated in the background
+const UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Runs the intake at a state with unjamming enabled while an async block runs.
+///
+/// The block gets the robot mutably as `$r`, so it can drive (or run the intake) as usual.
+#[macro_export]
+macro_rules! intake_unjamming {
+	($robot:expr, $state:expr, $r:ident => async $block:block) => {{
+		let intake = $robot.intake.clone();
+		intake
+			.run_unjamming($state, async {
+				#[allow(unused_mut, unused_variables)]
+				let mut $r = &mut *$robot;
+				$block
+			})
+			.await;
+	}};
+}
+
+pub struct IntakeMotors {
+	pub bottom: MotorGroup,
+	pub middle: MotorGroup,
+	pub top: MotorGroup,
+}
+
+impl IntakeMotors {
+	pub fn stage(&self, stage: Stage) -> &MotorGroup {
+		match stage {
+			Stage::Top => &self.top,
+			Stage::Middle => &self.middle,
+			Stage::Bottom => &self.bottom,
+		}
+	}
+
+	pub fn stage_mut(&mut self, stage: Stage) -> &mut MotorGroup {
+		match stage {
+			Stage::Top => &mut self.top,
+			Stage::Middle => &mut self.middle,
+			Stage::Bottom => &mut self.bottom,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+/// Intake state, 1.0 = max forward, -1.0 = max reverse, 0.0 = stop
+pub struct IntakeState {
+	pub top: f64,
+	pub middle: f64,
+	pub bottom: f64,
+}
+
+impl IntakeState {
+	pub const fn full_forward() -> Self {
+		Self {
+			top: 1.0,
+			middle: 1.0,
+			bottom: 1.0,
+		}
+	}
+
+	pub const fn full_brake() -> Self {
+		Self {
+			top: 0.0,
+			middle: 0.0,
+			bottom: 0.0,
+		}
+	}
+
+	pub const fn reverse(self) -> Self {
+		Self {
+			top: -self.top,
+			middle: -self.middle,
+			bottom: -self.bottom,
+		}
+	}
+}
+
+impl Interpolate for IntakeState {
+	fn lerp(&self, other: &Self, t: f64) -> Self {
+		Self {
+			top: self.top.lerp(&other.top, t),
+			middle: self.middle.lerp(&other.middle, t),
+			bottom: self.bottom.lerp(&other.bottom, t),
+		}
+	}
+}
+
+impl Mul<f64> for IntakeState {
+	type Output = Self;
+
+	fn mul(mut self, rhs: f64) -> Self::Output {
+		self.top *= rhs;
+		self.middle *= rhs;
+		self.bottom *= rhs;
+
+		self
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ParkState {
+	Disabled,
+	Outtaking,
+	/// Outtaking without the park sensor, until `until`
+	TimedOuttake {
+		until: Instant,
+	},
+	Parked,
+	Manual,
+}
+
+impl ParkState {
+	fn phase(self) -> Option<ParkPhase> {
+		match self {
+			ParkState::Outtaking => Some(ParkPhase::Outtaking),
+			ParkState::TimedOuttake { .. } => Some(ParkPhase::TimedOuttake),
+			ParkState::Parked => Some(ParkPhase::Parked),
+			ParkState::Disabled | ParkState::Manual => None,
+		}
+	}
+}
+
+/// Counts and sorts blocks, except while parking, when the park sensor is looking at the park
+/// zone rather than blocks
+fn sort(
+	sorter: &mut Sorter,
+	park_state: ParkState,
+	sample: Sample,
+	state: IntakeState,
+	now: Instant,
+) {
+	if park_state == ParkState::Disabled {
+		sorter.step(sample, state.bottom > 0.0, now);
+	}
+}
+
+struct IntakeInner {
+	motors: IntakeMotors,
+	state: IntakeState,
+	park_state: ParkState,
+	park_sensor: OpticalSensor,
+	park_piston: AdiPneumatic,
+	park_config: ParkConfig,
+	last_jiggle: Option<Instant>,
+	sorter: Sorter,
+	jam: JamDetector,
+	/// What the motors were last set to, after sorting, unjamming and jiggling
+	applied: IntakeState,
+	faults: FaultRegistry,
+	compensation: Option<VoltageCompensation>,
+}
+
+impl IntakeInner {
+	fn run(&mut self, state: IntakeState) {
+		match self.park_state {
+			ParkState::Disabled => {
+				self.set_piston(PneumaticState::Contracted);
+				let now = Instant::now();
+				let state = self.jam.apply(self.sorter.apply(state, now), now);
+
+				let coeff = match self.last_jiggle.map(|j| j.elapsed()) {
+					Some(last_jiggle) if last_jiggle >= Duration::from_millis(1300) => {
+						self.last_jiggle = Some(Instant::now());
+						1.0
+					}
+					Some(last_jiggle) if last_jiggle >= Duration::from_secs(1) => -0.3,
+					_ => 1.0,
+				};
+
+				self.applied = state * coeff;
+				for stage in Stage::ALL {
+					let power = self.compensated(stage.of(&self.applied));
+					let motors = self.motors.stage_mut(stage);
+					let result = motors.set_voltage(power * motors.max_voltage());
+					self.check_stage(stage, result);
+				}
+			}
+			ParkState::Outtaking => match self.park_sensor.proximity() {
+				Ok(proximity) if proximity < self.park_config.proximity => self.outtake(),
+				Ok(_) => self.park(),
+				Err(e) => {
+					eprintln!(
+						"Park sensor failed ({e}), outtaking for {}ms instead",
+						self.park_config.fallback_time.as_millis()
+					);
+					self.faults.record("intake", "park sensor", e);
+					self.park_state = ParkState::TimedOuttake {
+						until: Instant::now() + self.park_config.fallback_time,
+					};
+					self.outtake();
+				}
+			},
+			ParkState::TimedOuttake { until } => {
+				if Instant::now() < until {
+					self.outtake();
+				} else {
+					self.park();
+				}
+			}
+			ParkState::Parked => {
+				for (stage, mode) in [
+					(Stage::Top, BrakeMode::Coast),
+					(Stage::Middle, BrakeMode::Coast),
+					(Stage::Bottom, BrakeMode::Hold),
+				] {
+					let result = self.motors.stage_mut(stage).brake(mode);
+					self.check_stage(stage, result);
+				}
+			}
+			ParkState::Manual => {
+				self.set_piston(PneumaticState::Extended);
+			}
+		}
+	}
+
+	/// Runs the intake backwards to bring the robot into position over the park zone
+	fn outtake(&mut self) {
+		let power = self.compensated(self.park_config.outtake_power);
+
+		self.set_piston(PneumaticState::Contracted);
+		for stage in [Stage::Top, Stage::Middle] {
+			let motors = self.motors.stage_mut(stage);
+			let result = motors.set_voltage(-power * motors.max_voltage());
+			self.check_stage(stage, result);
+		}
+		let result = self
+			.motors
+			.bottom
+			.set_velocity(-self.park_config.bottom_velocity);
+		self.check_stage(Stage::Bottom, result);
+	}
+
+	fn park(&mut self) {
+		self.park_state = ParkState::Parked;
+		self.set_piston(PneumaticState::Extended);
+		let result = self.motors.bottom.brake(BrakeMode::Hold);
+		self.check_stage(Stage::Bottom, result);
+	}
+
+	fn compensated(&self, fraction: f64) -> f64 {
+		match &self.compensation {
+			Some(compensation) => compensation.apply(fraction),
+			None => fraction,
+		}
+	}
+
+	fn set_piston(&mut self, state: PneumaticState) {
+		let result = self.park_piston.set_state(state);
+		self.faults.check("intake", "park piston", result);
+	}
+
+	fn check_stage<E: Debug>(&self, stage: Stage, result: Result<(), E>) {
+		self.faults.check("intake", stage.device(), result);
+	}
+
+	fn stage_readings(&self) -> [StageReading; 3] {
+		Stage::ALL.map(|stage| {
+			let motors = self.motors.stage(stage);
+
+			StageReading {
+				current: motors.current().unwrap_or_default(),
+				velocity: motors.velocity().unwrap_or_default(),
+				command: stage.of(&self.applied),
+			}
+		})
+	}
+}
+
+/// A handle to the intake. Clones share the same motors and state, so a background behavior (like
+/// unjamming) can run the intake while something else holds the robot.
+#[derive(Clone)]
+pub struct IntakeSubsystem {
+	inner: Rc<RefCell<IntakeInner>>,
+}
+
+impl IntakeSubsystem {
+	pub fn new(
+		motors: IntakeMotors,
+		park_sensor: OpticalSensor,
+		park_piston: AdiPneumatic,
+	) -> Self {
+		let this = Self {
+			inner: Rc::new(RefCell::new(IntakeInner {
+				motors,
+				state: IntakeState::full_brake(),
+				park_state: ParkState::Disabled,
+				park_sensor,
+				park_piston,
+				park_config: ParkConfig::default(),
+				last_jiggle: None,
+				sorter: Sorter::default(),
+				jam: JamDetector::default(),
+				applied: IntakeState::full_brake(),
+				faults: FaultRegistry::new(),
+				compensation: None,
+			})),
+		};
+
+		vexide::task::spawn(Self::background_task(Rc::downgrade(&this.inner))).detach();
+
+		this
+	}
+
+	/// Keeps the intake running at its last state, so sorting and parking carry on between calls
+	/// to [`Self::run`]. Stops once every handle is dropped.
+	async fn background_task(weak: Weak<RefCell<IntakeInner>>) {
+		while let Some(inner) = weak.upgrade() {
+			// Something else might be holding the sensor or piston, it'll get updated next time
+			if let Ok(mut inner) = inner.try_borrow_mut() {
+				let sample = inner.sorter.sample(&inner.park_sensor);
+				let state = inner.state;
+				let park_state = inner.park_state;
+
+				let now = Instant::now();
+				sort(&mut inner.sorter, park_state, sample, state, now);
+				// Parking holds and brakes the motors, which isn't a jam
+				let readings = inner.stage_readings();
+				if inner.park_state == ParkState::Disabled
+					&& let Some(jam) = inner.jam.step(readings, now)
+				{
+					println!("Warning: {jam}");
+				}
+				inner.run(state);
+			}
+
+			drop(inner);
+			sleep(UPDATE_INTERVAL).await;
+		}
+	}
+
+	pub fn run(&self, state: IntakeState) {
+		let mut inner = self.inner.borrow_mut();
+		inner.state = state;
+		inner.run(state);
+	}
+
+	/// Blocks counted since the last reset
+	pub fn counts(&self) -> BlockCounts {
+		self.inner.borrow().sorter.counts()
+	}
+
+	pub fn reset_counts(&self) {
+		self.inner.borrow_mut().sorter.reset_counts();
+	}
+
+	pub fn alliance(&self) -> Option<Alliance> {
+		self.inner.borrow().sorter.alliance
+	}
+
+	/// Sets which alliance's blocks to keep, ejecting the other's, or `None` to keep everything
+	pub fn set_alliance(&self, alliance: Option<Alliance>) {
+		self.inner.borrow_mut().sorter.alliance = alliance;
+	}
+
+	/// How many jams have been detected
+	pub fn jams(&self) -> u32 {
+		self.inner.borrow().jam.jams()
+	}
+
+	pub fn set_jam_config(&self, config: JamConfig) {
+		self.inner.borrow_mut().jam.config = config;
+	}
+
+	/// Records motor, sensor and piston errors into `faults`
+	pub fn set_faults(&self, faults: FaultRegistry) {
+		self.inner.borrow_mut().faults = faults;
+	}
+
+	/// Scales stage power against the battery's voltage, or `None` to run at the fractions as given
+	pub fn set_voltage_compensation(&self, compensation: Option<VoltageCompensation>) {
+		self.inner.borrow_mut().compensation = compensation;
+	}
+
+	/// Detects blocks with a distance sensor as well as the optical sensor's proximity
+	pub fn set_distance_sensor(&self, sensor: Option<DistanceSensor>) {
+		self.inner.borrow_mut().sorter.distance_sensor = sensor;
+	}
+
+	/// Waits until `count` more blocks have come through the intake
+	pub async fn wait_for_blocks(&self, count: u32) {
+		let target = self.counts().total + count;
+		while self.counts().total < target {
+			sleep(UPDATE_INTERVAL).await;
+		}
+	}
+
+	pub fn enable_unjam(&self) {
+		self.inner.borrow_mut().last_jiggle = Some(Instant::now());
+	}
+
+	pub fn disable_unjam(&self) {
+		self.inner.borrow_mut().last_jiggle = None;
+	}
+
+	/// The park sensor's proximity reading, higher the closer something is
+	pub fn park_proximity(&self) -> Result<f64, PortError> {
+		self.inner.borrow().park_sensor.proximity()
+	}
+
+	pub fn set_park_piston(&self, state: PneumaticState) -> Result<(), PortError> {
+		self.inner.borrow_mut().park_piston.set_state(state)
+	}
+
+	pub fn set_park_config(&self, config: ParkConfig) {
+		self.inner.borrow_mut().park_config = config;
+	}
+
+	/// How far parking has got, or `None` if the intake isn't parking
+	pub fn park_phase(&self) -> Option<ParkPhase> {
+		self.inner.borrow().park_state.phase()
+	}
+
+	/// Starts parking, leaving the background task to bring it in and fire the piston
+	pub fn start_autopark(&self) {
+		self.inner.borrow_mut().park_state = ParkState::Outtaking;
+	}
+
+	/// Stops autoparking, or lets go if it's finished. Manual parking is left alone.
+	pub fn unpark(&self) {
+		let mut inner = self.inner.borrow_mut();
+		if inner.park_state.phase().is_some() {
+			inner.park_state = ParkState::Disabled;
+		}
+	}
+
+	/// Stops parking, if it hasn't finished already
+	pub fn cancel_autopark(&self) {
+		let mut inner = self.inner.borrow_mut();
+		if matches!(
+			inner.park_state,
+			ParkState::Outtaking | ParkState::TimedOuttake { .. }
+		) {
+			inner.park_state = ParkState::Disabled;
+		}
+	}
+
+	/// Outtakes until the park sensor sees the robot is in position (or for a fixed time if the
+	/// sensor isn't working), then fires the park piston.
+	///
+	/// Dropping the future cancels parking, as does the driver toggling it off.
+	pub async fn run_autopark(&self) -> Result<(), ParkError> {
+		/// Cancels parking if the future is dropped part way through
+		struct CancelOnDrop<'a>(&'a IntakeSubsystem);
+
+		impl Drop for CancelOnDrop<'_> {
+			fn drop(&mut self) {
+				self.0.cancel_autopark();
+			}
+		}
+
+		self.start_autopark();
+		let timeout = self.inner.borrow().park_config.timeout;
+		let _cancel = CancelOnDrop(self);
+		let start = Instant::now();
+		let mut last_phase = None;
+
+		loop {
+			let phase = self.park_phase().ok_or(ParkError::Cancelled)?;
+			if last_phase != Some(phase) {
+				println!("Autopark: {phase}");
+				last_phase = Some(phase);
+			}
+
+			if phase == ParkPhase::Parked {
+				return Ok(());
+			}
+			if start.elapsed() > timeout {
+				return Err(ParkError::Timeout { phase });
+			}
+
+			sleep(UPDATE_INTERVAL).await;
+		}
+	}
+
+	/// Runs the intake at `state` with unjamming enabled until `fut` completes
+	///
+	/// Dropping the future part way through also turns unjamming off.
+	pub async fn run_unjamming<F: Future>(&self, state: IntakeState, fut: F) -> F::Output {
+		/// Turns unjamming off however the future ends
+		struct DisableOnDrop<'a>(&'a IntakeSubsystem);
+
+		impl Drop for DisableOnDrop<'_> {
+			fn drop(&mut self) {
+				self.0.disable_unjam();
+			}
+		}
+
+		self.enable_unjam();
+		let _disable = DisableOnDrop(self);
+
+		let unjam = async {
+			loop {
+				self.run(state);
+				pending!()
+			}
+		};
+
+		match select(pin!(fut), pin!(unjam)).await {
+			Either::Left((output, _)) => output,
+			Either::Right(_) => unreachable!("Unjamming should never finish"),
+		}
+	}
+}
+
+impl ControllableSubsystem for IntakeSubsystem {
+	type State = IntakeState;
+
+	fn direct(&mut self, state: Self::State) {
+		let mut inner = self.inner.borrow_mut();
+		inner.state = state;
+
+		inner.run(state);
+	}
+
+	fn interpolate(&self, from: &Self::State, to: &Self::State, t: f64) -> Option<Self::State> {
+		Some(from.lerp(to, t))
+	}
+
+	fn control(&mut self, input: &DriverInput) {
+		let mut inner = self.inner.borrow_mut();
+
+		inner.state = if input.is_pressed(Action::Intake) {
+			// Normal intake
+			// IntakeState {
+			// 	bottom: 1.0,
+			// 	middle: 1.0,
+			// 	top: 0.0,
+			// }
+			IntakeState::full_forward()
+		} else if input.is_pressed(Action::LongGoal) {
+			// Long goal outtake
+			IntakeState::full_forward()
+		} else if input.is_pressed(Action::CenterGoalTop) {
+			// Run at half speed for top center goal outtake
+			IntakeState {
+				top: 0.5,
+				middle: 1.0,
+				bottom: 1.0,
+			}
+		} else if input.is_pressed(Action::CenterGoalBottom) {
+			// Run at half speed for bottom center goal outtake
+			IntakeState {
+				top: -1.0,
+				middle: -1.0,
+				bottom: -0.5,
+			}
+		} else {
+			IntakeState::full_brake()
+		};
+
+		// Autoparking and unjamming are commands, bound in the robot's scheduler
+		if input.is_now_pressed(Action::ManualPark) {
+			inner.park_state = match inner.park_state {
+				ParkState::Disabled => ParkState::Manual,
+				_ => ParkState::Disabled,
+			}
+		}
+
+		let state = inner.state;
+		inner.run(state);
+	}
+
+	fn state(&self) -> Option<Self::State> {
+		Some(self.inner.borrow().state)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_doesnt_sort_while_parking() {
+		let mut sorter = Sorter {
+			alliance: Some(Alliance::Red),
+			..Default::default()
+		};
+		let now = Instant::now();
+		let block = Sample {
+			present: true,
+			hue: 220.0,
+			saturation: 0.8,
+		};
+
+		for park_state in [ParkState::Outtaking, ParkState::Parked, ParkState::Manual] {
+			sort(&mut sorter, park_state, block, IntakeState::full_forward(), now);
+		}
+		assert_eq!(sorter.counts(), BlockCounts::default());
+		assert!(!sorter.is_ejecting(now));
+
+		sort(&mut sorter, ParkState::Disabled, block, IntakeState::full_forward(), now);
+		assert_eq!(sorter.counts().blue, 1);
+		assert!(sorter.is_ejecting(now));
+	}
+}
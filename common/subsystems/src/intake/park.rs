@@ -0,0 +1,70 @@
+//! Settings and results for parking with the intake.
+
+use std::{fmt::Display, time::Duration};
+
+/// How a bot parks, set with [`IntakeSubsystem::set_park_config`](super::IntakeSubsystem::set_park_config)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParkConfig {
+	/// Park sensor proximity (0.0 to 1.0) at which the robot is in position to park
+	pub proximity: f64,
+	/// How fast to run the top and middle stages backwards (0.0 to 1.0)
+	pub outtake_power: f64,
+	/// How fast to run the bottom stage backwards, in RPM
+	pub bottom_velocity: i32,
+	/// How long to outtake for if the park sensor stops working
+	pub fallback_time: Duration,
+	/// How long autopark has before it gives up
+	pub timeout: Duration,
+}
+
+impl Default for ParkConfig {
+	fn default() -> Self {
+		Self {
+			proximity: 0.85,
+			outtake_power: 1.0,
+			bottom_velocity: 100,
+			fallback_time: Duration::from_millis(800),
+			timeout: Duration::from_secs(3),
+		}
+	}
+}
+
+/// Where parking has got to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParkPhase {
+	/// Outtaking until the park sensor sees the robot is in position
+	Outtaking,
+	/// Outtaking for [`ParkConfig::fallback_time`], since the park sensor isn't working
+	TimedOuttake,
+	Parked,
+}
+
+impl Display for ParkPhase {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			ParkPhase::Outtaking => "outtaking",
+			ParkPhase::TimedOuttake => "outtaking (timed)",
+			ParkPhase::Parked => "parked",
+		})
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParkError {
+	/// Parking didn't finish in time, having got as far as `phase`
+	Timeout { phase: ParkPhase },
+	/// Parking was cancelled, either by the driver or with
+	/// [`IntakeSubsystem::cancel_autopark`](super::IntakeSubsystem::cancel_autopark)
+	Cancelled,
+}
+
+impl Display for ParkError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Timeout { phase } => write!(f, "autopark timed out while {phase}"),
+			Self::Cancelled => write!(f, "autopark was cancelled"),
+		}
+	}
+}
+
+impl std::error::Error for ParkError {}
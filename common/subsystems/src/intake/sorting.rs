@@ -0,0 +1,281 @@
+//! Counts blocks going through the intake by color, and throws out the other alliance's.
+//!
+//! A block is in front of the sensors while the optical sensor's proximity (or the optional
+//! distance sensor's reading) is past a threshold. Each one is counted as it arrives, then
+//! classified by hue once the optical sensor gets a saturated enough reading of it.
+
+use std::time::{Duration, Instant};
+
+use vexide::prelude::{DistanceSensor, OpticalSensor};
+
+use super::IntakeState;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Alliance {
+	Red,
+	Blue,
+}
+
+impl Alliance {
+	pub fn other(self) -> Self {
+		match self {
+			Alliance::Red => Alliance::Blue,
+			Alliance::Blue => Alliance::Red,
+		}
+	}
+
+	/// The alliance a block's color belongs to, if the reading is saturated enough to tell
+	pub fn from_hue(hue: f64, saturation: f64, min_saturation: f64) -> Option<Self> {
+		if saturation < min_saturation {
+			return None;
+		}
+
+		match hue {
+			// Red wraps around 0°
+			hue if !(30.0..330.0).contains(&hue) => Some(Alliance::Red),
+			180.0..=260.0 => Some(Alliance::Blue),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SortConfig {
+	/// Optical sensor proximity (0.0 to 1.0) past which a block is in front of it
+	pub proximity: f64,
+	/// Distance sensor reading (in mm) under which a block is in front of it
+	pub distance: u32,
+	/// Readings less saturated than this are too washed out to tell the color from
+	pub min_saturation: f64,
+	/// How long to run `eject` for to get rid of a block
+	pub eject_time: Duration,
+	/// What to run the intake at while ejecting
+	pub eject: IntakeState,
+}
+
+impl Default for SortConfig {
+	fn default() -> Self {
+		Self {
+			proximity: 0.5,
+			distance: 60,
+			min_saturation: 0.4,
+			eject_time: Duration::from_millis(300),
+			// Spits blocks out the back between the middle and top rollers
+			eject: IntakeState {
+				top: -1.0,
+				middle: 1.0,
+				bottom: 1.0,
+			},
+		}
+	}
+}
+
+/// How many blocks have gone through the intake since the counts were last reset
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct BlockCounts {
+	pub total: u32,
+	pub red: u32,
+	pub blue: u32,
+	pub ejected: u32,
+}
+
+impl BlockCounts {
+	pub fn of(&self, alliance: Alliance) -> u32 {
+		match alliance {
+			Alliance::Red => self.red,
+			Alliance::Blue => self.blue,
+		}
+	}
+}
+
+/// One reading of the sensors
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sample {
+	pub present: bool,
+	pub hue: f64,
+	pub saturation: f64,
+}
+
+#[derive(Default)]
+pub struct Sorter {
+	/// The alliance to keep blocks of, `None` to keep everything
+	pub alliance: Option<Alliance>,
+	pub config: SortConfig,
+	pub distance_sensor: Option<DistanceSensor>,
+	counts: BlockCounts,
+	present: bool,
+	/// The color of the block in front of the sensors, once it's known
+	color: Option<Alliance>,
+	/// A block needs ejecting, but the intake wasn't running to do it
+	eject_pending: bool,
+	ejecting_until: Option<Instant>,
+}
+
+impl Sorter {
+	pub fn counts(&self) -> BlockCounts {
+		self.counts
+	}
+
+	pub fn reset_counts(&mut self) {
+		self.counts = BlockCounts::default();
+	}
+
+	pub fn is_ejecting(&self, now: Instant) -> bool {
+		self.ejecting_until.is_some_and(|until| now < until)
+	}
+
+	pub fn sample(&self, optical: &OpticalSensor) -> Sample {
+		let near_distance = self
+			.distance_sensor
+			.as_ref()
+			.and_then(|sensor| sensor.object().ok().flatten())
+			.is_some_and(|object| object.distance <= self.config.distance);
+		let near_optical = optical
+			.proximity()
+			.is_ok_and(|proximity| proximity >= self.config.proximity);
+
+		Sample {
+			present: near_distance || near_optical,
+			hue: optical.hue().unwrap_or_default(),
+			saturation: optical.saturation().unwrap_or_default(),
+		}
+	}
+
+	/// Counts and classifies blocks from a sample. `running` is whether the intake is pulling
+	/// blocks in, since ejecting waits for that.
+	pub fn step(&mut self, sample: Sample, running: bool, now: Instant) {
+		if sample.present && !self.present {
+			self.counts.total += 1;
+			self.color = None;
+		}
+		self.present = sample.present;
+
+		if sample.present
+			&& self.color.is_none()
+			&& let Some(color) =
+				Alliance::from_hue(sample.hue, sample.saturation, self.config.min_saturation)
+		{
+			self.color = Some(color);
+			match color {
+				Alliance::Red => self.counts.red += 1,
+				Alliance::Blue => self.counts.blue += 1,
+			}
+
+			if self.alliance == Some(color.other()) {
+				self.counts.ejected += 1;
+				self.eject_pending = true;
+			}
+		}
+
+		if self.eject_pending && running {
+			self.eject_pending = false;
+			self.ejecting_until = Some(now + self.config.eject_time);
+		}
+	}
+
+	/// What to actually run the intake at, which is the eject state while ejecting
+	pub fn apply(&self, state: IntakeState, now: Instant) -> IntakeState {
+		if self.is_ejecting(now) {
+			self.config.eject
+		} else {
+			state
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn block(hue: f64) -> Sample {
+		Sample {
+			present: true,
+			hue,
+			saturation: 0.8,
+		}
+	}
+
+	const GAP: Sample = Sample {
+		present: false,
+		hue: 0.0,
+		saturation: 0.0,
+	};
+
+	#[test]
+	fn test_from_hue() {
+		assert_eq!(Alliance::from_hue(5.0, 0.8, 0.4), Some(Alliance::Red));
+		assert_eq!(Alliance::from_hue(350.0, 0.8, 0.4), Some(Alliance::Red));
+		assert_eq!(Alliance::from_hue(220.0, 0.8, 0.4), Some(Alliance::Blue));
+		assert_eq!(Alliance::from_hue(120.0, 0.8, 0.4), None);
+		assert_eq!(Alliance::from_hue(220.0, 0.1, 0.4), None);
+	}
+
+	#[test]
+	fn test_counts_each_block_once() {
+		let mut sorter = Sorter::default();
+		let now = Instant::now();
+
+		for sample in [block(10.0), block(12.0), GAP, block(220.0), GAP, GAP, block(0.0)] {
+			sorter.step(sample, true, now);
+		}
+
+		assert_eq!(
+			sorter.counts(),
+			BlockCounts {
+				total: 3,
+				red: 2,
+				blue: 1,
+				ejected: 0,
+			}
+		);
+	}
+
+	#[test]
+	fn test_classifies_late_readings() {
+		let mut sorter = Sorter::default();
+		let now = Instant::now();
+
+		// The edge of the block is too washed out to tell
+		sorter.step(
+			Sample {
+				saturation: 0.1,
+				..block(220.0)
+			},
+			true,
+			now,
+		);
+		sorter.step(block(220.0), true, now);
+
+		assert_eq!(sorter.counts().total, 1);
+		assert_eq!(sorter.counts().blue, 1);
+	}
+
+	#[test]
+	fn test_ejects_other_alliance_once_running() {
+		let mut sorter = Sorter {
+			alliance: Some(Alliance::Red),
+			..Default::default()
+		};
+		let now = Instant::now();
+
+		sorter.step(block(10.0), true, now);
+		assert!(!sorter.is_ejecting(now));
+
+		sorter.step(GAP, true, now);
+		sorter.step(block(220.0), false, now);
+		assert!(!sorter.is_ejecting(now));
+		assert_eq!(
+			sorter.apply(IntakeState::full_brake(), now),
+			IntakeState::full_brake()
+		);
+
+		sorter.step(block(220.0), true, now);
+		assert!(sorter.is_ejecting(now));
+		assert_eq!(
+			sorter.apply(IntakeState::full_forward(), now),
+			sorter.config.eject
+		);
+		assert!(!sorter.is_ejecting(now + sorter.config.eject_time));
+		assert_eq!(sorter.counts().ejected, 1);
+	}
+}
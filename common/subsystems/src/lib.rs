@@ -2,43 +2,37 @@
 
 pub use futures_util;
 
-use vexide::controller::ControllerState;
+use bindings::DriverInput;
+use serde::{Serialize, de::DeserializeOwned};
 
+pub mod bindings;
+pub mod checks;
+pub mod commands;
 pub mod copro;
 pub mod drivetrain;
+pub mod faults;
 pub mod intake;
 pub mod pnemuatics;
 pub mod replay;
-
-#[derive(Clone, Copy, Debug)]
-pub enum ControllerConfiguration {
-	Noah,
-	Connor,
-}
-
-impl ControllerConfiguration {
-	pub fn as_str(&self) -> &'static str {
-		match self {
-			ControllerConfiguration::Noah => "Noah  ",
-			ControllerConfiguration::Connor => "Connor",
-		}
-	}
-
-	pub fn next(&self) -> Self {
-		match self {
-			ControllerConfiguration::Noah => ControllerConfiguration::Connor,
-			ControllerConfiguration::Connor => ControllerConfiguration::Noah,
-		}
-	}
-}
+pub mod wiring;
 
 pub trait ControllableSubsystem {
+	/// The state recorded and replayed for this subsystem
+	type State: Serialize + DeserializeOwned;
+
 	/// Update the subsystem based on controller input
-	fn control(&mut self, controller: &ControllerState, configuration: ControllerConfiguration);
-	/// Update the subsystem based on raw serialized state
-	fn direct(&mut self, _state: &ciborium::Value) {}
+	fn control(&mut self, input: &DriverInput);
+	/// Update the subsystem to a recorded state
+	fn direct(&mut self, _state: Self::State) {}
 	/// Get the current subsystem state (for recording or similar)
-	fn state(&self) -> Option<ciborium::Value> {
+	fn state(&self) -> Option<Self::State> {
+		None
+	}
+	/// Blend between two states, `t` being how far from `from` to `to` (0.0 to 1.0).
+	///
+	/// Continuous state (motor outputs) should override this, discrete state (pistons) should
+	/// leave it returning `None`, which steps to `from` during replays.
+	fn interpolate(&self, _from: &Self::State, _to: &Self::State, _t: f64) -> Option<Self::State> {
 		None
 	}
 }
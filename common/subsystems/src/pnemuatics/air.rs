@@ -0,0 +1,137 @@
+//! Estimates how much air is left from how many times each piston has fired.
+//!
+//! Each actuation fills a cylinder from the tank, which then vents it to atmosphere. Filling it
+//! shares the tank's air with the cylinder's volume, so the tank's gauge pressure drops by a
+//! factor of `tank / (tank + cylinder)` each time.
+
+use std::f64::consts::PI;
+
+use crate::pnemuatics::PneumaticState;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cylinder {
+	/// Millimetres
+	pub bore: f64,
+	/// Millimetres
+	pub stroke: f64,
+	/// Whether it's driven both ways, and so uses air retracting as well as extending
+	pub double_acting: bool,
+}
+
+impl Cylinder {
+	pub const fn new(bore: f64, stroke: f64) -> Self {
+		Self {
+			bore,
+			stroke,
+			double_acting: true,
+		}
+	}
+
+	/// The air one stroke uses, in cm³
+	pub fn volume(&self) -> f64 {
+		PI * (self.bore / 2.0).powi(2) * self.stroke / 1000.0
+	}
+
+	/// Whether moving to `state` draws air from the tank, rather than just venting
+	pub fn uses_air(&self, state: PneumaticState) -> bool {
+		self.double_acting || state == PneumaticState::Extended
+	}
+}
+
+impl Default for Cylinder {
+	fn default() -> Self {
+		Self::new(10.0, 50.0)
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AirTank {
+	/// Total tank volume, in cm³
+	pub volume: f64,
+	/// Gauge pressure (in PSI) when the tank is filled
+	pub pressure: f64,
+	/// Pressure (in PSI) under which non-critical pistons stop firing, if any
+	pub reserve: Option<f64>,
+}
+
+impl Default for AirTank {
+	fn default() -> Self {
+		Self {
+			volume: 200.0,
+			pressure: 100.0,
+			reserve: None,
+		}
+	}
+}
+
+impl AirTank {
+	/// Estimated gauge pressure after each cylinder has used air some number of times
+	pub fn pressure_after(&self, uses: impl IntoIterator<Item = (Cylinder, u32)>) -> f64 {
+		uses.into_iter().fold(self.pressure, |pressure, (cylinder, count)| {
+			pressure * (self.volume / (self.volume + cylinder.volume())).powi(count as i32)
+		})
+	}
+
+	pub fn is_below_reserve(&self, pressure: f64) -> bool {
+		self.reserve.is_some_and(|reserve| pressure < reserve)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_cylinder_volume() {
+		let cylinder = Cylinder::new(20.0, 100.0);
+		assert!((cylinder.volume() - 31.416).abs() < 0.001);
+	}
+
+	#[test]
+	fn test_pressure_drops_per_use() {
+		let tank = AirTank::default();
+		let cylinder = Cylinder::default();
+
+		assert_eq!(tank.pressure_after([]), 100.0);
+		assert_eq!(tank.pressure_after([(cylinder, 0)]), 100.0);
+
+		let after = tank.pressure_after([(cylinder, 10)]);
+		assert!((after - 82.3).abs() < 0.1, "{after}");
+		assert!(tank.pressure_after([(cylinder, 20)]) < after);
+	}
+
+	#[test]
+	fn test_order_doesnt_matter() {
+		let tank = AirTank::default();
+		let small = Cylinder::new(10.0, 25.0);
+		let large = Cylinder::new(20.0, 75.0);
+
+		let a = tank.pressure_after([(small, 3), (large, 2)]);
+		let b = tank.pressure_after([(large, 2), (small, 3)]);
+		assert!((a - b).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_single_acting_retracts_free() {
+		let single = Cylinder {
+			double_acting: false,
+			..Cylinder::default()
+		};
+
+		assert!(single.uses_air(PneumaticState::Extended));
+		assert!(!single.uses_air(PneumaticState::Contracted));
+		assert!(Cylinder::default().uses_air(PneumaticState::Contracted));
+	}
+
+	#[test]
+	fn test_reserve() {
+		let tank = AirTank {
+			reserve: Some(40.0),
+			..Default::default()
+		};
+
+		assert!(tank.is_below_reserve(39.0));
+		assert!(!tank.is_below_reserve(41.0));
+		assert!(!AirTank::default().is_below_reserve(0.0));
+	}
+}
@@ -0,0 +1,566 @@
+//! A bank of pistons, keyed by name. Each bot declares its own pistons with [`Piston`], including
+//! what state they start in, what state they go to while pneumatics are disabled and which
+//! actions move them.
+//!
+//! The bank also keeps an estimate of the air left in the tank, see [`air`].
+
+pub mod air;
+
+use std::{
+	collections::BTreeMap,
+	fmt::Display,
+	ops::Not,
+	time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use vexide::{
+	adi::digital::LogicLevel,
+	prelude::{AdiDigitalIn, AdiDigitalOut},
+	runtime::block_on,
+	smart::PortError,
+	time::sleep,
+};
+
+use crate::{
+	ControllableSubsystem,
+	bindings::{Action, DriverInput},
+	faults::FaultRegistry,
+	pnemuatics::air::{AirTank, Cylinder},
+};
+
+enum DisabledState {
+	Enabled,
+	PowerHeld(Instant),
+	Disabled,
+}
+
+/// The state of every piston that could be read, by name
+pub type PneumaticsSubsystemState = BTreeMap<String, PneumaticState>;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize, Default)]
+pub enum PneumaticState {
+	Extended,
+	#[default]
+	Contracted,
+}
+
+impl Not for PneumaticState {
+	type Output = Self;
+
+	fn not(self) -> Self::Output {
+		match self {
+			Self::Contracted => Self::Extended,
+			Self::Extended => Self::Contracted,
+		}
+	}
+}
+
+/// How often [`AdiPneumatic::actuate`] checks the feedback sensor
+const FEEDBACK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A limit switch or reed sensor that sees where a piston actually is
+pub struct PistonSensor {
+	pub port: AdiDigitalIn,
+	/// The state the piston is in while the sensor reads high
+	pub high_mode: PneumaticState,
+}
+
+impl PistonSensor {
+	pub fn state(&self) -> Result<PneumaticState, PortError> {
+		Ok(match self.port.level()? {
+			LogicLevel::High => self.high_mode,
+			LogicLevel::Low => !self.high_mode,
+		})
+	}
+}
+
+pub struct AdiPneumatic {
+	pub port: AdiDigitalOut,
+	pub high_mode: PneumaticState,
+	pub cylinder: Cylinder,
+	pub sensor: Option<PistonSensor>,
+	actuations: u32,
+	air_uses: u32,
+}
+
+impl AdiPneumatic {
+	pub fn new(port: AdiDigitalOut, high_mode: PneumaticState) -> Self {
+		Self {
+			port,
+			high_mode,
+			cylinder: Cylinder::default(),
+			sensor: None,
+			actuations: 0,
+			air_uses: 0,
+		}
+	}
+
+	pub fn with_cylinder(mut self, cylinder: Cylinder) -> Self {
+		self.cylinder = cylinder;
+		self
+	}
+
+	pub fn with_sensor(mut self, port: AdiDigitalIn, high_mode: PneumaticState) -> Self {
+		self.sensor = Some(PistonSensor { port, high_mode });
+		self
+	}
+
+	/// How many times it's changed state since it was created or last refilled
+	pub fn actuations(&self) -> u32 {
+		self.actuations
+	}
+
+	/// How many of those actuations drew air from the tank
+	pub fn air_uses(&self) -> u32 {
+		self.air_uses
+	}
+
+	pub fn reset_actuations(&mut self) {
+		self.actuations = 0;
+		self.air_uses = 0;
+	}
+
+	/// The state it was last told to be in, which is read back from the output level. See
+	/// [`Self::sensed_state`] for where it actually is.
+	pub fn state(&self) -> Result<PneumaticState, PortError> {
+		let level = self.port.level()?;
+
+		// vexide i swear to god
+		// let level = !level;
+
+		if self.high_mode == PneumaticState::Extended {
+			if level == LogicLevel::High {
+				Ok(PneumaticState::Extended)
+			} else {
+				Ok(PneumaticState::Contracted)
+			}
+		} else {
+			#[allow(clippy::collapsible_else_if)] // shush its intentional
+			if level == LogicLevel::High {
+				Ok(PneumaticState::Contracted)
+			} else {
+				Ok(PneumaticState::Extended)
+			}
+		}
+	}
+
+	pub fn set_state(&mut self, state: PneumaticState) -> Result<(), PortError> {
+		let level = if self.high_mode == PneumaticState::Contracted {
+			match state {
+				PneumaticState::Contracted => LogicLevel::High,
+				PneumaticState::Extended => LogicLevel::Low,
+			}
+		} else {
+			match state {
+				PneumaticState::Contracted => LogicLevel::Low,
+				PneumaticState::Extended => LogicLevel::High,
+			}
+		};
+
+		let previous = self.state().ok();
+
+		// vexide i swear to god
+		self.port.set_level(level)?;
+
+		if previous != Some(state) {
+			self.actuations += 1;
+			if self.cylinder.uses_air(state) {
+				self.air_uses += 1;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Whether setting it to `state` would draw air from the tank, counting towards
+	/// [`Self::air_uses`]
+	pub fn uses_air(&self, state: PneumaticState) -> bool {
+		self.state().ok() != Some(state) && self.cylinder.uses_air(state)
+	}
+
+	/// Where the feedback sensor says it is, or `None` if it doesn't have one
+	pub fn sensed_state(&self) -> Option<Result<PneumaticState, PortError>> {
+		self.sensor.as_ref().map(PistonSensor::state)
+	}
+
+	/// Whether it's at `state`, going by the sensor if it has one and the output level otherwise
+	pub fn is_at(&self, state: PneumaticState) -> bool {
+		self.sensed_state()
+			.unwrap_or_else(|| self.state())
+			.is_ok_and(|s| s == state)
+	}
+
+	/// Sets the state, then waits for the sensor to see it get there. See [`Self::wait_for`].
+	pub async fn actuate(
+		&mut self,
+		state: PneumaticState,
+		timeout: Duration,
+	) -> Result<(), PneumaticError> {
+		self.set_state(state)?;
+		self.wait_for(state, timeout).await
+	}
+
+	/// Waits for the sensor to see it at `state`. Without a sensor there's nothing to confirm
+	/// against, so this just waits out `timeout`.
+	pub async fn wait_for(
+		&self,
+		state: PneumaticState,
+		timeout: Duration,
+	) -> Result<(), PneumaticError> {
+		if self.sensor.is_none() {
+			sleep(timeout).await;
+			return Ok(());
+		}
+
+		let start = Instant::now();
+		while !self.is_at(state) {
+			if start.elapsed() >= timeout {
+				return Err(PneumaticError::Timeout(state));
+			}
+			sleep(FEEDBACK_INTERVAL).await;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub enum PneumaticError {
+	/// No piston with this name is in the bank
+	UnknownPiston(String),
+	/// The piston isn't critical, the tank is below its reserve, and the move would use air
+	LowAir,
+	/// The feedback sensor didn't see the piston reach this state in time
+	Timeout(PneumaticState),
+	Port(PortError),
+}
+
+impl From<PortError> for PneumaticError {
+	fn from(value: PortError) -> Self {
+		Self::Port(value)
+	}
+}
+
+impl Display for PneumaticError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::UnknownPiston(name) => write!(f, "no piston named \"{name}\""),
+			Self::LowAir => write!(f, "not enough air left for non-critical pistons"),
+			Self::Timeout(state) => write!(f, "piston didn't reach {state:?} in time"),
+			Self::Port(e) => write!(f, "{e}"),
+		}
+	}
+}
+
+impl std::error::Error for PneumaticError {}
+
+/// Declares one piston in a [`PneumaticsSubsystem`]
+pub struct Piston {
+	pub name: &'static str,
+	pub pneumatic: AdiPneumatic,
+	/// What [`PneumaticsSubsystem::initialize`] sets it to
+	pub default: PneumaticState,
+	/// What it's held at while pneumatics are disabled
+	pub disabled: PneumaticState,
+	/// Action that flips it each time it's pressed
+	pub toggle: Option<Action>,
+	/// States to hold while an action is pressed, the first pressed one winning
+	pub holds: Vec<(Action, PneumaticState)>,
+	/// What to go back to when none of `holds` are pressed
+	pub rest: Option<PneumaticState>,
+	/// If set, [`PneumaticsSubsystem::initialize`] flicks it to the opposite state before settling
+	/// at `default`, unless it's already there. This is how long it waits for the flick to be
+	/// confirmed, or just waits if there's no sensor.
+	pub settle: Option<Duration>,
+	/// Whether it keeps firing once the tank drops below [`AirTank::reserve`]. Non-critical
+	/// pistons can still make moves that don't use air, and go to `disabled`.
+	pub critical: bool,
+}
+
+impl Piston {
+	pub fn new(name: &'static str, pneumatic: AdiPneumatic) -> Self {
+		Self {
+			name,
+			pneumatic,
+			default: PneumaticState::Contracted,
+			disabled: PneumaticState::Contracted,
+			toggle: None,
+			holds: Vec::new(),
+			rest: None,
+			settle: None,
+			critical: true,
+		}
+	}
+
+	pub fn default_state(mut self, state: PneumaticState) -> Self {
+		self.default = state;
+		self
+	}
+
+	pub fn disabled_state(mut self, state: PneumaticState) -> Self {
+		self.disabled = state;
+		self
+	}
+
+	pub fn toggle(mut self, action: Action) -> Self {
+		self.toggle = Some(action);
+		self
+	}
+
+	pub fn hold(mut self, action: Action, state: PneumaticState) -> Self {
+		self.holds.push((action, state));
+		self
+	}
+
+	pub fn rest(mut self, state: PneumaticState) -> Self {
+		self.rest = Some(state);
+		self
+	}
+
+	pub fn settle(mut self, duration: Duration) -> Self {
+		self.settle = Some(duration);
+		self
+	}
+
+	pub fn non_critical(mut self) -> Self {
+		self.critical = false;
+		self
+	}
+
+	/// What the driver wants this piston at right now, if anything
+	fn driven_state(&self, input: &DriverInput) -> Option<PneumaticState> {
+		if let Some(action) = self.toggle
+			&& input.is_now_pressed(action)
+		{
+			return self.pneumatic.state().ok().map(|state| !state);
+		}
+
+		self.holds
+			.iter()
+			.find(|(action, _)| input.is_pressed(*action))
+			.map(|(_, state)| *state)
+			.or(self.rest)
+	}
+}
+
+pub struct PneumaticsSubsystem {
+	pub tank: AirTank,
+	pub faults: FaultRegistry,
+	pistons: Vec<Piston>,
+	disabled: DisabledState,
+}
+
+impl PneumaticsSubsystem {
+	/// # Panics
+	///
+	/// If two pistons have the same name
+	pub fn new(pistons: impl IntoIterator<Item = Piston>) -> Self {
+		let pistons: Vec<Piston> = pistons.into_iter().collect();
+		for (i, piston) in pistons.iter().enumerate() {
+			assert!(
+				pistons[..i].iter().all(|other| other.name != piston.name),
+				"piston \"{}\" is declared twice",
+				piston.name
+			);
+		}
+
+		Self {
+			tank: AirTank::default(),
+			faults: FaultRegistry::new(),
+			pistons,
+			disabled: DisabledState::Enabled,
+		}
+	}
+
+	pub fn with_tank(mut self, tank: AirTank) -> Self {
+		self.tank = tank;
+		self
+	}
+
+	pub fn with_faults(mut self, faults: FaultRegistry) -> Self {
+		self.faults = faults;
+		self
+	}
+
+	/// Estimated tank pressure (in PSI), from how much air the pistons have used
+	pub fn pressure(&self) -> f64 {
+		self.tank.pressure_after(
+			self.pistons
+				.iter()
+				.map(|piston| (piston.pneumatic.cylinder, piston.pneumatic.air_uses())),
+		)
+	}
+
+	/// Whether the tank is below its reserve, so non-critical pistons won't fire
+	pub fn is_low(&self) -> bool {
+		self.tank.is_below_reserve(self.pressure())
+	}
+
+	/// Resets the air estimate after the tank has been pumped back up
+	pub fn refill(&mut self) {
+		for piston in &mut self.pistons {
+			piston.pneumatic.reset_actuations();
+		}
+	}
+
+	/// The names of every piston, in the order they were declared
+	pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+		self.pistons.iter().map(|piston| piston.name)
+	}
+
+	pub fn get(&self, name: &str) -> Option<&AdiPneumatic> {
+		self.piston(name).map(|piston| &piston.pneumatic)
+	}
+
+	pub fn get_mut(&mut self, name: &str) -> Option<&mut AdiPneumatic> {
+		self.pistons
+			.iter_mut()
+			.find(|piston| piston.name == name)
+			.map(|piston| &mut piston.pneumatic)
+	}
+
+	pub fn state_of(&self, name: &str) -> Result<PneumaticState, PneumaticError> {
+		Ok(self.pneumatic(name)?.state()?)
+	}
+
+	pub fn set(&mut self, name: &str, state: PneumaticState) -> Result<(), PneumaticError> {
+		let low = self.is_low();
+		let piston = self
+			.pistons
+			.iter_mut()
+			.find(|piston| piston.name == name)
+			.ok_or_else(|| PneumaticError::UnknownPiston(name.to_string()))?;
+
+		if low && !piston.critical && piston.pneumatic.uses_air(state) {
+			return Err(PneumaticError::LowAir);
+		}
+
+		let result = piston.pneumatic.set_state(state);
+		if let Err(e) = &result {
+			self.faults.record("pneumatics", name, e);
+		}
+		Ok(result?)
+	}
+
+	/// Flips a piston, returning the state it was flipped to
+	pub fn toggle(&mut self, name: &str) -> Result<PneumaticState, PneumaticError> {
+		let state = !self.state_of(name)?;
+		self.set(name, state)?;
+		Ok(state)
+	}
+
+	/// Sets a piston, finishing once its sensor confirms it moved or `timeout` passes. See
+	/// [`AdiPneumatic::actuate`].
+	pub async fn actuate(
+		&mut self,
+		name: &str,
+		state: PneumaticState,
+		timeout: Duration,
+	) -> Result<(), PneumaticError> {
+		self.set(name, state)?;
+		self.pneumatic(name)?.wait_for(state, timeout).await
+	}
+
+	/// Sets every piston named in `state`, skipping any this bot doesn't have
+	pub fn set_state(&mut self, state: &PneumaticsSubsystemState) -> Result<(), PortError> {
+		for piston in &mut self.pistons {
+			if let Some(&state) = state.get(piston.name) {
+				let result = piston.pneumatic.set_state(state);
+				if let Err(e) = &result {
+					self.faults.record("pneumatics", piston.name, e);
+				}
+				result?;
+			}
+		}
+
+		Ok(())
+	}
+
+	pub async fn initialize(&mut self) {
+		self.disabled = DisabledState::Enabled;
+
+		for piston in &mut self.pistons {
+			let Some(duration) = piston.settle else {
+				let result = piston.pneumatic.set_state(piston.default);
+				self.faults.check("pneumatics", piston.name, result);
+				continue;
+			};
+
+			if piston.pneumatic.is_at(piston.default) {
+				println!("{} already settled", piston.name);
+			} else {
+				println!("Settling {}", piston.name);
+				let result = piston.pneumatic.actuate(!piston.default, duration).await;
+				self.faults.check("pneumatics", piston.name, result);
+				let result = piston.pneumatic.set_state(piston.default);
+				self.faults.check("pneumatics", piston.name, result);
+			}
+		}
+	}
+
+	fn piston(&self, name: &str) -> Option<&Piston> {
+		self.pistons.iter().find(|piston| piston.name == name)
+	}
+
+	fn pneumatic(&self, name: &str) -> Result<&AdiPneumatic, PneumaticError> {
+		self.get(name)
+			.ok_or_else(|| PneumaticError::UnknownPiston(name.to_string()))
+	}
+}
+
+impl ControllableSubsystem for PneumaticsSubsystem {
+	type State = PneumaticsSubsystemState;
+
+	fn state(&self) -> Option<Self::State> {
+		Some(
+			self.pistons
+				.iter()
+				.filter_map(|piston| {
+					let state = piston.pneumatic.state().ok()?;
+					Some((piston.name.to_string(), state))
+				})
+				.collect(),
+		)
+	}
+
+	fn direct(&mut self, state: Self::State) {
+		_ = self.set_state(&state);
+	}
+
+	fn control(&mut self, input: &DriverInput) {
+		if input.is_now_pressed(Action::DisablePneumatics) {
+			match self.disabled {
+				DisabledState::Enabled => self.disabled = DisabledState::PowerHeld(Instant::now()),
+				DisabledState::Disabled => {
+					self.disabled = DisabledState::Enabled;
+					block_on(self.initialize())
+				}
+				_ => (),
+			}
+		} else if input.is_now_released(Action::DisablePneumatics)
+			&& let DisabledState::PowerHeld(t) = self.disabled
+			&& t.elapsed() < Duration::from_millis(500)
+		{
+			self.disabled = DisabledState::Disabled;
+		}
+
+		let enabled = !matches!(self.disabled, DisabledState::Disabled);
+		let low = self.is_low();
+		for piston in &mut self.pistons {
+			let state = if enabled {
+				// Below the reserve, non-critical pistons can still move without using air
+				piston.driven_state(input).filter(|&state| {
+					!(low && !piston.critical && piston.pneumatic.uses_air(state))
+				})
+			} else {
+				Some(piston.disabled)
+			};
+
+			if let Some(state) = state {
+				let result = piston.pneumatic.set_state(state);
+				self.faults.check("pneumatics", piston.name, result);
+			}
+		}
+	}
+}
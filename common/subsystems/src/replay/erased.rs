@@ -0,0 +1,139 @@
+//! An object-safe view of [`ControllableSubsystem`]s, so subsystems with different state types can
+//! be recorded and replayed together.
+
+use std::{any::type_name, fmt::Display};
+
+use crate::ControllableSubsystem;
+
+#[derive(Debug)]
+pub enum StateError {
+	/// The subsystem's state couldn't be serialized
+	Serialize(ciborium::value::Error),
+	/// A recorded state didn't match the subsystem's state type
+	Mismatch {
+		schema: &'static str,
+		error: ciborium::value::Error,
+	},
+}
+
+impl Display for StateError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Serialize(e) => write!(f, "state couldn't be serialized: {e}"),
+			Self::Mismatch { schema, error } => {
+				write!(f, "recorded state isn't a valid {schema}: {error}")
+			}
+		}
+	}
+}
+
+impl std::error::Error for StateError {}
+
+/// A subsystem with its state serialized, implemented for every [`ControllableSubsystem`]
+pub trait ErasedSubsystem {
+	/// The name of the subsystem's state type, recorded so mismatched recordings can be spotted
+	fn schema(&self) -> &'static str;
+	/// The current state, serialized
+	fn serialized_state(&self) -> Option<Result<ciborium::Value, StateError>>;
+	/// Apply a recorded state, blended towards `to` by `t` if given
+	fn replay_state(
+		&mut self,
+		state: &ciborium::Value,
+		to: Option<(&ciborium::Value, f64)>,
+	) -> Result<(), StateError>;
+}
+
+impl<S: ControllableSubsystem> ErasedSubsystem for S {
+	fn schema(&self) -> &'static str {
+		type_name::<S::State>()
+	}
+
+	fn serialized_state(&self) -> Option<Result<ciborium::Value, StateError>> {
+		let state = self.state()?;
+
+		Some(ciborium::Value::serialized(&state).map_err(StateError::Serialize))
+	}
+
+	fn replay_state(
+		&mut self,
+		state: &ciborium::Value,
+		to: Option<(&ciborium::Value, f64)>,
+	) -> Result<(), StateError> {
+		let deserialize = |value: &ciborium::Value| {
+			value
+				.deserialized::<S::State>()
+				.map_err(|error| StateError::Mismatch {
+					schema: type_name::<S::State>(),
+					error,
+				})
+		};
+
+		let from = deserialize(state)?;
+		let state = match to {
+			Some((to, t)) => {
+				let to = deserialize(to)?;
+				self.interpolate(&from, &to, t.clamp(0.0, 1.0))
+					.unwrap_or(from)
+			}
+			None => from,
+		};
+
+		self.direct(state);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{bindings::DriverInput, replay::interpolate::Interpolate};
+
+	#[derive(Default)]
+	struct Tank {
+		state: (f64, f64),
+	}
+
+	impl ControllableSubsystem for Tank {
+		type State = (f64, f64);
+
+		fn control(&mut self, _input: &DriverInput) {}
+
+		fn direct(&mut self, state: Self::State) {
+			self.state = state;
+		}
+
+		fn state(&self) -> Option<Self::State> {
+			Some(self.state)
+		}
+
+		fn interpolate(&self, from: &Self::State, to: &Self::State, t: f64) -> Option<Self::State> {
+			Some(from.lerp(to, t))
+		}
+	}
+
+	#[test]
+	fn test_replay_state_clamps() {
+		let mut tank = Tank::default();
+		let from = ciborium::Value::serialized(&(0.0, 0.0)).unwrap();
+		let to = ciborium::Value::serialized(&(1.0, 1.0)).unwrap();
+
+		tank.replay_state(&from, Some((&to, 1.5))).unwrap();
+		assert_eq!(tank.state, (1.0, 1.0));
+	}
+
+	#[test]
+	fn test_replay_state_mismatch() {
+		let mut tank = Tank::default();
+		let from = ciborium::Value::serialized(&(0.5, 0.5)).unwrap();
+		let to = ciborium::Value::serialized(&"flap").unwrap();
+
+		assert!(matches!(
+			tank.replay_state(&from, Some((&to, 0.5))),
+			Err(StateError::Mismatch { .. })
+		));
+		assert!(tank.replay_state(&to, None).is_err());
+		// Nothing should have been applied
+		assert_eq!(tank.state, (0.0, 0.0));
+	}
+}
@@ -0,0 +1,591 @@
+//! The on-disk replay format.
+//!
+//! A recording is a header line of `REPLAY_MAGIC!v2:{duration in seconds}\n`, followed by
+//! CBOR-serialized [`Frame`]s, each COBS-encoded and terminated with a `\0`.
+//!
+//! Subsystems are referred to by their index in the latest [`Frame::Subsystems`] table. Every
+//! [`KEYFRAME_INTERVAL`] a [`Frame::Keyframe`] holds every subsystem's state, and in between
+//! [`Frame::Delta`]s only hold the states that changed. The table is written again before each
+//! keyframe, so a corrupt table only loses replay up to the next one.
+//!
+//! Version 1 recordings (`REPLAY_MAGIC!{duration}\n`, with every frame a full [`RecordingEntry`])
+//! can still be read.
+
+use std::{
+	fmt::Display,
+	io::{self, BufRead, Write},
+	time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::structs::{RecordingEntry, SerializedSubsystemStates};
+
+pub const MAGIC: &str = "REPLAY_MAGIC!";
+const VERSION_2: &str = "v2:";
+
+/// How often a full keyframe is written, so a corrupt frame only affects a short stretch of replay
+pub const KEYFRAME_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+	/// Every frame is a full [`RecordingEntry`]
+	V1,
+	/// Frames are [`Frame`]s
+	V2,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Frame {
+	/// The names of every subsystem, in id order
+	#[serde(rename = "s")]
+	Subsystems(Vec<String>),
+	/// The state of every subsystem at a time (in microseconds)
+	#[serde(rename = "k")]
+	Keyframe(u32, Vec<(u8, ciborium::Value)>),
+	/// The states of only the subsystems that changed since the last frame
+	#[serde(rename = "d")]
+	Delta(u32, Vec<(u8, ciborium::Value)>),
+	/// The name of a subsystem's state type, written before its first state
+	#[serde(rename = "t")]
+	Schema(String, String),
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+	Io(io::Error),
+	/// The header line didn't start with [`MAGIC`]
+	MissingMagic(String),
+	/// The header line's duration wasn't a whole number of seconds
+	InvalidDuration(String),
+	/// The last frame was cut off before its delimiter
+	Truncated,
+	/// A frame wasn't valid COBS
+	Cobs(cobs::DecodeError),
+	/// A frame decoded, but wasn't a valid entry
+	Cbor(ciborium::de::Error<io::Error>),
+	/// A frame referred to a subsystem id that isn't in the table
+	UnknownSubsystem(u8),
+	/// A delta came before any keyframe, so there was nothing to apply it to
+	MissingKeyframe,
+	/// More subsystems reported state than fit in a frame's `u8` ids
+	TooManySubsystems(String),
+}
+
+impl Display for FormatError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io(e) => write!(f, "io error: {e}"),
+			Self::MissingMagic(line) => write!(f, "header is missing magic: {line:?}"),
+			Self::InvalidDuration(secs) => write!(f, "header has invalid duration: {secs:?}"),
+			Self::Truncated => write!(f, "frame is truncated, missing delimiter"),
+			Self::Cobs(e) => write!(f, "frame is not valid COBS: {e:?}"),
+			Self::Cbor(e) => write!(f, "frame is not a valid entry: {e}"),
+			Self::UnknownSubsystem(id) => write!(f, "frame refers to unknown subsystem id {id}"),
+			Self::MissingKeyframe => write!(f, "delta frame has no keyframe before it"),
+			Self::TooManySubsystems(name) => {
+				write!(f, "no id left for subsystem {name:?}, only 256 fit in a frame")
+			}
+		}
+	}
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<io::Error> for FormatError {
+	fn from(value: io::Error) -> Self {
+		Self::Io(value)
+	}
+}
+
+pub fn write_header(writer: &mut impl Write, duration: Duration) -> io::Result<()> {
+	writer.write_all(MAGIC.as_bytes())?;
+	writer.write_all(VERSION_2.as_bytes())?;
+	writer.write_all(format!("{}", duration.as_secs()).as_bytes())?;
+	writer.write_all(b"\n")
+}
+
+/// Reads the header line, returning the recording's version and duration
+pub fn read_header(reader: &mut impl BufRead) -> Result<(Version, Duration), FormatError> {
+	let mut magic = String::new();
+	reader.read_line(&mut magic)?;
+	magic.pop(); // newline
+
+	let Some((_, rest)) = magic.split_once(MAGIC) else {
+		return Err(FormatError::MissingMagic(magic));
+	};
+	let (version, secs) = match rest.strip_prefix(VERSION_2) {
+		Some(secs) => (Version::V2, secs),
+		None => (Version::V1, rest),
+	};
+
+	secs.parse()
+		.map(|secs| (version, Duration::from_secs(secs)))
+		.map_err(|_| FormatError::InvalidDuration(secs.to_string()))
+}
+
+/// Serializes and COBS-encodes a value, appending it to `out` with its delimiter
+fn write_frame(value: &impl Serialize, scratch: &mut Vec<u8>, out: &mut Vec<u8>) {
+	scratch.clear();
+	ciborium::into_writer(value, &mut *scratch).expect("Serializing recording frame should succeed");
+
+	let start = out.len();
+	out.resize(start + cobs::max_encoding_length(scratch.len()), 0);
+	let encoded_len = cobs::encode(scratch, &mut out[start..]);
+	out.truncate(start + encoded_len);
+	out.push(b'\0');
+}
+
+/// Reads the next raw frame (including its delimiter) into `buffer`, returning `false` at the end of the file
+pub fn read_frame(reader: &mut impl BufRead, buffer: &mut Vec<u8>) -> io::Result<bool> {
+	buffer.clear();
+	Ok(reader.read_until(b'\0', buffer)? != 0)
+}
+
+/// Decodes a raw frame read by [`read_frame`] in place
+fn decode_frame<T: serde::de::DeserializeOwned>(frame: &mut [u8]) -> Result<T, FormatError> {
+	if frame.last() != Some(&b'\0') {
+		return Err(FormatError::Truncated);
+	}
+
+	let n = cobs::decode_in_place(frame).map_err(FormatError::Cobs)?;
+
+	ciborium::from_reader(&frame[0..n]).map_err(FormatError::Cbor)
+}
+
+/// Turns subsystem states into keyframes and deltas
+#[derive(Debug, Default)]
+pub struct StateEncoder {
+	names: Vec<String>,
+	/// Subsystems whose schema has already been written
+	described: Vec<String>,
+	/// The last written state of each subsystem, by id
+	previous: Vec<Option<ciborium::Value>>,
+	last_keyframe: Option<u32>,
+	scratch: Vec<u8>,
+}
+
+impl StateEncoder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Records the state type of a subsystem, appending a frame to `out` the first time it's seen
+	pub fn encode_schema(&mut self, name: &str, schema: &str, out: &mut Vec<u8>) {
+		if self.described.iter().any(|n| n == name) {
+			return;
+		}
+
+		self.described.push(name.to_string());
+		write_frame(
+			&Frame::Schema(name.to_string(), schema.to_string()),
+			&mut self.scratch,
+			out,
+		);
+	}
+
+	/// Encodes the states at a time, appending any frames needed to `out`
+	///
+	/// Nothing is written if no state changed.
+	pub fn encode<S: AsRef<str>>(
+		&mut self,
+		micros_elapsed: u32,
+		states: &[(S, ciborium::Value)],
+		out: &mut Vec<u8>,
+	) -> Result<(), FormatError> {
+		// Give ids to any subsystems we haven't seen yet
+		let mut new_names = false;
+		for (name, _) in states {
+			if !self.names.iter().any(|n| n == name.as_ref()) {
+				if u8::try_from(self.names.len()).is_err() {
+					return Err(FormatError::TooManySubsystems(name.as_ref().to_string()));
+				}
+
+				self.names.push(name.as_ref().to_string());
+				self.previous.push(None);
+				new_names = true;
+			}
+		}
+
+		let mut seen = vec![false; self.names.len()];
+		let mut changed = Vec::new();
+		for (name, state) in states {
+			let id = self
+				.names
+				.iter()
+				.position(|n| n == name.as_ref())
+				.expect("Subsystem should have been given an id");
+			seen[id] = true;
+
+			if self.previous[id].as_ref() != Some(state) {
+				changed.push((subsystem_id(&self.names, id)?, state.clone()));
+				self.previous[id] = Some(state.clone());
+			}
+		}
+
+		// A subsystem stopping reporting state can't be expressed as a delta
+		let mut dropped = false;
+		for (previous, seen) in self.previous.iter_mut().zip(seen) {
+			if !seen && previous.take().is_some() {
+				dropped = true;
+			}
+		}
+
+		let keyframe_due = self.last_keyframe.is_none_or(|last| {
+			Duration::from_micros(micros_elapsed.saturating_sub(last) as u64) >= KEYFRAME_INTERVAL
+		});
+
+		if new_names || dropped || (keyframe_due && !changed.is_empty()) {
+			self.last_keyframe = Some(micros_elapsed);
+			let all = self
+				.previous
+				.iter()
+				.enumerate()
+				.filter_map(|(id, state)| Some((id, state.clone()?)))
+				.map(|(id, state)| Ok((subsystem_id(&self.names, id)?, state)))
+				.collect::<Result<_, FormatError>>()?;
+			write_frame(
+				&Frame::Subsystems(self.names.clone()),
+				&mut self.scratch,
+				out,
+			);
+			write_frame(&Frame::Keyframe(micros_elapsed, all), &mut self.scratch, out);
+		} else if !changed.is_empty() {
+			write_frame(&Frame::Delta(micros_elapsed, changed), &mut self.scratch, out);
+		}
+
+		Ok(())
+	}
+}
+
+/// The id a frame refers to the subsystem at `index` in `names` by
+fn subsystem_id(names: &[String], index: usize) -> Result<u8, FormatError> {
+	u8::try_from(index).map_err(|_| FormatError::TooManySubsystems(names[index].clone()))
+}
+
+/// Writes full entries in the current format
+pub struct RecordingWriter<W: Write> {
+	writer: W,
+	encoder: StateEncoder,
+	buffer: Vec<u8>,
+}
+
+impl<W: Write> RecordingWriter<W> {
+	pub fn new(mut writer: W, duration: Duration) -> io::Result<Self> {
+		write_header(&mut writer, duration)?;
+
+		Ok(Self {
+			writer,
+			encoder: StateEncoder::new(),
+			buffer: Vec::new(),
+		})
+	}
+
+	pub fn write_schema(&mut self, name: &str, schema: &str) -> io::Result<()> {
+		self.buffer.clear();
+		self.encoder.encode_schema(name, schema, &mut self.buffer);
+		self.writer.write_all(&self.buffer)
+	}
+
+	pub fn write_entry(&mut self, entry: &RecordingEntry) -> Result<(), FormatError> {
+		self.buffer.clear();
+		self.encoder.encode(
+			entry.micros_elapsed,
+			&entry.subsystem_states,
+			&mut self.buffer,
+		)?;
+		Ok(self.writer.write_all(&self.buffer)?)
+	}
+
+	pub fn into_inner(mut self) -> io::Result<W> {
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+}
+
+/// Reads a recording of any version back as full entries
+pub struct RecordingReader<R: BufRead> {
+	reader: R,
+	version: Version,
+	duration: Duration,
+	buffer: Vec<u8>,
+	offset: u64,
+	names: Vec<String>,
+	schemas: Vec<(String, String)>,
+	/// The current state of each subsystem, or `None` until a keyframe has been read
+	current: Option<Vec<Option<ciborium::Value>>>,
+	/// Set after a bad frame until the next keyframe, since deltas would apply to stale state
+	resyncing: bool,
+}
+
+impl<R: BufRead> RecordingReader<R> {
+	pub fn new(mut reader: R) -> Result<Self, FormatError> {
+		let mut header = String::new();
+		let header_len = reader.read_line(&mut header)?;
+		let (version, duration) = read_header(&mut header.as_bytes())?;
+
+		Ok(Self {
+			reader,
+			version,
+			duration,
+			buffer: Vec::new(),
+			offset: header_len as u64,
+			names: Vec::new(),
+			schemas: Vec::new(),
+			current: None,
+			resyncing: false,
+		})
+	}
+
+	pub fn version(&self) -> Version {
+		self.version
+	}
+
+	/// How long a replay of this recording runs for
+	pub fn duration(&self) -> Duration {
+		self.duration
+	}
+
+	/// The state type of each subsystem that has been read so far, by name
+	pub fn schemas(&self) -> &[(String, String)] {
+		&self.schemas
+	}
+
+	/// The byte offset of the next frame
+	pub fn offset(&self) -> u64 {
+		self.offset
+	}
+
+	fn apply(&mut self, states: Vec<(u8, ciborium::Value)>) -> Result<(), FormatError> {
+		let current = self.current.as_mut().ok_or(FormatError::MissingKeyframe)?;
+
+		for (id, state) in states {
+			*current
+				.get_mut(id as usize)
+				.ok_or(FormatError::UnknownSubsystem(id))? = Some(state);
+		}
+
+		Ok(())
+	}
+
+	fn snapshot(&self, micros_elapsed: u32) -> RecordingEntry {
+		RecordingEntry {
+			subsystem_states: self
+				.names
+				.iter()
+				.zip(self.current.iter().flatten())
+				.filter_map(|(name, state)| Some((name.clone(), state.clone()?)))
+				.collect::<SerializedSubsystemStates>(),
+			micros_elapsed,
+		}
+	}
+
+	/// Reads the next entry, returning `None` at the end of the file.
+	///
+	/// After an error, reading can continue with the next frame. Any deltas before the next
+	/// keyframe are skipped, since the state they'd apply to is missing whatever the bad frame held.
+	pub fn next_entry(&mut self) -> Result<Option<RecordingEntry>, FormatError> {
+		let entry = self.read_entry();
+		if self.version == Version::V2
+			&& matches!(entry, Err(ref e) if !matches!(e, FormatError::Io(_)))
+		{
+			self.current = None;
+			self.resyncing = true;
+		}
+
+		entry
+	}
+
+	fn read_entry(&mut self) -> Result<Option<RecordingEntry>, FormatError> {
+		loop {
+			if !read_frame(&mut self.reader, &mut self.buffer)? {
+				return Ok(None);
+			}
+			self.offset += self.buffer.len() as u64;
+
+			if self.version == Version::V1 {
+				return decode_frame(&mut self.buffer).map(Some);
+			}
+
+			match decode_frame(&mut self.buffer)? {
+				Frame::Subsystems(names) => {
+					if let Some(current) = &mut self.current {
+						current.resize(names.len(), None);
+					}
+					self.names = names;
+				}
+				Frame::Schema(name, schema) => self.schemas.push((name, schema)),
+				Frame::Keyframe(micros_elapsed, states) => {
+					self.current = Some(vec![None; self.names.len()]);
+					self.resyncing = false;
+					self.apply(states)?;
+					return Ok(Some(self.snapshot(micros_elapsed)));
+				}
+				Frame::Delta(..) if self.resyncing => {}
+				Frame::Delta(micros_elapsed, states) => {
+					self.apply(states)?;
+					return Ok(Some(self.snapshot(micros_elapsed)));
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ciborium::Value;
+
+	use super::*;
+
+	fn entry(micros_elapsed: u32, states: &[(&str, f64)]) -> RecordingEntry {
+		RecordingEntry {
+			subsystem_states: states
+				.iter()
+				.map(|(name, value)| (name.to_string(), Value::Float(*value)))
+				.collect(),
+			micros_elapsed,
+		}
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let entries = [
+			entry(0, &[("drivetrain", 0.0), ("intake", 0.0)]),
+			entry(10_000, &[("drivetrain", 0.5), ("intake", 0.0)]),
+			entry(1_500_000, &[("drivetrain", 0.5), ("intake", 1.0)]),
+			entry(1_510_000, &[("drivetrain", 0.5), ("intake", 1.0), ("pose", 3.0)]),
+			entry(1_520_000, &[("intake", 1.0), ("pose", 3.0)]),
+		];
+
+		let mut writer = RecordingWriter::new(Vec::new(), Duration::from_secs(15)).unwrap();
+		writer.write_schema("drivetrain", "(f64, f64)").unwrap();
+		for entry in &entries {
+			writer.write_entry(entry).unwrap();
+		}
+		let bytes = writer.into_inner().unwrap();
+
+		let mut reader = RecordingReader::new(bytes.as_slice()).unwrap();
+		assert_eq!(reader.version(), Version::V2);
+		assert_eq!(reader.duration(), Duration::from_secs(15));
+
+		let mut read = Vec::new();
+		while let Some(entry) = reader.next_entry().unwrap() {
+			read.push(entry);
+		}
+		assert_eq!(read, entries);
+		assert_eq!(
+			reader.schemas(),
+			[("drivetrain".to_string(), "(f64, f64)".to_string())]
+		);
+	}
+
+	#[test]
+	fn test_skips_deltas_after_corrupt_frame() {
+		let entries = [
+			entry(0, &[("a", 0.0), ("b", 0.0)]),
+			entry(10_000, &[("a", 1.0), ("b", 0.0)]),
+			entry(20_000, &[("a", 1.0), ("b", 1.0)]),
+			entry(1_100_000, &[("a", 2.0), ("b", 1.0)]),
+		];
+
+		let mut writer = RecordingWriter::new(Vec::new(), Duration::from_secs(15)).unwrap();
+		for entry in &entries {
+			writer.write_entry(entry).unwrap();
+		}
+		let bytes = writer.into_inner().unwrap();
+
+		// Subsystems, keyframe, delta, delta, subsystems, keyframe: break the first delta
+		let header_len = bytes.iter().position(|&b| b == b'\n').unwrap() + 1;
+		let mut frames = bytes[header_len..]
+			.split_inclusive(|&b| b == b'\0')
+			.map(<[u8]>::to_vec)
+			.collect::<Vec<_>>();
+		assert_eq!(frames.len(), 6);
+		frames[2] = vec![0xFF, 0x01, b'\0'];
+		let corrupted = [bytes[..header_len].to_vec(), frames.concat()].concat();
+
+		let mut reader = RecordingReader::new(corrupted.as_slice()).unwrap();
+		assert_eq!(reader.next_entry().unwrap(), Some(entries[0].clone()));
+		assert!(reader.next_entry().is_err());
+		// The second delta only holds `b`, so applying it would replay `a` as 0.0
+		assert_eq!(reader.next_entry().unwrap(), Some(entries[3].clone()));
+		assert_eq!(reader.next_entry().unwrap(), None);
+	}
+
+	#[test]
+	fn test_delta_only_has_changes() {
+		let mut encoder = StateEncoder::new();
+		let mut out = Vec::new();
+		encoder
+			.encode(0, &[("a", Value::Float(0.0)), ("b", Value::Float(0.0))], &mut out)
+			.unwrap();
+
+		out.clear();
+		encoder
+			.encode(10, &[("a", Value::Float(1.0)), ("b", Value::Float(0.0))], &mut out)
+			.unwrap();
+		assert_eq!(
+			decode_frame::<Frame>(&mut out).unwrap(),
+			Frame::Delta(10, vec![(0, Value::Float(1.0))])
+		);
+
+		out.clear();
+		encoder
+			.encode(20, &[("a", Value::Float(1.0)), ("b", Value::Float(0.0))], &mut out)
+			.unwrap();
+		assert!(out.is_empty(), "Unchanged states shouldn't write anything");
+	}
+
+	#[test]
+	fn test_recovers_from_corrupt_subsystems() {
+		let entries = [
+			entry(0, &[("a", 0.0), ("b", 0.0)]),
+			entry(10_000, &[("a", 1.0), ("b", 0.0)]),
+			entry(1_100_000, &[("a", 2.0), ("b", 1.0)]),
+			entry(1_110_000, &[("a", 2.0), ("b", 2.0)]),
+		];
+
+		let mut writer = RecordingWriter::new(Vec::new(), Duration::from_secs(15)).unwrap();
+		for entry in &entries {
+			writer.write_entry(entry).unwrap();
+		}
+		let bytes = writer.into_inner().unwrap();
+
+		// Subsystems, keyframe, delta, subsystems, keyframe, delta: break the first table
+		let header_len = bytes.iter().position(|&b| b == b'\n').unwrap() + 1;
+		let mut frames = bytes[header_len..]
+			.split_inclusive(|&b| b == b'\0')
+			.map(<[u8]>::to_vec)
+			.collect::<Vec<_>>();
+		assert_eq!(frames.len(), 6);
+		frames[0] = vec![0xFF, 0x01, b'\0'];
+		let corrupted = [bytes[..header_len].to_vec(), frames.concat()].concat();
+
+		let mut reader = RecordingReader::new(corrupted.as_slice()).unwrap();
+		assert!(reader.next_entry().is_err());
+		// The first keyframe's ids are from the lost table
+		assert!(matches!(
+			reader.next_entry(),
+			Err(FormatError::UnknownSubsystem(0))
+		));
+		assert_eq!(reader.next_entry().unwrap(), Some(entries[2].clone()));
+		assert_eq!(reader.next_entry().unwrap(), Some(entries[3].clone()));
+		assert_eq!(reader.next_entry().unwrap(), None);
+	}
+
+	#[test]
+	fn test_too_many_subsystems() {
+		let names = (0..=256).map(|i| format!("s{i}")).collect::<Vec<_>>();
+		let states = names
+			.iter()
+			.map(|name| (name.as_str(), Value::Float(0.0)))
+			.collect::<Vec<_>>();
+
+		let mut out = Vec::new();
+		assert!(matches!(
+			StateEncoder::new().encode(0, &states, &mut out),
+			Err(FormatError::TooManySubsystems(name)) if name == "s256"
+		));
+		assert!(out.is_empty());
+	}
+}
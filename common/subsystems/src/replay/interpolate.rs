@@ -0,0 +1,27 @@
+/// State that can be smoothly blended between two recorded values
+pub trait Interpolate {
+	/// Linearly interpolate from `self` towards `other`, where `t` is in `0.0..=1.0`
+	fn lerp(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+	fn lerp(&self, other: &Self, t: f64) -> Self {
+		self + (other - self) * t
+	}
+}
+
+impl<A: Interpolate, B: Interpolate> Interpolate for (A, B) {
+	fn lerp(&self, other: &Self, t: f64) -> Self {
+		(self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_lerp_tuple() {
+		assert_eq!((0.0, 1.0).lerp(&(1.0, -1.0), 0.25), (0.25, 0.5));
+	}
+}
@@ -1,46 +1,85 @@
+pub mod erased;
+pub mod format;
+pub mod interpolate;
+pub mod pose;
 pub mod structs;
 
 use std::{
-	collections::HashMap,
+	cell::RefCell,
+	collections::{HashMap, HashSet},
 	fs::File,
 	io::{self, BufRead, BufReader, Write},
+	rc::Rc,
 	time::{Duration, Instant},
 };
 use structs::*;
 
-use vexide::{controller::ControllerState, time::sleep};
+use vexide::time::sleep;
 
-use crate::ControllableSubsystem;
-
-const MAGIC: &str = "REPLAY_MAGIC!";
+use crate::{
+	bindings::{Action, DriverInput},
+	replay::{
+		erased::ErasedSubsystem,
+		format::{FormatError, RecordingReader, StateEncoder},
+	},
+};
 
-fn read_entry(file: &mut impl BufRead, buffer: &mut Vec<u8>) -> Option<RecordingEntry> {
-	buffer.clear();
-	let read_len = file.read_until(b'\0', buffer);
-	if let Err(e) = read_len {
-		eprintln!("reading entry of recording should succeed: {e:?}");
-		return None;
-	} else if let Ok(0) = read_len {
-		return None;
+/// How often subsystem states are applied during a replay
+const TICK_INTERVAL: Duration = Duration::from_millis(5);
+/// The largest gap between two entries that will be interpolated across.
+///
+/// Entries are only written on changes, so a large gap means the state was held the whole time and
+/// blending across it would start moving too early.
+const MAX_INTERPOLATION_GAP: Duration = Duration::from_millis(50);
+
+/// How often the background task writes pending frames to the SD card
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+fn read_entry(reader: &mut RecordingReader<impl BufRead>) -> Option<RecordingEntry> {
+	loop {
+		match reader.next_entry() {
+			Ok(entry) => return entry,
+			Err(FormatError::Io(e)) => {
+				eprintln!("reading entry of recording should succeed: {e:?}");
+				return None;
+			}
+			// Frames are delimited, so skip to the next one (the reader waits for a keyframe)
+			Err(e) => eprintln!("Skipping corrupt frame of recording: {e}"),
+		}
 	}
+}
 
-	let n = match cobs::decode_in_place(buffer) {
-		Ok(n) => n,
-		Err(e) => {
-			eprintln!("cobs decoding entry of recording should succeed: {e:?}");
-			return None;
-		}
-	};
+/// Writes frames to the recording file in batches, so the driver loop never waits on the SD card
+async fn flush_task(mut file: File, pending: Rc<RefCell<PendingWrites>>) {
+	let mut batch = Vec::new();
 
-	let parsed = match ciborium::from_reader(&buffer[0..n]) {
-		Ok(v) => v,
-		Err(e) => {
-			eprintln!("parsing entry of recording should succeed: {e:?}");
-			return None;
+	loop {
+		sleep(FLUSH_INTERVAL).await;
+
+		let finished = {
+			let mut pending = pending.borrow_mut();
+			std::mem::swap(&mut pending.bytes, &mut batch);
+			pending.finished
+		};
+
+		if let Err(e) = file.write_all(&batch).and_then(|_| file.flush()) {
+			eprintln!("Writing replay file should succeed: {e:?}");
+			// Stop recording, rather than piling up frames nothing will write
+			let mut pending = pending.borrow_mut();
+			pending.failed = true;
+			pending.bytes = Vec::new();
+			return;
 		}
-	};
+		batch.clear();
 
-	Some(parsed)
+		if finished {
+			match file.metadata() {
+				Ok(metadata) => println!("Ended recording!\nFile size: {} bytes", metadata.len()),
+				Err(e) => eprintln!("Reading replay file metadata should succeed: {e:?}"),
+			}
+			return;
+		}
+	}
 }
 
 #[derive(Default)]
@@ -54,159 +93,201 @@ impl ReplaySubsystem {
 	}
 
 	pub fn start_recording(&mut self, path: &str, duration: Duration) -> io::Result<()> {
+		self.stop_recording();
+
 		let mut file = File::options()
 			.create(true)
 			.write(true)
 			.truncate(true)
 			.open(path)?;
 
-		file.write_all(MAGIC.as_bytes())?;
-		file.write_all(format!("{}", duration.as_secs()).as_bytes())?;
-		file.write_all(b"\n")?;
+		format::write_header(&mut file, duration)?;
 		file.flush()?;
 
 		println!("Started recording for {duration:?}");
 
+		let pending = Rc::new(RefCell::new(PendingWrites::default()));
+		vexide::task::spawn(flush_task(file, pending.clone())).detach();
+
 		self.state = SubsystemState::Enabled {
-			file,
-			buffer: Vec::new(),
+			pending,
+			encoder: StateEncoder::new(),
 			state_buffer: Vec::new(),
 			start_time: Instant::now(),
 			duration,
-			previous_state: SerializedSubsystemStates::new(),
 			mode: ReplayMode::Recording,
 		};
 
 		Ok(())
 	}
 
+	/// Stops recording, leaving the background task to write out whatever is left
+	pub fn stop_recording(&mut self) {
+		if let SubsystemState::Enabled { pending, .. } = &self.state {
+			pending.borrow_mut().finished = true;
+			self.state = SubsystemState::Disabled;
+		}
+	}
+
 	pub fn record(
 		&mut self,
-		controller: &ControllerState,
-		states: &[(&'static str, &dyn ControllableSubsystem)],
+		input: &DriverInput,
+		states: &[(&'static str, &dyn ErasedSubsystem)],
 	) {
-		let current_state = &mut self.state;
-		if let SubsystemState::Enabled {
-			file,
-			buffer,
+		let SubsystemState::Enabled {
+			pending,
+			encoder,
 			state_buffer,
 			start_time,
 			duration,
-			previous_state,
 			mode,
-		} = current_state
-		{
-			match mode {
-				// Exit if the duration has passed or x pressed
-				_ if start_time.elapsed() > *duration || controller.button_x.is_now_pressed() => {
-					file.flush().expect("Flushing replay file should succeed");
-					let metadata = file
-						.metadata()
-						.expect("Reading replay file metadata should succeed");
-					println!("Ended recording!\nFile size: {} bytes", metadata.len());
-					*current_state = SubsystemState::Disabled;
-				}
-				// If recording, write to file
-				ReplayMode::Recording => {
-					state_buffer.clear();
-					state_buffer.extend(states.iter().filter_map(|(name, subsystem)| {
-						Some((name.to_string(), subsystem.state()?))
-					}));
-
-					if *state_buffer != *previous_state {
-						// 2^32 - 1 microseconds is well over the 2min this code needs to deal with, so discard the rest
-						let micros_elapsed = start_time.elapsed().as_micros() as u32;
-
-						buffer.clear();
-						ciborium::into_writer(
-							&RecordingEntry {
-								subsystem_states: state_buffer.clone(),
-								micros_elapsed,
-							},
-							&mut *buffer,
-						)
-						.expect("Serializing recording entry should succeed");
-						let unencoded_len = buffer.len();
-
-						let max_encoded_len = cobs::max_encoding_length(unencoded_len);
-						buffer.resize(unencoded_len + max_encoded_len, 0);
-
-						let (unencoded, encoded) = buffer.split_at_mut(unencoded_len);
-						let encoded_len = cobs::encode(unencoded, encoded);
-
-						file.write_all(&encoded[0..encoded_len])
-							.expect("Recording state should succeed");
-						file.write_all(b"\0")
-							.expect("Recording state delimeter should succeed");
-						file.flush().expect("flushing should succeed");
-
-						_ = std::mem::replace(previous_state, state_buffer.clone());
+		} = &mut self.state
+		else {
+			return;
+		};
+
+		match mode {
+			// Exit if the duration has passed or the stop button is pressed
+			_ if start_time.elapsed() > *duration || input.is_now_pressed(Action::ReplayStop) => {
+				self.stop_recording();
+			}
+			_ if pending.borrow().failed => {
+				eprintln!("Stopped recording, the replay file couldn't be written");
+				self.stop_recording();
+			}
+			// If recording, encode anything that changed for the background task to write
+			ReplayMode::Recording => {
+				let mut pending = pending.borrow_mut();
+
+				state_buffer.clear();
+				for (name, subsystem) in states {
+					match subsystem.serialized_state() {
+						Some(Ok(state)) => {
+							encoder.encode_schema(name, subsystem.schema(), &mut pending.bytes);
+							state_buffer.push((*name, state));
+						}
+						Some(Err(e)) => eprintln!("Recording {name} failed: {e}"),
+						None => (),
 					}
 				}
-				_ => (),
+
+				// 2^32 - 1 microseconds is well over the 2min this code needs to deal with, so discard the rest
+				let micros_elapsed = start_time.elapsed().as_micros() as u32;
+
+				if let Err(e) = encoder.encode(micros_elapsed, state_buffer, &mut pending.bytes) {
+					eprintln!("Stopped recording, encoding states failed: {e}");
+					drop(pending);
+					self.stop_recording();
+				}
 			}
+			_ => (),
 		}
 	}
 
 	pub async fn replay(
 		&mut self,
 		file: File,
-		mut subsystems: HashMap<&'static str, &mut dyn ControllableSubsystem>,
-	) {
-		let mut reader = BufReader::new(file);
-		let mut buffer = Vec::new();
-
-		let mut magic = String::new();
-		if let Err(e) = reader.read_line(&mut magic) {
-			eprintln!("Reading magic line should succeed: {e:?}");
-			return;
+		mut subsystems: HashMap<&'static str, &mut dyn ErasedSubsystem>,
+	) -> Option<ReplayReport> {
+		let mut reader = match RecordingReader::new(BufReader::new(file)) {
+			Ok(reader) => reader,
+			Err(e) => {
+				eprintln!("Reading replay header should succeed: {e}");
+				return None;
+			}
 		};
-		magic.pop(); // newline
+		let duration = reader.duration();
 
-		let Some((_, secs)) = magic.split_once(MAGIC) else {
-			eprintln!("Splitting magic line should succeed: {magic}");
-			return;
-		};
-		let Ok(secs) = secs.parse() else {
-			eprintln!(
-				"Parsing magic duration should succeed: {secs}, {}",
-				secs.len()
-			);
-			return;
-		};
-		let duration = Duration::from_secs(secs);
+		// Only report each subsystem's mismatched state once, rather than every tick
+		let mut mismatched = HashSet::new();
 
-		let mut next_entry = read_entry(&mut reader, &mut buffer);
+		let mut current_entry: Option<RecordingEntry> = None;
+		let mut next_entry = read_entry(&mut reader);
 
-		let mut current_states = None;
+		let mut report = ReplayReport::default();
+		let mut total_tick_slip = Duration::ZERO;
 
 		let started_at = Instant::now();
+		let mut deadline = started_at;
 		loop {
-			let elapsed = started_at.elapsed();
+			// Wait for the next tick, measuring how late the executor woke us up
+			sleep(deadline.saturating_duration_since(Instant::now())).await;
+			let now = Instant::now();
+			let tick_slip = now.saturating_duration_since(deadline);
+			total_tick_slip += tick_slip;
+			report.max_tick_slip = report.max_tick_slip.max(tick_slip);
+			report.ticks += 1;
+
+			// Schedule off of the original start time so slip doesn't accumulate, skipping any
+			// ticks we were too late for entirely
+			deadline += TICK_INTERVAL;
+			while deadline <= now {
+				deadline += TICK_INTERVAL;
+				report.skipped_ticks += 1;
+			}
 
+			let elapsed = now - started_at;
 			if elapsed > duration {
 				println!("Auton finished");
 				break;
 			}
 
-			if next_entry
-				.as_ref()
-				.is_some_and(|ne| elapsed > Duration::from_micros(ne.micros_elapsed as u64))
+			// Catch up on every entry that should have started by now
+			while let Some(entry) = next_entry
+				.take_if(|ne| elapsed >= Duration::from_micros(ne.micros_elapsed as u64))
 			{
-				current_states = Some(next_entry.take().unwrap().subsystem_states);
-				next_entry = read_entry(&mut reader, &mut buffer);
+				let entry_slip = elapsed - Duration::from_micros(entry.micros_elapsed as u64);
+				report.max_entry_slip = report.max_entry_slip.max(entry_slip);
+				report.entries_applied += 1;
+
+				current_entry = Some(entry);
+				next_entry = read_entry(&mut reader);
 			}
 
-			if let Some(ref states) = current_states {
-				for (subsystem, state) in states {
-					if let Some(subsystem) = subsystems.get_mut(subsystem.as_str()) {
-						subsystem.direct(state);
-						sleep(Duration::ZERO).await // Let VexOS flush everything necessary
+			let Some(current) = &current_entry else {
+				continue;
+			};
+
+			// How far we are between the current and next entry, if they're close enough together
+			// that blending between them is meaningful
+			let blend = next_entry.as_ref().and_then(|next| {
+				let gap = next.micros_elapsed.saturating_sub(current.micros_elapsed);
+				let into = (elapsed.as_micros() as u32).saturating_sub(current.micros_elapsed);
+
+				(gap > 0 && Duration::from_micros(gap as u64) <= MAX_INTERPOLATION_GAP)
+					.then(|| (next, into as f64 / gap as f64))
+			});
+
+			for (name, state) in &current.subsystem_states {
+				let Some(subsystem) = subsystems.get_mut(name.as_str()) else {
+					continue;
+				};
+
+				let to = blend.and_then(|(next, t)| {
+					let (_, next_state) = next.subsystem_states.iter().find(|(n, _)| n == name)?;
+					Some((next_state, t))
+				});
+
+				if let Err(e) = subsystem.replay_state(state, to) {
+					report.state_errors += 1;
+					if mismatched.insert(name.clone()) {
+						eprintln!("Replaying {name} failed: {e}");
 					}
 				}
 			}
+		}
 
-			sleep(Duration::from_millis(2)).await
+		if report.ticks > 0 {
+			report.mean_tick_slip = total_tick_slip / report.ticks as u32;
 		}
+		println!("{report}");
+
+		Some(report)
+	}
+}
+
+impl Drop for ReplaySubsystem {
+	fn drop(&mut self) {
+		self.stop_recording();
 	}
 }
@@ -0,0 +1,24 @@
+use evian::prelude::{TracksHeading, TracksPosition};
+
+use super::structs::RecordedPose;
+use crate::{ControllableSubsystem, bindings::DriverInput};
+
+/// Records the tracked pose alongside the subsystem states, so a recording can be turned back
+/// into a route later. It can't be replayed directly, so it ignores [`ControllableSubsystem::direct`].
+pub struct PoseRecorder<'a, T>(pub &'a T);
+
+impl<T: TracksPosition + TracksHeading> ControllableSubsystem for PoseRecorder<'_, T> {
+	type State = RecordedPose;
+
+	fn control(&mut self, _input: &DriverInput) {}
+
+	fn state(&self) -> Option<Self::State> {
+		let position = self.0.position();
+
+		Some(RecordedPose {
+			x: position.x,
+			y: position.y,
+			heading: self.0.heading().as_degrees(),
+		})
+	}
+}
@@ -1,41 +1,100 @@
 use serde::{Deserialize, Serialize};
 use std::{
-	fs::File,
+	cell::RefCell,
+	fmt::Display,
+	rc::Rc,
 	time::{Duration, Instant},
 };
 
+use super::format::StateEncoder;
+
 pub type SerializedSubsystemStates = Vec<(String, ciborium::Value)>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RecordingEntry {
 	pub subsystem_states: SerializedSubsystemStates,
 	pub micros_elapsed: u32,
 }
 
+/// The tracked pose of the robot when an entry was recorded
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPose {
+	/// Inches
+	pub x: f64,
+	/// Inches
+	pub y: f64,
+	/// Degrees, using the same convention as the tracking heading
+	pub heading: f64,
+}
+
 #[derive(Debug)]
 pub enum ReplayMode {
 	Replaying { next_entry: Option<RecordingEntry> },
 	Recording,
 }
 
+/// Encoded frames shared between the recorder and the task writing them to the SD card
+#[derive(Debug, Default)]
+pub struct PendingWrites {
+	/// Frames that haven't been written yet
+	pub bytes: Vec<u8>,
+	/// Whether recording has stopped, and the file should be closed after the next write
+	pub finished: bool,
+	/// Whether writing to the file failed, so nothing more will be written
+	pub failed: bool,
+}
+
 #[derive(Debug, Default)]
 pub enum SubsystemState {
 	#[default]
 	Disabled,
 	Enabled {
-		/// The file storing the replay data
-		file: File,
-		/// A buffer to be used for reading from files
-		buffer: Vec<u8>,
+		/// Frames waiting to be written to the replay file
+		pending: Rc<RefCell<PendingWrites>>,
+		/// Turns subsystem states into keyframes and deltas
+		encoder: StateEncoder,
 		/// A buffer to be used for storing subsystem states
-		state_buffer: Vec<(String, ciborium::Value)>,
+		state_buffer: Vec<(&'static str, ciborium::Value)>,
 		/// When the replay subsystem was enabled
 		start_time: Instant,
 		/// How long this replay session runs for
 		duration: Duration,
-		/// The previous state of the controller
-		previous_state: SerializedSubsystemStates,
 		/// Whether the subsystem is recording or replaying
 		mode: ReplayMode,
 	},
 }
+
+/// Timing statistics for a finished replay, used to judge how faithfully it followed the recording
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplayReport {
+	/// How many recorded entries were applied
+	pub entries_applied: usize,
+	/// How many scheduler ticks ran
+	pub ticks: usize,
+	/// Ticks that were missed entirely because the previous one ran too late
+	pub skipped_ticks: usize,
+	/// The latest an entry was applied after its recorded time
+	pub max_entry_slip: Duration,
+	/// The latest a tick woke up after its deadline
+	pub max_tick_slip: Duration,
+	/// The average amount a tick woke up after its deadline
+	pub mean_tick_slip: Duration,
+	/// How many times a recorded state didn't match its subsystem's state type
+	pub state_errors: usize,
+}
+
+impl Display for ReplayReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(
+			f,
+			"Replayed {} entries over {} ticks ({} skipped)\nEntry slip: {:?} max\nTick slip: {:?} max, {:?} mean\nState errors: {}",
+			self.entries_applied,
+			self.ticks,
+			self.skipped_ticks,
+			self.max_entry_slip,
+			self.max_tick_slip,
+			self.mean_tick_slip,
+			self.state_errors
+		)
+	}
+}
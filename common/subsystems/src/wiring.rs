@@ -0,0 +1,285 @@
+//! A description of where everything on a bot is plugged in, which can be checked for mistakes
+//! before any ports are used, and written out as a table for whoever's doing the wiring.
+
+use std::fmt::Display;
+
+use vexide::{prelude::Direction, smart::SmartDeviceType};
+
+use crate::checks::ExpectedDevice;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Port {
+	/// A smart port, 1 to 21
+	Smart(u8),
+	/// An ADI (three-wire) port, by the letter on the brain, `'A'` to `'H'`. Lowercase letters
+	/// don't exist, so `'a'` and `'A'` can't both be used without it being caught.
+	Adi(char),
+}
+
+impl Port {
+	pub fn exists(&self) -> bool {
+		match *self {
+			Port::Smart(port) => (1..=21).contains(&port),
+			Port::Adi(port) => ('A'..='H').contains(&port),
+		}
+	}
+}
+
+impl Display for Port {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Port::Smart(port) => write!(f, "{port}"),
+			Port::Adi(port) => write!(f, "{port}"),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceKind {
+	Motor(Direction),
+	Optical,
+	Imu,
+	/// The coprocessor, over a generic serial connection
+	Coprocessor,
+	Piston,
+}
+
+impl DeviceKind {
+	/// What the brain reports for a smart device of this kind, or `None` if it goes on an ADI port
+	pub fn smart_type(&self) -> Option<SmartDeviceType> {
+		match self {
+			DeviceKind::Motor(_) => Some(SmartDeviceType::Motor),
+			DeviceKind::Optical => Some(SmartDeviceType::Optical),
+			DeviceKind::Imu => Some(SmartDeviceType::Imu),
+			DeviceKind::Coprocessor => Some(SmartDeviceType::GenericSerial),
+			DeviceKind::Piston => None,
+		}
+	}
+
+	/// Whether this kind of device can go on `port` at all
+	pub fn fits(&self, port: Port) -> bool {
+		matches!(port, Port::Smart(_)) == self.smart_type().is_some()
+	}
+}
+
+impl Display for DeviceKind {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			DeviceKind::Motor(Direction::Forward) => "Motor",
+			DeviceKind::Motor(Direction::Reverse) => "Motor (reversed)",
+			DeviceKind::Optical => "Optical sensor",
+			DeviceKind::Imu => "IMU",
+			DeviceKind::Coprocessor => "Coprocessor",
+			DeviceKind::Piston => "Piston",
+		})
+	}
+}
+
+/// One device and the port it's plugged into
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Wire {
+	pub port: Port,
+	pub device: &'static str,
+	pub kind: DeviceKind,
+}
+
+impl Wire {
+	pub const fn new(port: Port, device: &'static str, kind: DeviceKind) -> Self {
+		Self { port, device, kind }
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WiringError {
+	/// There's no port with that number or letter
+	NoSuchPort(Wire),
+	/// A smart device is on an ADI port, or the other way around
+	WrongPortType(Wire),
+	/// Two devices are on the same port
+	Duplicate { first: Wire, second: Wire },
+}
+
+impl Display for WiringError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::NoSuchPort(wire) => write!(
+				f,
+				"{} is on port {}, which doesn't exist",
+				wire.device, wire.port
+			),
+			Self::WrongPortType(wire) => {
+				let expected = if wire.kind.smart_type().is_some() { "smart" } else { "ADI" };
+				write!(
+					f,
+					"{} is on port {}, but a {} goes on a {expected} port",
+					wire.device, wire.port, wire.kind
+				)
+			}
+			Self::Duplicate { first, second } => write!(
+				f,
+				"{} and {} are both on port {}",
+				first.device, second.device, first.port
+			),
+		}
+	}
+}
+
+impl std::error::Error for WiringError {}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct WiringTable {
+	pub wires: Vec<Wire>,
+}
+
+impl WiringTable {
+	pub fn new(wires: impl IntoIterator<Item = Wire>) -> Self {
+		Self {
+			wires: wires.into_iter().collect(),
+		}
+	}
+
+	/// Every problem with the wiring, or `Ok` if there aren't any
+	pub fn validate(&self) -> Result<(), Vec<WiringError>> {
+		let mut errors = Vec::new();
+
+		for (i, wire) in self.wires.iter().enumerate() {
+			if !wire.port.exists() {
+				errors.push(WiringError::NoSuchPort(*wire));
+			} else if !wire.kind.fits(wire.port) {
+				errors.push(WiringError::WrongPortType(*wire));
+			}
+
+			if let Some(first) = self.wires[..i].iter().find(|first| first.port == wire.port) {
+				errors.push(WiringError::Duplicate {
+					first: *first,
+					second: *wire,
+				});
+			}
+		}
+
+		if errors.is_empty() { Ok(()) } else { Err(errors) }
+	}
+
+	/// The wires in port order, smart ports first
+	pub fn sorted(&self) -> Vec<Wire> {
+		let mut wires = self.wires.clone();
+		wires.sort_by_key(|wire| wire.port);
+		wires
+	}
+
+	/// Every smart port device, for the pre-match device check
+	pub fn expected_devices(&self) -> Vec<ExpectedDevice> {
+		self.wires
+			.iter()
+			.filter_map(|wire| match (wire.port, wire.kind.smart_type()) {
+				(Port::Smart(port), Some(kind)) => {
+					Some(ExpectedDevice::new(wire.device, port, kind))
+				}
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// The wiring as a markdown document, titled `title`
+	pub fn to_markdown(&self, title: &str) -> String {
+		let mut markdown = format!("# {title}\n\n");
+		markdown += "Generated from the bot's port map, don't edit it by hand.\n\n";
+		markdown += "| Port | Device | Type |\n| --- | --- | --- |\n";
+		for wire in self.sorted() {
+			markdown += &format!("| {} | {} | {} |\n", wire.port, wire.device, wire.kind);
+		}
+
+		markdown
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const LEFT: Wire = Wire::new(
+		Port::Smart(1),
+		"Left drive",
+		DeviceKind::Motor(Direction::Forward),
+	);
+	const IMU: Wire = Wire::new(Port::Smart(6), "IMU", DeviceKind::Imu);
+	const FLAP: Wire = Wire::new(Port::Adi('H'), "Flap", DeviceKind::Piston);
+
+	#[test]
+	fn test_valid() {
+		assert_eq!(WiringTable::new([LEFT, IMU, FLAP]).validate(), Ok(()));
+	}
+
+	#[test]
+	fn test_duplicates() {
+		let other = Wire::new(Port::Smart(6), "Coprocessor", DeviceKind::Coprocessor);
+
+		assert_eq!(
+			WiringTable::new([LEFT, IMU, other]).validate(),
+			Err(vec![WiringError::Duplicate {
+				first: IMU,
+				second: other
+			}])
+		);
+	}
+
+	#[test]
+	fn test_port_types() {
+		let missing = Wire::new(Port::Smart(22), "IMU", DeviceKind::Imu);
+		let piston = Wire::new(Port::Smart(3), "Flap", DeviceKind::Piston);
+		let motor = Wire::new(
+			Port::Adi('A'),
+			"Left drive",
+			DeviceKind::Motor(Direction::Forward),
+		);
+
+		assert_eq!(
+			WiringTable::new([missing, piston, motor]).validate(),
+			Err(vec![
+				WiringError::NoSuchPort(missing),
+				WiringError::WrongPortType(piston),
+				WiringError::WrongPortType(motor),
+			])
+		);
+	}
+
+	#[test]
+	fn test_lowercase_adi() {
+		let lower = Wire::new(Port::Adi('h'), "Wing", DeviceKind::Piston);
+
+		assert_eq!(
+			WiringTable::new([FLAP, lower]).validate(),
+			Err(vec![WiringError::NoSuchPort(lower)])
+		);
+	}
+
+	#[test]
+	fn test_markdown_in_port_order() {
+		let markdown = WiringTable::new([FLAP, IMU, LEFT]).to_markdown("Test");
+		let rows = markdown
+			.lines()
+			.filter(|line| line.starts_with('|'))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			rows,
+			[
+				"| Port | Device | Type |",
+				"| --- | --- | --- |",
+				"| 1 | Left drive | Motor |",
+				"| 6 | IMU | IMU |",
+				"| H | Flap | Piston |",
+			]
+		);
+	}
+
+	#[test]
+	fn test_expected_devices_skip_pistons() {
+		let devices = WiringTable::new([LEFT, FLAP]).expected_devices();
+
+		assert_eq!(
+			devices,
+			[ExpectedDevice::new("Left drive", 1, SmartDeviceType::Motor)]
+		);
+	}
+}
@@ -0,0 +1,169 @@
+//! Commands built from closures and futures, for when a whole [`Command`] impl is overkill.
+
+use std::{
+	pin::Pin,
+	time::{Duration, Instant},
+};
+
+use futures_util::poll;
+
+use crate::{command::Command, subsystem::SubsystemId};
+
+/// Runs a closure once, then finishes
+pub struct RunOnce<F: FnMut()> {
+	action: F,
+	requirements: Vec<SubsystemId>,
+}
+
+impl<F: FnMut()> RunOnce<F> {
+	pub fn new(requirements: &[SubsystemId], action: F) -> Self {
+		Self {
+			action,
+			requirements: requirements.to_vec(),
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<F: FnMut()> Command for RunOnce<F> {
+	async fn initialize(&mut self) {
+		(self.action)();
+	}
+
+	fn finished(&self) -> bool {
+		true
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+}
+
+/// Runs a closure every tick until interrupted, which makes it a good default command
+pub struct Run<F: FnMut()> {
+	action: F,
+	requirements: Vec<SubsystemId>,
+}
+
+impl<F: FnMut()> Run<F> {
+	pub fn new(requirements: &[SubsystemId], action: F) -> Self {
+		Self {
+			action,
+			requirements: requirements.to_vec(),
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<F: FnMut()> Command for Run<F> {
+	async fn execute(&mut self) {
+		(self.action)();
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+}
+
+/// Finishes once a duration has passed since it started
+pub struct Wait {
+	duration: Duration,
+	started: Option<Instant>,
+}
+
+impl Wait {
+	pub fn new(duration: Duration) -> Self {
+		Self {
+			duration,
+			started: None,
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Wait {
+	async fn initialize(&mut self) {
+		self.started = Some(Instant::now());
+	}
+
+	fn finished(&self) -> bool {
+		self.started.is_some_and(|s| s.elapsed() >= self.duration)
+	}
+}
+
+/// Finishes once a condition returns true, checking it every tick
+pub struct WaitUntil<F: FnMut() -> bool> {
+	condition: F,
+	met: bool,
+}
+
+impl<F: FnMut() -> bool> WaitUntil<F> {
+	pub fn new(condition: F) -> Self {
+		Self {
+			condition,
+			met: false,
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<F: FnMut() -> bool> Command for WaitUntil<F> {
+	async fn initialize(&mut self) {
+		self.met = false;
+	}
+
+	async fn execute(&mut self) {
+		self.met = (self.condition)();
+	}
+
+	fn finished(&self) -> bool {
+		self.met
+	}
+}
+
+/// Runs a future, polling it once per tick, and finishes when it completes.
+///
+/// A new future is made from `factory` every time the command starts. Interrupting the command
+/// drops the future, so (like cancelling any future) it stops wherever it last yielded.
+pub struct FutureCommand<F: FnMut() -> Fut, Fut: Future<Output = ()>> {
+	factory: F,
+	future: Option<Pin<Box<Fut>>>,
+	requirements: Vec<SubsystemId>,
+}
+
+impl<F: FnMut() -> Fut, Fut: Future<Output = ()>> FutureCommand<F, Fut> {
+	pub fn new(requirements: &[SubsystemId], factory: F) -> Self {
+		Self {
+			factory,
+			future: None,
+			requirements: requirements.to_vec(),
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl<F: FnMut() -> Fut, Fut: Future<Output = ()>> Command for FutureCommand<F, Fut> {
+	async fn initialize(&mut self) {
+		self.future = Some(Box::pin((self.factory)()));
+	}
+
+	async fn execute(&mut self) {
+		if let Some(future) = &mut self.future
+			&& poll!(future.as_mut()).is_ready()
+		{
+			self.future = None;
+		}
+	}
+
+	async fn end(&mut self, _interrupted: bool) {
+		self.future = None;
+	}
+
+	fn finished(&self) -> bool {
+		self.future.is_none()
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+}
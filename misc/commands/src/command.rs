@@ -1,4 +1,12 @@
-#[async_trait::async_trait]
+use std::time::Duration;
+
+use crate::{
+	basic::{Wait, WaitUntil},
+	compose::{Deadline, Parallel, Race, Sequence},
+	subsystem::SubsystemId,
+};
+
+#[async_trait::async_trait(?Send)]
 pub trait Command {
 	/// Runs once when a [Command] is started.
 	///
@@ -13,7 +21,7 @@ pub trait Command {
 	/// Runs once when a [Command] is finsihed or canceled.
 	///
 	/// This should be used to clean-up state, such as setting drivetrain motors to zero
-	async fn end(&mut self) {}
+	async fn end(&mut self, _interrupted: bool) {}
 
 	/// Should return whether or not this command is finished.
 	///
@@ -21,4 +29,93 @@ pub trait Command {
 	fn finished(&self) -> bool {
 		false
 	}
+
+	/// Whether this command finished without doing its job, like a piston that wouldn't fire.
+	///
+	/// A failed command is ended as interrupted, and stops any [Sequence] it's part of.
+	fn failed(&self) -> bool {
+		false
+	}
+
+	/// The subsystems this command needs exclusive use of.
+	///
+	/// Scheduling a command interrupts any running commands that share a requirement with it.
+	fn requirements(&self) -> &[SubsystemId] {
+		&[]
+	}
+
+	/// Whether a conflicting command can interrupt this one. If not, the new command isn't scheduled
+	fn interruptible(&self) -> bool {
+		true
+	}
 }
+
+#[async_trait::async_trait(?Send)]
+impl<C: Command + ?Sized> Command for Box<C> {
+	async fn initialize(&mut self) {
+		(**self).initialize().await
+	}
+
+	async fn execute(&mut self) {
+		(**self).execute().await
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		(**self).end(interrupted).await
+	}
+
+	fn finished(&self) -> bool {
+		(**self).finished()
+	}
+
+	fn failed(&self) -> bool {
+		(**self).failed()
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		(**self).requirements()
+	}
+
+	fn interruptible(&self) -> bool {
+		(**self).interruptible()
+	}
+}
+
+/// Shorthands for composing commands
+pub trait CommandExt: Command + Sized + 'static {
+	fn boxed(self) -> Box<dyn Command> {
+		Box::new(self)
+	}
+
+	/// Runs `next` once this command finishes
+	fn and_then(self, next: impl Command + 'static) -> Sequence {
+		Sequence::new(vec![self.boxed(), next.boxed()])
+	}
+
+	/// Runs both commands until both finish
+	fn alongside(self, other: impl Command + 'static) -> Parallel {
+		Parallel::new(vec![self.boxed(), other.boxed()])
+	}
+
+	/// Runs both commands until either finishes
+	fn race_with(self, other: impl Command + 'static) -> Race {
+		Race::new(vec![self.boxed(), other.boxed()])
+	}
+
+	/// Runs `other` alongside this command, interrupting it once this command finishes
+	fn deadline_for(self, other: impl Command + 'static) -> Deadline {
+		Deadline::new(self.boxed(), vec![other.boxed()])
+	}
+
+	/// Interrupts this command once `condition` returns true
+	fn until(self, condition: impl FnMut() -> bool + 'static) -> Race {
+		self.race_with(WaitUntil::new(condition))
+	}
+
+	/// Interrupts this command if it's still running after `timeout`
+	fn with_timeout(self, timeout: Duration) -> Race {
+		self.race_with(Wait::new(timeout))
+	}
+}
+
+impl<C: Command + 'static> CommandExt for C {}
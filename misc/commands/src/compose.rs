@@ -0,0 +1,372 @@
+//! Commands made out of other commands.
+
+use crate::{command::Command, subsystem::SubsystemId};
+
+/// Every requirement of `commands`, without duplicates
+fn union<'a>(commands: impl IntoIterator<Item = &'a Box<dyn Command>>) -> Vec<SubsystemId> {
+	let mut requirements = Vec::new();
+	for command in commands {
+		for requirement in command.requirements() {
+			if !requirements.contains(requirement) {
+				requirements.push(*requirement);
+			}
+		}
+	}
+	requirements
+}
+
+/// Panics if two of `commands` share a requirement, since they'd fight over the subsystem
+fn assert_disjoint<'a>(commands: impl IntoIterator<Item = &'a Box<dyn Command>>) {
+	let mut seen = Vec::new();
+	for command in commands {
+		for requirement in command.requirements() {
+			assert!(
+				!seen.contains(requirement),
+				"Commands run together shouldn't share {requirement:?}"
+			);
+		}
+		seen.extend_from_slice(command.requirements());
+	}
+}
+
+/// Runs commands one after another, stopping early if one fails
+pub struct Sequence {
+	commands: Vec<Box<dyn Command>>,
+	index: usize,
+	failed: bool,
+	requirements: Vec<SubsystemId>,
+}
+
+impl Sequence {
+	pub fn new(commands: Vec<Box<dyn Command>>) -> Self {
+		Self {
+			requirements: union(&commands),
+			commands,
+			index: 0,
+			failed: false,
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Sequence {
+	async fn initialize(&mut self) {
+		self.index = 0;
+		self.failed = false;
+		if let Some(first) = self.commands.first_mut() {
+			first.initialize().await;
+		}
+	}
+
+	async fn execute(&mut self) {
+		let Some(current) = self.commands.get_mut(self.index) else {
+			return;
+		};
+
+		current.execute().await;
+		if current.finished() {
+			let failed = current.failed();
+			current.end(failed).await;
+
+			if failed {
+				// Skip the rest, they probably depend on what this one was meant to do
+				self.failed = true;
+				self.index = self.commands.len();
+			} else {
+				self.index += 1;
+				if let Some(next) = self.commands.get_mut(self.index) {
+					next.initialize().await;
+				}
+			}
+		}
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		if interrupted && let Some(current) = self.commands.get_mut(self.index) {
+			current.end(true).await;
+		}
+	}
+
+	fn finished(&self) -> bool {
+		self.index >= self.commands.len()
+	}
+
+	fn failed(&self) -> bool {
+		self.failed
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+
+	fn interruptible(&self) -> bool {
+		self.commands.iter().all(|c| c.interruptible())
+	}
+}
+
+/// Runs commands at the same time until all of them finish
+pub struct Parallel {
+	/// Each command, and whether it's still running
+	commands: Vec<(Box<dyn Command>, bool)>,
+	requirements: Vec<SubsystemId>,
+}
+
+impl Parallel {
+	pub fn new(commands: Vec<Box<dyn Command>>) -> Self {
+		assert_disjoint(&commands);
+
+		Self {
+			requirements: union(&commands),
+			commands: commands.into_iter().map(|c| (c, false)).collect(),
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Parallel {
+	async fn initialize(&mut self) {
+		for (command, running) in &mut self.commands {
+			command.initialize().await;
+			*running = true;
+		}
+	}
+
+	async fn execute(&mut self) {
+		for (command, running) in &mut self.commands {
+			if !*running {
+				continue;
+			}
+
+			command.execute().await;
+			if command.finished() {
+				command.end(command.failed()).await;
+				*running = false;
+			}
+		}
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		if !interrupted {
+			return;
+		}
+
+		for (command, running) in &mut self.commands {
+			if *running {
+				command.end(true).await;
+				*running = false;
+			}
+		}
+	}
+
+	fn finished(&self) -> bool {
+		self.commands.iter().all(|(_, running)| !running)
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+
+	fn interruptible(&self) -> bool {
+		self.commands.iter().all(|(c, _)| c.interruptible())
+	}
+}
+
+/// Runs commands at the same time until any one of them finishes, interrupting the rest
+pub struct Race {
+	commands: Vec<Box<dyn Command>>,
+	winner: Option<usize>,
+	requirements: Vec<SubsystemId>,
+}
+
+impl Race {
+	pub fn new(commands: Vec<Box<dyn Command>>) -> Self {
+		assert_disjoint(&commands);
+
+		Self {
+			requirements: union(&commands),
+			commands,
+			winner: None,
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Race {
+	async fn initialize(&mut self) {
+		self.winner = None;
+		for command in &mut self.commands {
+			command.initialize().await;
+		}
+	}
+
+	async fn execute(&mut self) {
+		for (i, command) in self.commands.iter_mut().enumerate() {
+			command.execute().await;
+			if command.finished() {
+				self.winner = Some(i);
+				return;
+			}
+		}
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		for (i, command) in self.commands.iter_mut().enumerate() {
+			command.end(interrupted || self.winner != Some(i) || command.failed()).await;
+		}
+	}
+
+	fn finished(&self) -> bool {
+		self.winner.is_some()
+	}
+
+	fn failed(&self) -> bool {
+		self.winner.is_some_and(|i| self.commands[i].failed())
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+
+	fn interruptible(&self) -> bool {
+		self.commands.iter().all(|c| c.interruptible())
+	}
+}
+
+/// Runs commands alongside a deadline command, interrupting any still running once it finishes
+pub struct Deadline {
+	deadline: Box<dyn Command>,
+	others: Parallel,
+	requirements: Vec<SubsystemId>,
+}
+
+impl Deadline {
+	pub fn new(deadline: Box<dyn Command>, others: Vec<Box<dyn Command>>) -> Self {
+		assert_disjoint(std::iter::once(&deadline).chain(&others));
+
+		Self {
+			requirements: union(std::iter::once(&deadline).chain(&others)),
+			deadline,
+			others: Parallel::new(others),
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Deadline {
+	async fn initialize(&mut self) {
+		self.deadline.initialize().await;
+		self.others.initialize().await;
+	}
+
+	async fn execute(&mut self) {
+		self.deadline.execute().await;
+		self.others.execute().await;
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		self.deadline.end(interrupted).await;
+		// Only ends the ones that haven't finished on their own
+		self.others.end(true).await;
+	}
+
+	fn finished(&self) -> bool {
+		self.deadline.finished()
+	}
+
+	fn failed(&self) -> bool {
+		self.deadline.failed()
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+
+	fn interruptible(&self) -> bool {
+		self.deadline.interruptible() && self.others.interruptible()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		command::CommandExt,
+		testing::{Log, Step, block_on},
+	};
+
+	#[test]
+	fn test_sequence_runs_in_order() {
+		let log = Log::default();
+		let mut sequence = Step::new(&log, "a", 2, &[]).and_then(Step::new(&log, "b", 1, &[]));
+
+		block_on(async {
+			sequence.initialize().await;
+			while !sequence.finished() {
+				sequence.execute().await;
+			}
+			sequence.end(false).await;
+		});
+
+		assert_eq!(
+			log.take(),
+			[
+				"a initialize",
+				"a execute",
+				"a execute",
+				"a end",
+				"b initialize",
+				"b execute",
+				"b end"
+			]
+		);
+	}
+
+	#[test]
+	fn test_sequence_stops_at_failure() {
+		let log = Log::default();
+		let mut piston = Step::new(&log, "piston", 1, &[]);
+		piston.fails = true;
+		let mut sequence = piston.and_then(Step::new(&log, "unjam", 1, &[]));
+
+		block_on(async {
+			sequence.initialize().await;
+			while !sequence.finished() {
+				sequence.execute().await;
+			}
+			sequence.end(sequence.failed()).await;
+		});
+
+		assert!(sequence.failed());
+		assert_eq!(
+			log.take(),
+			["piston initialize", "piston execute", "piston interrupted"]
+		);
+	}
+
+	#[test]
+	fn test_race_interrupts_losers() {
+		let log = Log::default();
+		let mut race = Step::new(&log, "slow", 5, &[]).race_with(Step::new(&log, "fast", 1, &[]));
+
+		block_on(async {
+			race.initialize().await;
+			while !race.finished() {
+				race.execute().await;
+			}
+			race.end(false).await;
+		});
+
+		let log = log.take();
+		assert!(log.contains(&"slow interrupted".to_string()));
+		assert!(log.contains(&"fast end".to_string()));
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_parallel_rejects_shared_requirements() {
+		let log = Log::default();
+		let intake = SubsystemId("intake");
+
+		_ = Step::new(&log, "a", 1, &[intake]).alongside(Step::new(&log, "b", 1, &[intake]));
+	}
+}
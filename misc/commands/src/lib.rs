@@ -1,2 +1,20 @@
+pub mod basic;
 pub mod command;
+pub mod compose;
+pub mod scheduler;
 pub mod subsystem;
+pub mod trigger;
+
+#[cfg(test)]
+mod testing;
+
+pub mod prelude {
+	pub use crate::{
+		basic::{FutureCommand, Run, RunOnce, Wait, WaitUntil},
+		command::{Command, CommandExt},
+		compose::{Deadline, Parallel, Race, Sequence},
+		scheduler::{CommandId, DefaultCommandError, Scheduler},
+		subsystem::{Subsystem, SubsystemId},
+		trigger::Trigger,
+	};
+}
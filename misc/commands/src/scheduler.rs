@@ -0,0 +1,462 @@
+use std::{collections::HashMap, fmt::Display};
+
+use vexide::controller::ControllerState;
+
+use crate::{
+	command::Command,
+	subsystem::{Subsystem, SubsystemId},
+	trigger::{Edge, Trigger},
+};
+
+/// Identifies a scheduled command, so it can be cancelled later
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct CommandId(u64);
+
+/// Returned by [`Scheduler::set_default`] when the command doesn't require the subsystem it's
+/// meant to be the default of, so it would never give way to anything else using it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DefaultCommandError(pub SubsystemId);
+
+impl Display for DefaultCommandError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "the default command of {:?} doesn't require it", self.0)
+	}
+}
+
+impl std::error::Error for DefaultCommandError {}
+
+struct Scheduled {
+	id: CommandId,
+	command: Box<dyn Command>,
+	requirements: Vec<SubsystemId>,
+	initialized: bool,
+	/// The subsystem this is the default command of, so it can be handed back when it stops
+	default_of: Option<SubsystemId>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BindingKind {
+	/// Schedule when the trigger activates
+	OnTrue,
+	/// Schedule when the trigger activates, and cancel when it deactivates
+	WhileTrue,
+	/// Schedule when the trigger activates, or cancel if it's still running
+	ToggleOnTrue,
+}
+
+struct Binding<I> {
+	trigger: Trigger<I>,
+	kind: BindingKind,
+	factory: Box<dyn FnMut() -> Box<dyn Command>>,
+	running: Option<CommandId>,
+}
+
+/// Runs commands, making sure only one command uses a subsystem at a time.
+///
+/// Call [`Scheduler::tick`] every loop, e.g. from the driver control loop, with the input its
+/// [`Trigger`]s read.
+pub struct Scheduler<I = ControllerState> {
+	subsystems: Vec<Box<dyn Subsystem>>,
+	/// Default commands that aren't running right now
+	defaults: HashMap<SubsystemId, Box<dyn Command>>,
+	scheduled: Vec<Scheduled>,
+	queued: Vec<(CommandId, Box<dyn Command>)>,
+	cancelled: Vec<CommandId>,
+	bindings: Vec<Binding<I>>,
+	next_id: u64,
+}
+
+impl<I> Default for Scheduler<I> {
+	fn default() -> Self {
+		Self {
+			subsystems: Vec::new(),
+			defaults: HashMap::new(),
+			scheduled: Vec::new(),
+			queued: Vec::new(),
+			cancelled: Vec::new(),
+			bindings: Vec::new(),
+			next_id: 0,
+		}
+	}
+}
+
+impl<I: 'static> Scheduler<I> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers a subsystem so its [`Subsystem::periodic`] runs every tick
+	pub fn register(&mut self, subsystem: impl Subsystem + 'static) {
+		self.subsystems.push(Box::new(subsystem));
+	}
+
+	/// Sets the command that runs whenever nothing else requires `subsystem`
+	pub fn set_default(
+		&mut self,
+		subsystem: SubsystemId,
+		command: impl Command + 'static,
+	) -> Result<(), DefaultCommandError> {
+		if !command.requirements().contains(&subsystem) {
+			return Err(DefaultCommandError(subsystem));
+		}
+
+		// Stop the old default if it's running, so the new one takes over next tick
+		if let Some(old) = self
+			.scheduled
+			.iter()
+			.find(|s| s.default_of == Some(subsystem))
+		{
+			self.cancelled.push(old.id);
+		}
+		self.defaults.insert(subsystem, Box::new(command));
+
+		Ok(())
+	}
+
+	fn next_id(&mut self) -> CommandId {
+		self.next_id += 1;
+		CommandId(self.next_id)
+	}
+
+	/// Schedules a command to start on the next tick, interrupting any commands it conflicts with
+	pub fn schedule(&mut self, command: impl Command + 'static) -> CommandId {
+		self.schedule_boxed(Box::new(command))
+	}
+
+	pub fn schedule_boxed(&mut self, command: Box<dyn Command>) -> CommandId {
+		let id = self.next_id();
+		self.queued.push((id, command));
+		id
+	}
+
+	/// Cancels a command on the next tick, if it's still scheduled
+	pub fn cancel(&mut self, id: CommandId) {
+		self.cancelled.push(id);
+	}
+
+	/// Cancels every scheduled command, which lets default commands take over
+	pub fn cancel_all(&mut self) {
+		self.queued.clear();
+		self.cancelled
+			.extend(self.scheduled.iter().filter(|s| s.default_of.is_none()).map(|s| s.id));
+	}
+
+	/// Whether a command is scheduled or running
+	pub fn is_scheduled(&self, id: CommandId) -> bool {
+		!self.cancelled.contains(&id)
+			&& (self.scheduled.iter().any(|s| s.id == id)
+				|| self.queued.iter().any(|(queued, _)| *queued == id))
+	}
+
+	fn bind<C: Command + 'static>(
+		&mut self,
+		trigger: Trigger<I>,
+		kind: BindingKind,
+		mut factory: impl FnMut() -> C + 'static,
+	) {
+		self.bindings.push(Binding {
+			trigger,
+			kind,
+			factory: Box::new(move || Box::new(factory())),
+			running: None,
+		});
+	}
+
+	/// Schedules a new command from `factory` whenever `trigger` activates
+	pub fn on_true<C: Command + 'static>(
+		&mut self,
+		trigger: Trigger<I>,
+		factory: impl FnMut() -> C + 'static,
+	) {
+		self.bind(trigger, BindingKind::OnTrue, factory);
+	}
+
+	/// Runs a new command from `factory` while `trigger` is active
+	pub fn while_true<C: Command + 'static>(
+		&mut self,
+		trigger: Trigger<I>,
+		factory: impl FnMut() -> C + 'static,
+	) {
+		self.bind(trigger, BindingKind::WhileTrue, factory);
+	}
+
+	/// Starts a new command from `factory` when `trigger` activates, or cancels it if it's running
+	pub fn toggle_on_true<C: Command + 'static>(
+		&mut self,
+		trigger: Trigger<I>,
+		factory: impl FnMut() -> C + 'static,
+	) {
+		self.bind(trigger, BindingKind::ToggleOnTrue, factory);
+	}
+
+	fn poll_bindings(&mut self, input: &I) {
+		let mut bindings = std::mem::take(&mut self.bindings);
+
+		for binding in &mut bindings {
+			let running = binding.running.filter(|id| self.is_scheduled(*id));
+
+			binding.running = match (binding.kind, binding.trigger.update(input), running) {
+				(BindingKind::ToggleOnTrue, Edge::Rising, Some(id)) => {
+					self.cancel(id);
+					None
+				}
+				(_, Edge::Rising, _) => Some(self.schedule_boxed((binding.factory)())),
+				(BindingKind::WhileTrue, Edge::Falling, Some(id)) => {
+					self.cancel(id);
+					None
+				}
+				_ => running,
+			};
+		}
+
+		self.bindings = bindings;
+	}
+
+	/// Takes a command that stopped running, handing default commands back to their subsystem
+	fn retire(&mut self, scheduled: Scheduled) {
+		if let Some(subsystem) = scheduled.default_of {
+			self.defaults.entry(subsystem).or_insert(scheduled.command);
+		}
+	}
+
+	async fn stop(&mut self, index: usize, interrupted: bool) {
+		let mut scheduled = self.scheduled.remove(index);
+		if scheduled.initialized {
+			scheduled.command.end(interrupted).await;
+		}
+		self.retire(scheduled);
+	}
+
+	async fn start(&mut self, id: CommandId, command: Box<dyn Command>) {
+		let requirements = command.requirements().to_vec();
+		let conflicts = |s: &Scheduled| s.requirements.iter().any(|r| requirements.contains(r));
+
+		// Default commands can always be interrupted, that's the point of them
+		if let Some(blocking) = self
+			.scheduled
+			.iter()
+			.find(|s| conflicts(s) && s.default_of.is_none() && !s.command.interruptible())
+		{
+			eprintln!(
+				"Not scheduling command, {:?} is required by an uninterruptible command",
+				blocking.requirements
+			);
+			return;
+		}
+
+		let mut i = 0;
+		while i < self.scheduled.len() {
+			if conflicts(&self.scheduled[i]) {
+				self.stop(i, true).await;
+			} else {
+				i += 1;
+			}
+		}
+
+		self.scheduled.push(Scheduled {
+			id,
+			command,
+			requirements,
+			initialized: false,
+			default_of: None,
+		});
+	}
+
+	/// Runs one iteration of every scheduled command.
+	///
+	/// Triggers are only checked when an input is given, so autons can pass `None`.
+	pub async fn tick(&mut self, input: Option<&I>) {
+		for subsystem in &mut self.subsystems {
+			subsystem.periodic();
+		}
+
+		if let Some(input) = input {
+			self.poll_bindings(input);
+		}
+
+		for id in std::mem::take(&mut self.cancelled) {
+			self.queued.retain(|(queued, _)| *queued != id);
+			if let Some(i) = self.scheduled.iter().position(|s| s.id == id) {
+				self.stop(i, true).await;
+			}
+		}
+
+		for (id, command) in std::mem::take(&mut self.queued) {
+			self.start(id, command).await;
+		}
+
+		// Start default commands for any subsystems left idle
+		let idle = self
+			.defaults
+			.keys()
+			.filter(|subsystem| !self.scheduled.iter().any(|s| s.requirements.contains(subsystem)))
+			.copied()
+			.collect::<Vec<_>>();
+		for subsystem in idle {
+			if let Some(command) = self.defaults.remove(&subsystem) {
+				let id = self.next_id();
+				self.scheduled.push(Scheduled {
+					id,
+					requirements: command.requirements().to_vec(),
+					command,
+					initialized: false,
+					default_of: Some(subsystem),
+				});
+			}
+		}
+
+		let mut i = 0;
+		while i < self.scheduled.len() {
+			let scheduled = &mut self.scheduled[i];
+			if !scheduled.initialized {
+				scheduled.command.initialize().await;
+				scheduled.initialized = true;
+			}
+
+			scheduled.command.execute().await;
+			if scheduled.command.finished() {
+				let failed = scheduled.command.failed();
+				self.stop(i, failed).await;
+			} else {
+				i += 1;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		basic::Run,
+		testing::{Log, Step, block_on},
+	};
+
+	const INTAKE: SubsystemId = SubsystemId("intake");
+
+	#[test]
+	fn test_conflict_interrupts_and_default_resumes() {
+		let log = Log::default();
+		let mut scheduler: Scheduler = Scheduler::new();
+
+		let default_log = log.clone();
+		scheduler
+			.set_default(
+				INTAKE,
+				Run::new(&[INTAKE], move || default_log.push("default".to_string())),
+			)
+			.unwrap();
+
+		block_on(async {
+			scheduler.tick(None).await;
+			scheduler.schedule(Step::new(&log, "unjam", 1, &[INTAKE]));
+			scheduler.tick(None).await;
+			scheduler.tick(None).await;
+		});
+
+		assert_eq!(
+			log.take(),
+			[
+				"default",
+				"unjam initialize",
+				"unjam execute",
+				"unjam end",
+				"default"
+			]
+		);
+	}
+
+	#[test]
+	fn test_default_must_require_subsystem() {
+		let mut scheduler: Scheduler = Scheduler::new();
+
+		assert_eq!(
+			scheduler.set_default(INTAKE, Run::new(&[], || {})),
+			Err(DefaultCommandError(INTAKE))
+		);
+	}
+
+	#[test]
+	fn test_failed_command_ends_interrupted() {
+		let log = Log::default();
+		let mut scheduler: Scheduler = Scheduler::new();
+
+		let mut piston = Step::new(&log, "piston", 1, &[]);
+		piston.fails = true;
+
+		block_on(async {
+			scheduler.schedule(piston);
+			scheduler.tick(None).await;
+		});
+
+		assert_eq!(
+			log.take(),
+			["piston initialize", "piston execute", "piston interrupted"]
+		);
+	}
+
+	#[test]
+	fn test_uninterruptible_blocks_conflicts() {
+		let log = Log::default();
+		let mut scheduler: Scheduler = Scheduler::new();
+
+		let mut park = Step::new(&log, "park", 3, &[INTAKE]);
+		park.interruptible = false;
+
+		block_on(async {
+			scheduler.schedule(park);
+			scheduler.tick(None).await;
+			let intake = scheduler.schedule(Step::new(&log, "intake", 1, &[INTAKE]));
+			scheduler.tick(None).await;
+			assert!(!scheduler.is_scheduled(intake));
+		});
+
+		assert!(!log.take().iter().any(|event| event.starts_with("intake")));
+	}
+
+	#[test]
+	fn test_while_true() {
+		let log = Log::default();
+		let mut scheduler = Scheduler::<bool>::new();
+
+		let unjam_log = log.clone();
+		scheduler.while_true(Trigger::new(|held: &bool| *held), move || {
+			Step::new(&unjam_log, "unjam", 10, &[INTAKE])
+		});
+
+		block_on(async {
+			for held in [true, true, false, false] {
+				scheduler.tick(Some(&held)).await;
+			}
+		});
+
+		assert_eq!(
+			log.take(),
+			[
+				"unjam initialize",
+				"unjam execute",
+				"unjam execute",
+				"unjam interrupted"
+			]
+		);
+	}
+
+	#[test]
+	fn test_cancel() {
+		let log = Log::default();
+		let mut scheduler: Scheduler = Scheduler::new();
+
+		block_on(async {
+			let id = scheduler.schedule(Step::new(&log, "drive", 10, &[]));
+			scheduler.tick(None).await;
+			scheduler.cancel(id);
+			scheduler.tick(None).await;
+			assert!(!scheduler.is_scheduled(id));
+		});
+
+		assert_eq!(
+			log.take(),
+			["drive initialize", "drive execute", "drive interrupted"]
+		);
+	}
+}
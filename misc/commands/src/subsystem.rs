@@ -1,9 +1,12 @@
+/// Identifies a subsystem, so the scheduler can tell which commands conflict
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SubsystemId(pub &'static str);
+
 /// The common trait that should be implemented by all subsystems
 pub trait Subsystem {
-	/// The periodic function is run
-	fn periodic();
+	/// The id commands use to require this subsystem
+	fn id(&self) -> SubsystemId;
 
-	fn state<'a>() -> Option<&'a [u8]> {
-		None
-	}
+	/// Runs every scheduler tick, whichever command (if any) requires this subsystem
+	fn periodic(&mut self) {}
 }
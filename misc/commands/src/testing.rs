@@ -0,0 +1,100 @@
+//! Helpers for testing commands without a brain.
+
+use std::{
+	cell::RefCell,
+	pin::pin,
+	rc::Rc,
+	task::{Context, Poll, Waker},
+};
+
+use crate::{command::Command, subsystem::SubsystemId};
+
+/// Polls a future to completion. Commands under test never wait on anything, so spinning is fine
+pub fn block_on<F: Future>(future: F) -> F::Output {
+	let mut future = pin!(future);
+	let mut cx = Context::from_waker(Waker::noop());
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+			return output;
+		}
+	}
+}
+
+/// A shared log of what commands did
+#[derive(Clone, Default)]
+pub struct Log(Rc<RefCell<Vec<String>>>);
+
+impl Log {
+	pub fn push(&self, event: String) {
+		self.0.borrow_mut().push(event);
+	}
+
+	pub fn take(&self) -> Vec<String> {
+		self.0.take()
+	}
+}
+
+/// A command that finishes after executing a number of times, logging everything it does
+pub struct Step {
+	log: Log,
+	name: &'static str,
+	executions: usize,
+	remaining: usize,
+	requirements: Vec<SubsystemId>,
+	pub interruptible: bool,
+	/// Whether it reports [`Command::failed`] once it's finished
+	pub fails: bool,
+}
+
+impl Step {
+	pub fn new(
+		log: &Log,
+		name: &'static str,
+		executions: usize,
+		requirements: &[SubsystemId],
+	) -> Self {
+		Self {
+			log: log.clone(),
+			name,
+			executions,
+			remaining: executions,
+			requirements: requirements.to_vec(),
+			interruptible: true,
+			fails: false,
+		}
+	}
+}
+
+#[async_trait::async_trait(?Send)]
+impl Command for Step {
+	async fn initialize(&mut self) {
+		self.remaining = self.executions;
+		self.log.push(format!("{} initialize", self.name));
+	}
+
+	async fn execute(&mut self) {
+		self.remaining = self.remaining.saturating_sub(1);
+		self.log.push(format!("{} execute", self.name));
+	}
+
+	async fn end(&mut self, interrupted: bool) {
+		let event = if interrupted { "interrupted" } else { "end" };
+		self.log.push(format!("{} {event}", self.name));
+	}
+
+	fn finished(&self) -> bool {
+		self.remaining == 0
+	}
+
+	fn failed(&self) -> bool {
+		self.fails && self.finished()
+	}
+
+	fn requirements(&self) -> &[SubsystemId] {
+		&self.requirements
+	}
+
+	fn interruptible(&self) -> bool {
+		self.interruptible
+	}
+}
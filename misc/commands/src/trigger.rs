@@ -0,0 +1,54 @@
+use vexide::controller::ControllerState;
+
+/// A condition on an input, such as a button being held, that commands can be bound to.
+///
+/// The input is whatever the scheduler is ticked with, the controller's state unless it's given
+/// something else (like the robot's actions, read through the driver's bindings).
+pub struct Trigger<I = ControllerState> {
+	condition: Box<dyn FnMut(&I) -> bool>,
+	last: bool,
+}
+
+/// How a trigger changed since it was last checked
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Edge {
+	Rising,
+	Falling,
+	Unchanged,
+}
+
+impl<I: 'static> Trigger<I> {
+	pub fn new(condition: impl FnMut(&I) -> bool + 'static) -> Self {
+		Self {
+			condition: Box::new(condition),
+			last: false,
+		}
+	}
+
+	/// Active while both triggers are
+	pub fn and(mut self, mut other: Trigger<I>) -> Self {
+		Self::new(move |input| (self.condition)(input) && (other.condition)(input))
+	}
+
+	/// Active while either trigger is
+	pub fn or(mut self, mut other: Trigger<I>) -> Self {
+		Self::new(move |input| (self.condition)(input) || (other.condition)(input))
+	}
+
+	/// Active while this trigger isn't
+	pub fn negate(mut self) -> Self {
+		Self::new(move |input| !(self.condition)(input))
+	}
+
+	pub(crate) fn update(&mut self, input: &I) -> Edge {
+		let active = (self.condition)(input);
+		let edge = match (self.last, active) {
+			(false, true) => Edge::Rising,
+			(true, false) => Edge::Falling,
+			_ => Edge::Unchanged,
+		};
+		self.last = active;
+
+		edge
+	}
+}
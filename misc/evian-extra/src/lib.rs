@@ -5,6 +5,7 @@ pub mod control;
 pub mod math;
 pub mod motion;
 pub mod utils;
+pub mod voltage;
 
 pub trait TankVelocity: DrivetrainModel {
 	/// Drives a tank drivetrain at a specified velocity in RPMs
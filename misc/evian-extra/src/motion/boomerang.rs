@@ -5,10 +5,14 @@ use std::{
 
 use evian::{
 	control::loops::Feedback,
-	math::{Angle, Vec2},
-	prelude::{Arcade, Drivetrain, Tolerances, TracksHeading, TracksPosition, TracksVelocity},
+	math::{Angle, Vec2, desaturate},
+	prelude::{
+		Arcade, Drivetrain, Tank, Tolerances, TracksHeading, TracksPosition, TracksVelocity,
+	},
 };
 
+use crate::voltage::VoltageCompensation;
+
 pub struct BoomerangMotion<
 	L: Feedback<State = f64, Signal = f64> + Clone,
 	A: Feedback<State = Angle, Signal = f64> + Clone,
@@ -17,6 +21,8 @@ pub struct BoomerangMotion<
 	pub angular_controller: A,
 	pub linear_tolerances: Tolerances,
 	pub angular_tolerances: Tolerances,
+	/// Scales output against the battery's voltage, if set
+	pub compensation: Option<VoltageCompensation>,
 }
 
 impl<
@@ -24,7 +30,7 @@ impl<
 	A: Feedback<State = Angle, Signal = f64> + Clone,
 > BoomerangMotion<L, A>
 {
-	pub fn boomerang<'a, M: Arcade, T: TracksPosition + TracksVelocity + TracksHeading>(
+	pub fn boomerang<'a, M: Arcade + Tank, T: TracksPosition + TracksVelocity + TracksHeading>(
 		&self,
 		drivetrain: &'a mut Drivetrain<M, T>,
 		target: Vec2<f64>,
@@ -36,6 +42,7 @@ impl<
 			angular_controller: self.angular_controller.clone(),
 			linear_tolerances: self.linear_tolerances,
 			angular_tolerances: self.angular_tolerances,
+			compensation: self.compensation,
 			drivetrain,
 			target,
 			theta,
@@ -49,13 +56,14 @@ pub struct BoomerangFuture<
 	'a,
 	L: Feedback<State = f64, Signal = f64>,
 	A: Feedback<State = Angle, Signal = f64>,
-	M: Arcade,
+	M: Arcade + Tank,
 	T: TracksPosition + TracksVelocity + TracksHeading,
 > {
 	linear_controller: L,
 	angular_controller: A,
 	linear_tolerances: Tolerances,
 	angular_tolerances: Tolerances,
+	compensation: Option<VoltageCompensation>,
 	drivetrain: &'a mut Drivetrain<M, T>,
 	target: Vec2<f64>,
 	theta: Angle,
@@ -67,7 +75,7 @@ impl<
 	'a,
 	L: Feedback<State = f64, Signal = f64> + Unpin,
 	A: Feedback<State = Angle, Signal = f64> + Unpin,
-	M: Arcade,
+	M: Arcade + Tank,
 	T: TracksPosition + TracksVelocity + TracksHeading,
 > Future for BoomerangFuture<'a, L, A, M, T>
 {
@@ -119,7 +127,15 @@ impl<
 			.update(angular_error, this.theta, dt);
 		this.last_poll = Some(Instant::now());
 
-		_ = this.drivetrain.model.drive_arcade(throttle, steer);
+		match this.compensation {
+			// Compensate each side, so the ratio between them (and so the curve) is kept
+			Some(compensation) => {
+				let [left, right] = desaturate([throttle + steer, throttle - steer], 1_f64);
+				let (left, right) = compensation.apply_pair((left, right));
+				_ = this.drivetrain.model.drive_tank(left, right);
+			}
+			None => _ = this.drivetrain.model.drive_arcade(throttle, steer),
+		}
 
 		Poll::Pending
 	}
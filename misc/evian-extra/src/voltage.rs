@@ -0,0 +1,105 @@
+//! Scales commanded output against the battery's voltage, so an open-loop command (like driving at
+//! 35% for half a second) does the same thing on a fresh battery as on a tired one.
+//!
+//! A fraction of `1.0` means the full `nominal` voltage. When the battery is above `nominal`,
+//! output is turned down to match, and when it's below, output is turned up until it runs out of
+//! headroom at full power.
+//!
+//! evian's own motions (`Basic`, `Seeking`) aren't compensated, since they drive the model
+//! directly. They're closed-loop, so a weaker battery only slows them down rather than changing
+//! where they end up, which is the problem this solves for open-loop driving.
+
+use vexide::battery;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VoltageCompensation {
+	/// The battery voltage commands are tuned at
+	pub nominal: f64,
+	/// Readings under this are treated as this, so a bad reading can't ask for huge output
+	pub min_voltage: f64,
+}
+
+impl Default for VoltageCompensation {
+	fn default() -> Self {
+		Self::new(12.0)
+	}
+}
+
+impl VoltageCompensation {
+	pub const fn new(nominal: f64) -> Self {
+		Self {
+			nominal,
+			min_voltage: 10.0,
+		}
+	}
+
+	/// How much to scale output by at the battery's current voltage
+	pub fn scale(&self) -> f64 {
+		self.scale_at(battery::voltage())
+	}
+
+	/// How much to scale output by at `voltage`
+	pub fn scale_at(&self, voltage: f64) -> f64 {
+		self.nominal / voltage.max(self.min_voltage)
+	}
+
+	/// Compensates a single output (-1.0 to 1.0)
+	pub fn apply(&self, fraction: f64) -> f64 {
+		apply(self.scale(), fraction)
+	}
+
+	/// Compensates a pair of outputs, like the two sides of a drivetrain, turning both down
+	/// together if either would go over full power so their ratio (and so the robot's curvature)
+	/// stays the same
+	pub fn apply_pair(&self, pair: (f64, f64)) -> (f64, f64) {
+		apply_pair(self.scale(), pair)
+	}
+}
+
+fn apply(scale: f64, fraction: f64) -> f64 {
+	(fraction * scale).clamp(-1.0, 1.0)
+}
+
+fn apply_pair(scale: f64, (a, b): (f64, f64)) -> (f64, f64) {
+	let (a, b) = (a * scale, b * scale);
+	let max = a.abs().max(b.abs()).max(1.0);
+
+	(a / max, b / max)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_scale() {
+		let compensation = VoltageCompensation::new(12.0);
+
+		assert_eq!(compensation.scale_at(12.0), 1.0);
+		assert!((compensation.scale_at(12.8) - 0.9375).abs() < 1e-9);
+		assert!(compensation.scale_at(11.5) > 1.0);
+		// A bad reading is capped
+		assert_eq!(compensation.scale_at(0.0), compensation.scale_at(10.0));
+	}
+
+	#[test]
+	fn test_same_voltage_across_batteries() {
+		let compensation = VoltageCompensation::new(12.0);
+
+		for battery in [11.6, 12.0, 12.4, 12.9] {
+			let fraction = apply(compensation.scale_at(battery), 0.35);
+			assert!((fraction * battery - 0.35 * 12.0).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_clamps_at_full_power() {
+		assert_eq!(apply(1.2, 0.9), 1.0);
+		assert_eq!(apply(1.2, -0.9), -1.0);
+
+		let (left, right) = apply_pair(1.2, (1.0, 0.5));
+		assert_eq!(left, 1.0);
+		assert!((right - 0.5).abs() < 1e-9);
+		assert_eq!(apply_pair(0.9, (1.0, -1.0)), (0.9, -0.9));
+	}
+}
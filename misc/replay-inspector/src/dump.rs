@@ -0,0 +1,142 @@
+use std::{collections::HashMap, io::Write, time::Duration};
+
+use ciborium::Value;
+
+use crate::recording::Recording;
+
+pub fn json(recording: &Recording, out: &mut impl Write) -> anyhow::Result<()> {
+	let entries = recording
+		.entries
+		.iter()
+		.map(|entry| {
+			let states = entry
+				.subsystem_states
+				.iter()
+				.map(|(name, state)| Ok((name.clone(), serde_json::to_value(state)?)))
+				.collect::<serde_json::Result<serde_json::Map<_, _>>>()?;
+
+			Ok(serde_json::json!({
+				"micros_elapsed": entry.micros_elapsed,
+				"states": states,
+			}))
+		})
+		.collect::<serde_json::Result<Vec<_>>>()?;
+
+	serde_json::to_writer_pretty(
+		&mut *out,
+		&serde_json::json!({
+			"duration_secs": recording.duration.as_secs(),
+			"entries": entries,
+		}),
+	)?;
+	writeln!(out)?;
+
+	Ok(())
+}
+
+/// Writes one row per entry, with a column for every leaf value of every subsystem's state
+pub fn csv(recording: &Recording, out: &mut impl Write) -> anyhow::Result<()> {
+	let mut columns = Vec::<String>::new();
+	let mut rows = Vec::with_capacity(recording.entries.len());
+
+	for entry in &recording.entries {
+		let mut fields = Vec::new();
+		for (name, state) in &entry.subsystem_states {
+			flatten(name.clone(), state, &mut fields);
+		}
+
+		for (column, _) in &fields {
+			if !columns.contains(column) {
+				columns.push(column.clone());
+			}
+		}
+
+		rows.push((entry.micros_elapsed, fields.into_iter().collect::<HashMap<_, _>>()));
+	}
+
+	writeln!(out, "time_secs,{}", columns.join(","))?;
+	for (micros, fields) in rows {
+		let values = columns
+			.iter()
+			.map(|c| fields.get(c).map(String::as_str).unwrap_or_default())
+			.collect::<Vec<_>>();
+
+		writeln!(
+			out,
+			"{:.6},{}",
+			Duration::from_micros(micros as u64).as_secs_f64(),
+			values.join(",")
+		)?;
+	}
+
+	Ok(())
+}
+
+fn flatten(path: String, value: &Value, out: &mut Vec<(String, String)>) {
+	match value {
+		Value::Map(entries) => {
+			for (key, value) in entries {
+				let key = match key {
+					Value::Text(text) => text.clone(),
+					other => format!("{other:?}"),
+				};
+				flatten(format!("{path}.{key}"), value, out);
+			}
+		}
+		Value::Array(items) => {
+			for (i, value) in items.iter().enumerate() {
+				flatten(format!("{path}.{i}"), value, out);
+			}
+		}
+		Value::Float(f) => out.push((path, f.to_string())),
+		Value::Integer(i) => out.push((path, i128::from(*i).to_string())),
+		Value::Bool(b) => out.push((path, b.to_string())),
+		Value::Text(text) => out.push((path, text.clone())),
+		Value::Null => out.push((path, String::new())),
+		other => out.push((path, format!("{other:?}"))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use subsystems::replay::structs::RecordingEntry;
+
+	use super::*;
+
+	#[test]
+	fn test_csv_columns() {
+		let recording = Recording {
+			duration: Duration::from_secs(1),
+			schemas: Vec::new(),
+			entries: vec![
+				RecordingEntry {
+					subsystem_states: vec![(
+						"drivetrain".to_string(),
+						Value::serialized(&(0.5, -0.5)).unwrap(),
+					)],
+					micros_elapsed: 0,
+				},
+				RecordingEntry {
+					subsystem_states: vec![
+						(
+							"drivetrain".to_string(),
+							Value::serialized(&(1.0, 1.0)).unwrap(),
+						),
+						("flap".to_string(), Value::Text("Extended".to_string())),
+					],
+					micros_elapsed: 500_000,
+				},
+			],
+		};
+
+		let mut out = Vec::new();
+		csv(&recording, &mut out).unwrap();
+
+		assert_eq!(
+			String::from_utf8(out).unwrap(),
+			"time_secs,drivetrain.0,drivetrain.1,flap\n\
+			0.000000,0.5,-0.5,\n\
+			0.500000,1,1,Extended\n"
+		);
+	}
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use subsystems::replay::structs::RecordingEntry;
+
+use crate::recording::{Recording, whole_secs};
+
+/// Keeps only the part of a recording between `start` and `end`, shifted to start at zero.
+///
+/// The state at `start` is carried over as the first entry, so the trimmed recording begins with
+/// the mechanisms where they were at the cut rather than wherever the last change left them.
+pub fn trim(recording: &Recording, start: Duration, end: Option<Duration>) -> Recording {
+	let end = end
+		.unwrap_or(recording.duration)
+		.min(recording.duration);
+	let start_micros = start.as_micros() as u32;
+	let end_micros = end.as_micros() as u32;
+
+	let carried = recording
+		.entries
+		.iter()
+		.rev()
+		.find(|e| e.micros_elapsed <= start_micros)
+		.map(|e| RecordingEntry {
+			subsystem_states: e.subsystem_states.clone(),
+			micros_elapsed: 0,
+		});
+
+	let entries = carried
+		.into_iter()
+		.chain(
+			recording
+				.entries
+				.iter()
+				.filter(|e| e.micros_elapsed > start_micros && e.micros_elapsed <= end_micros)
+				.map(|e| RecordingEntry {
+					subsystem_states: e.subsystem_states.clone(),
+					micros_elapsed: e.micros_elapsed - start_micros,
+				}),
+		)
+		.collect();
+
+	Recording {
+		duration: whole_secs(end.saturating_sub(start)),
+		schemas: recording.schemas.clone(),
+		entries,
+	}
+}
+
+/// Joins recordings end to end, each starting once the previous one's duration has passed
+pub fn splice(recordings: &[Recording]) -> Recording {
+	let mut offset = Duration::ZERO;
+	let mut schemas = Vec::<(String, String)>::new();
+	let mut entries = Vec::new();
+
+	for recording in recordings {
+		for (name, schema) in &recording.schemas {
+			if !schemas.iter().any(|(n, _)| n == name) {
+				schemas.push((name.clone(), schema.clone()));
+			}
+		}
+
+		let offset_micros = offset.as_micros() as u32;
+		entries.extend(recording.entries.iter().map(|e| RecordingEntry {
+			subsystem_states: e.subsystem_states.clone(),
+			micros_elapsed: e.micros_elapsed + offset_micros,
+		}));
+
+		offset += recording.duration;
+	}
+
+	Recording {
+		duration: offset,
+		schemas,
+		entries,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ciborium::Value;
+
+	use super::*;
+
+	fn entry(micros_elapsed: u32, value: f64) -> RecordingEntry {
+		RecordingEntry {
+			subsystem_states: vec![("intake".to_string(), Value::Float(value))],
+			micros_elapsed,
+		}
+	}
+
+	#[test]
+	fn test_trim_carries_state() {
+		let recording = Recording {
+			duration: Duration::from_secs(10),
+			schemas: Vec::new(),
+			entries: vec![entry(0, 0.0), entry(1_000_000, 1.0), entry(3_000_000, 0.5)],
+		};
+
+		let trimmed = trim(
+			&recording,
+			Duration::from_millis(1500),
+			Some(Duration::from_secs(4)),
+		);
+
+		assert_eq!(trimmed.duration, Duration::from_secs(3));
+		assert_eq!(
+			trimmed.entries,
+			vec![entry(0, 1.0), entry(1_500_000, 0.5)]
+		);
+	}
+
+	#[test]
+	fn test_splice_offsets() {
+		let a = Recording {
+			duration: Duration::from_secs(2),
+			schemas: Vec::new(),
+			entries: vec![entry(0, 1.0)],
+		};
+		let b = Recording {
+			duration: Duration::from_secs(3),
+			schemas: Vec::new(),
+			entries: vec![entry(500_000, 0.0)],
+		};
+
+		let spliced = splice(&[a, b]);
+
+		assert_eq!(spliced.duration, Duration::from_secs(5));
+		assert_eq!(spliced.entries, vec![entry(0, 1.0), entry(2_500_000, 0.0)]);
+	}
+}
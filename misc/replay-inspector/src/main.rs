@@ -0,0 +1,103 @@
+//! Host-side tool for inspecting and editing recordings made by `ReplaySubsystem`.
+//!
+//! Build and run it for your computer rather than the brain, e.g.
+//! `cargo run -p replay-inspector -- stats replay.bin`
+
+mod dump;
+mod edit;
+mod recording;
+mod route;
+mod stats;
+mod validate;
+
+use std::{io::stdout, path::PathBuf, process::ExitCode, time::Duration};
+
+use anyhow::{Context, bail};
+
+use crate::recording::Recording;
+
+const USAGE: &str = "\
+Usage: replay-inspector <command> [args]
+
+Commands:
+  stats <recording>                               Print duration, entry count and change frequencies
+  dump <recording> [--csv]                        Print every entry as JSON (or CSV)
+  validate <recording>                            Check every frame, reporting any that are corrupt
+  trim <recording> <output> <start secs> [end secs]
+                                                  Keep only part of a recording
+  splice <output> <recording>...                  Join recordings end to end
+  route <recording> [--rust <function name>]      Convert a recording with poses into auton steps,
+                                                  as JSON or as a Rust auton function";
+
+fn secs(arg: &str) -> anyhow::Result<Duration> {
+	arg.parse::<f64>()
+		.ok()
+		.and_then(|s| Duration::try_from_secs_f64(s).ok())
+		.with_context(|| format!("{arg:?} is not a valid number of seconds"))
+}
+
+fn run(args: &[String]) -> anyhow::Result<()> {
+	let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+	match args.as_slice() {
+		["stats", path] => stats::print(&Recording::load(&PathBuf::from(path))?),
+		["dump", path] => dump::json(&Recording::load(&PathBuf::from(path))?, &mut stdout())?,
+		["dump", path, "--csv"] => {
+			dump::csv(&Recording::load(&PathBuf::from(path))?, &mut stdout())?
+		}
+		["validate", path] => validate::validate(&PathBuf::from(path))?,
+		["trim", input, output, start, rest @ ..] if rest.len() <= 1 => {
+			let recording = Recording::load(&PathBuf::from(input))?;
+			let end = rest.first().map(|end| secs(end)).transpose()?;
+
+			let trimmed = edit::trim(&recording, secs(start)?, end);
+			trimmed.save(&PathBuf::from(output))?;
+			println!(
+				"Wrote {} entries ({}s) to {output}",
+				trimmed.entries.len(),
+				trimmed.duration.as_secs()
+			);
+		}
+		["splice", output, inputs @ ..] if !inputs.is_empty() => {
+			let recordings = inputs
+				.iter()
+				.map(|p| Recording::load(&PathBuf::from(p)))
+				.collect::<anyhow::Result<Vec<_>>>()?;
+
+			let spliced = edit::splice(&recordings);
+			spliced.save(&PathBuf::from(output))?;
+			println!(
+				"Wrote {} entries ({}s) to {output}",
+				spliced.entries.len(),
+				spliced.duration.as_secs()
+			);
+		}
+		["route", path, rest @ ..] => {
+			let steps = route::extract(&Recording::load(&PathBuf::from(path))?);
+
+			match rest {
+				[] => {
+					serde_json::to_writer_pretty(stdout(), &steps)?;
+					println!();
+				}
+				["--rust", name] => print!("{}", route::to_rust(name, &steps)),
+				_ => bail!("{USAGE}"),
+			}
+		}
+		_ => bail!("{USAGE}"),
+	}
+
+	Ok(())
+}
+
+fn main() -> ExitCode {
+	let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+	match run(&args) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(e) => {
+			eprintln!("{e:?}");
+			ExitCode::FAILURE
+		}
+	}
+}
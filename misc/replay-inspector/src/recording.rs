@@ -0,0 +1,76 @@
+use std::{
+	fs::File,
+	io::{BufReader, BufWriter},
+	path::Path,
+	time::Duration,
+};
+
+use anyhow::Context;
+use subsystems::replay::{
+	format::{RecordingReader, RecordingWriter},
+	structs::RecordingEntry,
+};
+
+/// A fully loaded recording
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+	/// The duration in the header, which is how long a replay of this recording will run for
+	pub duration: Duration,
+	/// The state type of each subsystem, by name
+	pub schemas: Vec<(String, String)>,
+	pub entries: Vec<RecordingEntry>,
+}
+
+impl Recording {
+	pub fn load(path: &Path) -> anyhow::Result<Self> {
+		let mut reader = RecordingReader::new(BufReader::new(
+			File::open(path).with_context(|| format!("opening {}", path.display()))?,
+		))
+		.context("reading header")?;
+
+		let mut entries = Vec::new();
+		while let Some(entry) = reader
+			.next_entry()
+			.with_context(|| format!("reading entry {}", entries.len()))?
+		{
+			entries.push(entry);
+		}
+
+		Ok(Self {
+			duration: reader.duration(),
+			schemas: reader.schemas().to_vec(),
+			entries,
+		})
+	}
+
+	/// Saves in the current format, regardless of which version was loaded
+	pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+		let mut writer = RecordingWriter::new(
+			BufWriter::new(File::create(path).with_context(|| format!("creating {}", path.display()))?),
+			self.duration,
+		)?;
+
+		for (name, schema) in &self.schemas {
+			writer.write_schema(name, schema)?;
+		}
+		for entry in &self.entries {
+			writer.write_entry(entry)?;
+		}
+		writer.into_inner()?;
+
+		Ok(())
+	}
+
+	/// The time of the last entry, which may be shorter than [`Recording::duration`]
+	pub fn recorded_span(&self) -> Duration {
+		self.entries
+			.last()
+			.map(|e| Duration::from_micros(e.micros_elapsed as u64))
+			.unwrap_or_default()
+	}
+}
+
+/// Rounds a duration up to whole seconds, since that's all the header can store
+pub fn whole_secs(duration: Duration) -> Duration {
+	Duration::from_secs(duration.as_secs() + u64::from(duration.subsec_nanos() > 0))
+}
@@ -0,0 +1,333 @@
+//! Turns a driver recording into a deterministic route of auton steps.
+//!
+//! The pose path is simplified down to a handful of key poses, each leg of which becomes a
+//! turn-then-drive, and intake/piston state changes are slotted in between by time.
+
+use std::{collections::BTreeMap, fmt::Write as _, time::Duration};
+
+use serde::Serialize;
+use subsystems::{
+	intake::IntakeState,
+	pnemuatics::PneumaticState,
+	replay::structs::{RecordedPose, RecordingEntry},
+};
+
+use crate::recording::Recording;
+
+/// How far (in inches) the path can stray from the simplified route
+const PATH_TOLERANCE: f64 = 1.5;
+/// Legs shorter than this (in inches) are treated as turning in place
+const MIN_DRIVE_DISTANCE: f64 = 1.0;
+/// Turns smaller than this (in degrees) are skipped
+const MIN_TURN: f64 = 3.0;
+/// Gaps shorter than this between steps aren't worth waiting for
+const MIN_WAIT: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum Step {
+	TurnToHeading { degrees: f64 },
+	DriveDistance { inches: f64 },
+	Intake { state: IntakeState },
+	Piston { name: String, state: PneumaticState },
+	Wait { millis: u64 },
+}
+
+/// A step, and the span of the recording it covers
+struct TimedStep {
+	start: u32,
+	end: u32,
+	step: Step,
+}
+
+fn states<'a, T: serde::de::DeserializeOwned>(
+	entries: &'a [RecordingEntry],
+	subsystem: &'a str,
+) -> impl Iterator<Item = (u32, T)> + 'a {
+	entries.iter().filter_map(move |entry| {
+		let (_, value) = entry
+			.subsystem_states
+			.iter()
+			.find(|(name, _)| name == subsystem)?;
+
+		Some((entry.micros_elapsed, value.deserialized().ok()?))
+	})
+}
+
+/// Ramer-Douglas-Peucker simplification, returning the indices of the points to keep
+fn simplify(points: &[(f64, f64)], tolerance: f64) -> Vec<usize> {
+	fn recurse(
+		points: &[(f64, f64)],
+		start: usize,
+		end: usize,
+		tolerance: f64,
+		keep: &mut Vec<usize>,
+	) {
+		let (ax, ay) = points[start];
+		let (bx, by) = points[end];
+		let length = (bx - ax).hypot(by - ay);
+
+		let furthest = (start + 1..end)
+			.map(|i| {
+				let (px, py) = points[i];
+				let distance = if length == 0.0 {
+					(px - ax).hypot(py - ay)
+				} else {
+					((bx - ax) * (ay - py) - (ax - px) * (by - ay)).abs() / length
+				};
+				(i, distance)
+			})
+			.max_by(|a, b| a.1.total_cmp(&b.1));
+
+		if let Some((i, distance)) = furthest
+			&& distance > tolerance
+		{
+			recurse(points, start, i, tolerance, keep);
+			recurse(points, i, end, tolerance, keep);
+		} else {
+			keep.push(end);
+		}
+	}
+
+	if points.is_empty() {
+		return Vec::new();
+	}
+
+	let mut keep = vec![0];
+	if points.len() > 1 {
+		recurse(points, 0, points.len() - 1, tolerance, &mut keep);
+	}
+	keep
+}
+
+/// Wraps an angle in degrees to -180..180
+fn wrap_degrees(degrees: f64) -> f64 {
+	(degrees + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn motion_steps(poses: &[(u32, RecordedPose)]) -> Vec<TimedStep> {
+	let mut steps = Vec::new();
+	let Some((_, first)) = poses.first() else {
+		return steps;
+	};
+
+	let points = poses.iter().map(|(_, p)| (p.x, p.y)).collect::<Vec<_>>();
+	let keep = simplify(&points, PATH_TOLERANCE);
+
+	let mut heading = first.heading;
+	for leg in keep.windows(2) {
+		let (start_time, a) = poses[leg[0]];
+		let (end_time, b) = poses[leg[1]];
+
+		let distance = (b.x - a.x).hypot(b.y - a.y);
+		if distance < MIN_DRIVE_DISTANCE {
+			continue;
+		}
+
+		// Work out whether the robot was driving forwards or backwards along this leg
+		let travel = (b.y - a.y).atan2(b.x - a.x).to_degrees();
+		let reversed = wrap_degrees(travel - a.heading).abs() > 90.0;
+		let facing = if reversed { travel + 180.0 } else { travel };
+
+		if wrap_degrees(facing - heading).abs() > MIN_TURN {
+			steps.push(TimedStep {
+				start: start_time,
+				end: start_time,
+				step: Step::TurnToHeading {
+					degrees: wrap_degrees(facing),
+				},
+			});
+			heading = facing;
+		}
+
+		steps.push(TimedStep {
+			start: start_time,
+			end: end_time,
+			step: Step::DriveDistance {
+				inches: if reversed { -distance } else { distance },
+			},
+		});
+	}
+
+	if let Some((time, last)) = poses.last()
+		&& wrap_degrees(last.heading - heading).abs() > MIN_TURN
+	{
+		steps.push(TimedStep {
+			start: *time,
+			end: *time,
+			step: Step::TurnToHeading {
+				degrees: wrap_degrees(last.heading),
+			},
+		});
+	}
+
+	steps
+}
+
+fn event_steps(entries: &[RecordingEntry]) -> Vec<TimedStep> {
+	let mut steps = Vec::new();
+
+	let mut last_intake = None;
+	for (time, state) in states::<IntakeState>(entries, "intake") {
+		if last_intake != Some(state) {
+			steps.push(TimedStep {
+				start: time,
+				end: time,
+				step: Step::Intake { state },
+			});
+			last_intake = Some(state);
+		}
+	}
+
+	let mut last_pistons = BTreeMap::new();
+	for (time, pistons) in states::<BTreeMap<String, PneumaticState>>(entries, "pneumatics") {
+		for (name, state) in pistons {
+			if last_pistons.get(&name) != Some(&state) {
+				last_pistons.insert(name.clone(), state);
+				steps.push(TimedStep {
+					start: time,
+					end: time,
+					step: Step::Piston { name, state },
+				});
+			}
+		}
+	}
+
+	steps
+}
+
+/// Extracts a route from a recording made with a `PoseRecorder`
+pub fn extract(recording: &Recording) -> Vec<Step> {
+	let poses = states::<RecordedPose>(&recording.entries, "pose").collect::<Vec<_>>();
+
+	let mut timed = motion_steps(&poses);
+	timed.extend(event_steps(&recording.entries));
+	// Stable, so motion and events that start together keep that order
+	timed.sort_by_key(|s| s.start);
+
+	let mut steps = Vec::new();
+	let mut cursor = 0;
+	for TimedStep { start, end, step } in timed {
+		let gap = Duration::from_micros(start.saturating_sub(cursor) as u64);
+		if gap >= MIN_WAIT {
+			steps.push(Step::Wait {
+				millis: gap.as_millis() as u64,
+			});
+		}
+
+		steps.push(step);
+		cursor = cursor.max(end);
+	}
+
+	steps
+}
+
+/// Generates an auton function calling the same APIs as the hand-written autons
+pub fn to_rust(name: &str, steps: &[Step]) -> String {
+	let mut out = String::new();
+
+	_ = writeln!(out, "pub async fn {name}(robot: &mut Robot) {{");
+	_ = writeln!(out, "\tlet mut basic = crate::control::BASIC_CONTROLLER;");
+	_ = writeln!(out);
+
+	for step in steps {
+		_ = match step {
+			Step::TurnToHeading { degrees } => writeln!(
+				out,
+				"\tbasic\n\t\t.turn_to_heading(&mut robot.drivetrain, Angle::from_degrees({degrees:.1}))\n\t\t.await;"
+			),
+			Step::DriveDistance { inches } => writeln!(
+				out,
+				"\tbasic.drive_distance(&mut robot.drivetrain, {inches:.1}).await;"
+			),
+			Step::Intake { state } => writeln!(
+				out,
+				"\trobot.intake.run(IntakeState {{\n\t\ttop: {:.2},\n\t\tmiddle: {:.2},\n\t\tbottom: {:.2},\n\t}});",
+				state.top, state.middle, state.bottom
+			),
+			Step::Piston { name, state } => writeln!(
+				out,
+				"\t_ = robot.pneumatics.set({name:?}, PneumaticState::{state:?});"
+			),
+			Step::Wait { millis } => {
+				writeln!(out, "\tsleep(Duration::from_millis({millis})).await;")
+			}
+		};
+	}
+
+	_ = writeln!(out, "}}");
+
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use ciborium::Value;
+
+	use super::*;
+
+	fn entry(micros_elapsed: u32, x: f64, y: f64, heading: f64) -> RecordingEntry {
+		RecordingEntry {
+			subsystem_states: vec![(
+				"pose".to_string(),
+				Value::serialized(&RecordedPose { x, y, heading }).unwrap(),
+			)],
+			micros_elapsed,
+		}
+	}
+
+	#[test]
+	fn test_simplify_keeps_corners() {
+		let points = [(0.0, 0.0), (5.0, 0.1), (10.0, 0.0), (10.0, 5.0), (10.0, 10.0)];
+
+		assert_eq!(simplify(&points, 1.0), vec![0, 2, 4]);
+	}
+
+	#[test]
+	fn test_extract_forward_then_reverse() {
+		let recording = Recording {
+			duration: Duration::from_secs(15),
+			schemas: Vec::new(),
+			entries: vec![
+				entry(0, 0.0, 0.0, 0.0),
+				entry(500_000, 12.0, 0.0, 0.0),
+				entry(1_000_000, 24.0, 0.0, 0.0),
+				// Turn in place to face +y, then back up along it
+				entry(1_500_000, 24.0, 0.0, 90.0),
+				entry(2_000_000, 24.0, -12.0, 90.0),
+			],
+		};
+
+		assert_eq!(
+			extract(&recording),
+			vec![
+				Step::DriveDistance { inches: 24.0 },
+				Step::TurnToHeading { degrees: 90.0 },
+				Step::DriveDistance { inches: -12.0 },
+			]
+		);
+	}
+
+	#[test]
+	fn test_extract_events_wait() {
+		let mut recording = Recording {
+			duration: Duration::from_secs(15),
+			schemas: Vec::new(),
+			entries: vec![entry(0, 0.0, 0.0, 0.0), entry(1_000_000, 0.0, 0.0, 0.0)],
+		};
+		recording.entries[1].subsystem_states.push((
+			"intake".to_string(),
+			Value::serialized(&IntakeState::full_forward()).unwrap(),
+		));
+
+		assert_eq!(
+			extract(&recording),
+			vec![
+				Step::Wait { millis: 1000 },
+				Step::Intake {
+					state: IntakeState::full_forward()
+				},
+			]
+		);
+	}
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use crate::recording::Recording;
+
+pub fn print(recording: &Recording) {
+	let span = recording.recorded_span();
+
+	println!("Duration:      {}s", recording.duration.as_secs());
+	println!("Recorded span: {:.3}s", span.as_secs_f64());
+	println!("Entries:       {}", recording.entries.len());
+	if recording.entries.len() > 1 {
+		println!(
+			"Mean interval: {:.1}ms",
+			span.as_secs_f64() * 1000.0 / (recording.entries.len() - 1) as f64
+		);
+	}
+
+	if !recording.schemas.is_empty() {
+		println!("State types:");
+		for (name, schema) in &recording.schemas {
+			println!("  {name:<12} {schema}");
+		}
+	}
+
+	// Count how many entries actually changed each subsystem, in the order they first appear
+	let mut names = Vec::new();
+	let mut changes = HashMap::<&str, usize>::new();
+	let mut previous = HashMap::new();
+	for entry in &recording.entries {
+		for (name, state) in &entry.subsystem_states {
+			if previous.insert(name.as_str(), state) != Some(state) {
+				if !changes.contains_key(name.as_str()) {
+					names.push(name.as_str());
+				}
+				*changes.entry(name.as_str()).or_default() += 1;
+			}
+		}
+	}
+
+	println!("Changes per subsystem:");
+	for name in names {
+		let count = changes[name];
+		if span.is_zero() {
+			println!("  {name:<12} {count:>6}");
+		} else {
+			println!(
+				"  {name:<12} {count:>6} ({:.1}Hz)",
+				count as f64 / span.as_secs_f64()
+			);
+		}
+	}
+}
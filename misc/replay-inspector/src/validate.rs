@@ -0,0 +1,67 @@
+use std::{fs::File, io::BufReader, path::Path, time::Duration};
+
+use anyhow::{Context, bail};
+use subsystems::replay::format::{FormatError, RecordingReader};
+
+/// Checks every frame in a recording, continuing past bad frames
+///
+/// Each problem is printed as it's found, and the recording fails validation if there were any.
+pub fn validate(path: &Path) -> anyhow::Result<()> {
+	let mut reader = match RecordingReader::new(BufReader::new(
+		File::open(path).with_context(|| format!("opening {}", path.display()))?,
+	)) {
+		Ok(reader) => reader,
+		Err(e) => bail!("header: {e}"),
+	};
+	let duration = reader.duration();
+
+	println!("{:?} recording, {}s", reader.version(), duration.as_secs());
+
+	let mut problems = 0;
+	let mut index = 0;
+	let mut last_micros = None;
+	loop {
+		let offset = reader.offset();
+
+		match reader.next_entry() {
+			Ok(None) => break,
+			Ok(Some(entry)) => {
+				if last_micros.is_some_and(|last| entry.micros_elapsed < last) {
+					println!(
+						"entry {index} @ byte {offset}: time goes backwards ({}us after {}us)",
+						entry.micros_elapsed,
+						last_micros.unwrap_or_default()
+					);
+					problems += 1;
+				}
+				if Duration::from_micros(entry.micros_elapsed as u64) > duration {
+					println!(
+						"entry {index} @ byte {offset}: past the recording duration, it will never be replayed"
+					);
+					problems += 1;
+				}
+
+				last_micros = Some(entry.micros_elapsed);
+			}
+			// Nothing after an IO error can be trusted
+			Err(FormatError::Io(e)) => return Err(e.into()),
+			Err(e) => {
+				println!("entry {index} @ byte {offset}: {e}");
+				problems += 1;
+
+				if matches!(e, FormatError::Truncated) {
+					break;
+				}
+			}
+		}
+
+		index += 1;
+	}
+
+	println!("{index} entries, {problems} problems");
+	if problems > 0 {
+		bail!("{} is not a valid recording", path.display());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,33 @@
+//! vexide's clock, run off simulated time.
+//!
+//! vexide gets the time from the SDK's `vexSystemTimeGet` and `vexSystemHighResTimeGet`, which
+//! vex-sdk-mock lets the host answer through its time hooks. Once a [`Sim`](crate::Sim) has run,
+//! those hooks answer with the simulated time on the calling thread, so `sleep` and `Instant` only
+//! move when the simulation steps. Each test runs on its own thread, so they don't share a clock.
+
+use std::{cell::Cell, sync::Once, time::Duration};
+
+thread_local! {
+	static NOW: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// `vexSystemTimeGet`, in milliseconds
+fn system_time() -> u32 {
+	NOW.get().as_millis() as u32
+}
+
+/// `vexSystemHighResTimeGet`, in microseconds
+fn system_high_res_time() -> u64 {
+	NOW.get().as_micros() as u64
+}
+
+/// Sets the time vexide sees on this thread
+pub(crate) fn set(now: Duration) {
+	static HOOKS: Once = Once::new();
+	HOOKS.call_once(|| {
+		vex_sdk_mock::hooks::set_system_time(system_time);
+		vex_sdk_mock::hooks::set_system_high_res_time(system_high_res_time);
+	});
+
+	NOW.set(now);
+}
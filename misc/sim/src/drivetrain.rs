@@ -0,0 +1,73 @@
+//! The simulated drivetrain, as an evian model and tracking source, so anything written against
+//! evian's traits drives the simulation instead of motors.
+
+use std::convert::Infallible;
+
+use evian::{
+	drivetrain::model::DrivetrainModel,
+	math::{Angle, Vec2, desaturate},
+	prelude::{Arcade, Tank, TracksForwardTravel, TracksHeading, TracksPosition, TracksVelocity},
+	tracking::Tracking,
+};
+
+use crate::Sim;
+
+/// Drives the simulated robot, like [`Differential`](evian::drivetrain::model::Differential)
+/// drives motors
+pub struct SimDrivetrain {
+	pub(crate) sim: Sim,
+}
+
+impl DrivetrainModel for SimDrivetrain {
+	type Error = Infallible;
+}
+
+impl Tank for SimDrivetrain {
+	fn drive_tank(&mut self, left: f64, right: f64) -> Result<(), Self::Error> {
+		self.sim.world.borrow_mut().command = (left, right);
+		Ok(())
+	}
+}
+
+impl Arcade for SimDrivetrain {
+	fn drive_arcade(&mut self, throttle: f64, steer: f64) -> Result<(), Self::Error> {
+		let [left, right] = desaturate([throttle + steer, throttle - steer], 1.0);
+		self.drive_tank(left, right)
+	}
+}
+
+/// Where the simulated robot is, without any sensor noise
+pub struct SimTracking {
+	pub(crate) sim: Sim,
+}
+
+impl Tracking for SimTracking {}
+
+impl TracksPosition for SimTracking {
+	fn position(&self) -> Vec2<f64> {
+		self.sim.state().position
+	}
+}
+
+impl TracksHeading for SimTracking {
+	fn heading(&self) -> Angle {
+		self.sim.state().heading
+	}
+}
+
+impl TracksVelocity for SimTracking {
+	fn linear_velocity(&self) -> f64 {
+		self.sim.state().linear_velocity()
+	}
+
+	fn angular_velocity(&self) -> f64 {
+		let world = self.sim.world.borrow();
+		world.state.angular_velocity(&world.params)
+	}
+}
+
+impl TracksForwardTravel for SimTracking {
+	fn forward_travel(&self) -> f64 {
+		self.sim.state().forward_travel
+	}
+}
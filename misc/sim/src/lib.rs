@@ -0,0 +1,346 @@
+//! Runs autons on a desktop, against a simulated drivetrain instead of a robot on a field.
+//!
+//! A [`Sim`] owns simulated time. [`Sim::run`] polls an auton once per tick, stepping the physics
+//! between polls, so a run always plays out the same way no matter how fast the computer is. It
+//! also runs vexide's clock, so vexide's `sleep` and `Instant`, and evian's motions that wait on
+//! them, go by simulated time.
+//!
+//! The bots' autons run against a [`SimRobot`] if they're written against
+//! [`AutonRobot`](::robot::auton::AutonRobot) rather than `Robot`. It drives the simulated
+//! drivetrain, tracks it through [`CoproTracking`](subsystems::copro::tracking::CoproTracking)
+//! like the real robot, waits on simulated time, and records what the intake and pistons are told
+//! to do as [`Event`]s so tests can check the order and timing.
+
+mod clock;
+pub mod drivetrain;
+pub mod physics;
+pub mod robot;
+
+use std::{
+	cell::RefCell,
+	pin::{Pin, pin},
+	rc::Rc,
+	task::{Context, Poll, Waker},
+	time::Duration,
+};
+
+use evian::{
+	math::{Angle, Vec2},
+	prelude::Drivetrain,
+};
+use shrewnit::{Degrees, DegreesPerSecond, FeetPerSecond, Inches};
+use subsystems::{
+	copro::{CoproData, tracking::CoproTracking},
+	drivetrain::DrivetrainSubsystem,
+	intake::IntakeState,
+	pnemuatics::PneumaticState,
+};
+
+use crate::{
+	drivetrain::{SimDrivetrain, SimTracking},
+	physics::{DriveParams, DriveState},
+	robot::SimRobot,
+};
+
+/// How much simulated time passes between polls by default
+pub const DEFAULT_TICK: Duration = Duration::from_millis(10);
+
+/// What a mechanism was told to do
+#[derive(Clone, PartialEq, Debug)]
+pub enum Mechanism {
+	Intake(IntakeState),
+	/// A piston in the bank, by name
+	Piston(String, PneumaticState),
+	ParkPiston(PneumaticState),
+}
+
+/// Something a mechanism did, and when
+#[derive(Clone, PartialEq, Debug)]
+pub struct Event {
+	pub time: Duration,
+	pub mechanism: Mechanism,
+}
+
+struct World {
+	params: DriveParams,
+	state: DriveState,
+	/// What each side was last told to do (-1.0 to 1.0)
+	command: (f64, f64),
+	now: Duration,
+	tick: Duration,
+	events: Vec<Event>,
+	copro_data: Rc<RefCell<CoproData>>,
+}
+
+impl World {
+	fn step(&mut self) {
+		self.state.step(&self.params, self.command, self.tick);
+		self.now += self.tick;
+		clock::set(self.now);
+		self.publish();
+	}
+
+	/// Updates [`Self::copro_data`] to the robot's pose, as the coprocessor would report it
+	fn publish(&self) {
+		let mut data = self.copro_data.borrow_mut();
+		data.position.x = self.state.position.x * Inches;
+		data.position.y = self.state.position.y * Inches;
+		// The OTOS faces forward at 0°, where cartesian headings face +x
+		data.position.heading = (self.state.heading.as_degrees() - 90.0) * Degrees;
+		let velocity = Vec2::from_polar(
+			self.state.linear_velocity() / 12.0,
+			self.state.heading.as_radians(),
+		);
+		data.velocity.x = velocity.x * FeetPerSecond;
+		data.velocity.y = velocity.y * FeetPerSecond;
+		data.velocity.heading =
+			self.state.angular_velocity(&self.params).to_degrees() * DegreesPerSecond;
+		data.forward_travel = self.state.forward_travel * Inches;
+	}
+}
+
+/// A handle to a simulation. Clones share the same robot and clock.
+#[derive(Clone)]
+pub struct Sim {
+	world: Rc<RefCell<World>>,
+}
+
+impl Sim {
+	/// A robot at `position` (in inches) facing `heading`, standing still
+	pub fn new(params: DriveParams, position: Vec2<f64>, heading: Angle) -> Self {
+		let world = World {
+			params,
+			state: DriveState::new(position, heading),
+			command: (0.0, 0.0),
+			now: Duration::ZERO,
+			tick: DEFAULT_TICK,
+			events: Vec::new(),
+			copro_data: Rc::default(),
+		};
+		world.publish();
+		clock::set(world.now);
+
+		Self {
+			world: Rc::new(RefCell::new(world)),
+		}
+	}
+
+	pub fn with_tick(self, tick: Duration) -> Self {
+		self.world.borrow_mut().tick = tick;
+		self
+	}
+
+	/// A drivetrain that drives and tracks the simulated robot
+	pub fn drivetrain(&self) -> Drivetrain<SimDrivetrain, SimTracking> {
+		Drivetrain::new(
+			SimDrivetrain { sim: self.clone() },
+			SimTracking { sim: self.clone() },
+		)
+	}
+
+	/// A robot for autons to run on, tracked through [`CoproTracking`] like the real one. It only
+	/// has the named pistons, usually a bot's
+	/// [`RobotConfig::piston_names`](robot::config::RobotConfig::piston_names).
+	pub fn robot(&self, pistons: impl IntoIterator<Item = &'static str>) -> SimRobot {
+		SimRobot {
+			sim: self.clone(),
+			pistons: pistons.into_iter().collect(),
+			drivetrain: DrivetrainSubsystem::new(
+				SimDrivetrain { sim: self.clone() },
+				CoproTracking::without_imu(self.copro_data()),
+			),
+		}
+	}
+
+	/// The simulated robot's pose as the coprocessor would report it, for code that reads
+	/// [`CoproData`] rather than going through evian's tracking traits
+	pub fn copro_data(&self) -> Rc<RefCell<CoproData>> {
+		self.world.borrow().copro_data.clone()
+	}
+
+	/// Simulated time since the start
+	pub fn now(&self) -> Duration {
+		self.world.borrow().now
+	}
+
+	pub fn state(&self) -> DriveState {
+		self.world.borrow().state
+	}
+
+	/// Records what a mechanism was told to do, at the current simulated time
+	pub(crate) fn record(&self, mechanism: Mechanism) {
+		let mut world = self.world.borrow_mut();
+		let time = world.now;
+		world.events.push(Event { time, mechanism });
+	}
+
+	/// Cuts power to the drivetrain, and with `stop` stops it dead rather than letting it coast
+	pub(crate) fn brake(&self, stop: bool) {
+		let mut world = self.world.borrow_mut();
+		world.command = (0.0, 0.0);
+		if stop {
+			world.state.left_velocity = 0.0;
+			world.state.right_velocity = 0.0;
+			world.publish();
+		}
+	}
+
+	/// Everything the mechanisms were told to do, in order
+	pub fn events(&self) -> Vec<Event> {
+		self.world.borrow().events.clone()
+	}
+
+	/// Waits for `duration` of simulated time
+	pub fn sleep(&self, duration: Duration) -> Sleep {
+		Sleep {
+			sim: self.clone(),
+			until: self.now() + duration,
+		}
+	}
+
+	/// Runs `future` until it finishes, one tick at a time, or gives up with `None` after
+	/// `timeout` of simulated time
+	pub fn run<F: Future>(&self, future: F, timeout: Duration) -> Option<F::Output> {
+		let mut future = pin!(future);
+		let mut cx = Context::from_waker(Waker::noop());
+		clock::set(self.now());
+
+		loop {
+			if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+				return Some(output);
+			}
+			if self.now() >= timeout {
+				return None;
+			}
+			self.world.borrow_mut().step();
+		}
+	}
+}
+
+/// Returned by [`Sim::sleep`]
+pub struct Sleep {
+	sim: Sim,
+	until: Duration,
+}
+
+impl Future for Sleep {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.sim.now() >= self.until {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ::robot::auton::AutonRobot;
+	use evian::prelude::TracksPosition;
+	use subsystems::pnemuatics::PneumaticError;
+
+	use super::*;
+
+	/// Drives forward on a timer and scores, like the timed parts of the match autons
+	async fn timed_auton(robot: &mut impl AutonRobot) {
+		robot.run_intake(IntakeState::full_forward());
+		_ = robot.drivetrain().drive_arcade(0.35, 0.0);
+		robot.sleep(Duration::from_secs(1)).await;
+		_ = robot.drivetrain().drive_arcade(0.0, 0.0);
+		robot.sleep(Duration::from_millis(500)).await;
+		_ = robot.set_piston("flap", PneumaticState::Extended);
+	}
+
+	fn start() -> Sim {
+		Sim::new(
+			DriveParams::default(),
+			Vec2::new(0.0, 0.0),
+			Angle::from_degrees(90.0),
+		)
+	}
+
+	#[test]
+	fn test_auton_final_pose() {
+		let sim = start();
+		let mut robot = sim.robot(["flap"]);
+		assert!(sim.run(timed_auton(&mut robot), Duration::from_secs(15)).is_some());
+
+		let position = sim.drivetrain().tracking.position();
+		assert!(position.x.abs() < 1e-6);
+		assert!(position.y > 20.0 && position.y < 22.0, "{}", position.y);
+		// The robot's own tracking goes through the coprocessor data
+		assert!(robot.drivetrain.tracking.position().distance(position) < 1e-6);
+	}
+
+	#[test]
+	fn test_event_timeline() {
+		let sim = start();
+		sim.run(timed_auton(&mut sim.robot(["flap"])), Duration::from_secs(15));
+
+		assert_eq!(
+			sim.events(),
+			[
+				Event {
+					time: Duration::ZERO,
+					mechanism: Mechanism::Intake(IntakeState::full_forward()),
+				},
+				Event {
+					time: Duration::from_millis(1500),
+					mechanism: Mechanism::Piston("flap".to_string(), PneumaticState::Extended),
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn test_vexide_sleep() {
+		let sim = start();
+		assert!(
+			sim.run(
+				vexide::time::sleep(Duration::from_millis(500)),
+				Duration::from_secs(15)
+			)
+			.is_some()
+		);
+
+		// Done on the tick it's due, or the one after
+		let now = sim.now();
+		assert!(now >= Duration::from_millis(500) && now <= Duration::from_millis(510), "{now:?}");
+	}
+
+	#[test]
+	fn test_unknown_piston() {
+		let sim = start();
+		let mut robot = sim.robot(["flap"]);
+
+		assert!(matches!(
+			robot.set_piston("flapp", PneumaticState::Extended),
+			Err(PneumaticError::UnknownPiston(_))
+		));
+		assert!(sim.events().is_empty());
+	}
+
+	#[test]
+	fn test_deterministic() {
+		let a = start();
+		let b = start();
+		a.run(timed_auton(&mut a.robot(["flap"])), Duration::from_secs(15));
+		b.run(timed_auton(&mut b.robot(["flap"])), Duration::from_secs(15));
+
+		assert_eq!(a.state(), b.state());
+		assert_eq!(a.now(), b.now());
+	}
+
+	#[test]
+	fn test_times_out() {
+		let sim = start();
+
+		assert_eq!(
+			sim.run(std::future::pending::<()>(), Duration::from_secs(1)),
+			None
+		);
+		assert_eq!(sim.now(), Duration::from_secs(1));
+	}
+}
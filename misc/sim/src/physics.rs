@@ -0,0 +1,124 @@
+//! A simple model of a differential drive: each side speeds up towards its commanded speed with a
+//! first-order lag, and the robot moves like a unicycle from the two side speeds.
+
+use std::time::Duration;
+
+use evian::math::{Angle, Vec2};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DriveParams {
+	/// Distance between the left and right wheels, in inches
+	pub track_width: f64,
+	/// How fast a side goes at full power, in inches per second
+	pub max_speed: f64,
+	/// How long a side takes to get ~63% of the way to a new speed
+	pub time_constant: Duration,
+}
+
+impl Default for DriveParams {
+	fn default() -> Self {
+		Self {
+			track_width: 12.0,
+			max_speed: 60.0,
+			time_constant: Duration::from_millis(120),
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DriveState {
+	/// Inches
+	pub position: Vec2<f64>,
+	/// Cartesian, so 0° faces +x and counterclockwise is positive
+	pub heading: Angle,
+	/// Inches per second
+	pub left_velocity: f64,
+	/// Inches per second
+	pub right_velocity: f64,
+	/// How far the robot has driven forwards (backwards counts down), in inches
+	pub forward_travel: f64,
+}
+
+impl DriveState {
+	pub fn new(position: Vec2<f64>, heading: Angle) -> Self {
+		Self {
+			position,
+			heading,
+			left_velocity: 0.0,
+			right_velocity: 0.0,
+			forward_travel: 0.0,
+		}
+	}
+
+	/// Inches per second
+	pub fn linear_velocity(&self) -> f64 {
+		(self.left_velocity + self.right_velocity) / 2.0
+	}
+
+	/// Radians per second, counterclockwise
+	pub fn angular_velocity(&self, params: &DriveParams) -> f64 {
+		(self.right_velocity - self.left_velocity) / params.track_width
+	}
+
+	/// Moves the robot forward by `dt` with each side commanded at a fraction of full power
+	pub fn step(&mut self, params: &DriveParams, (left, right): (f64, f64), dt: Duration) {
+		let dt_secs = dt.as_secs_f64();
+		let response = 1.0 - (-dt_secs / params.time_constant.as_secs_f64()).exp();
+
+		self.left_velocity +=
+			(left.clamp(-1.0, 1.0) * params.max_speed - self.left_velocity) * response;
+		self.right_velocity +=
+			(right.clamp(-1.0, 1.0) * params.max_speed - self.right_velocity) * response;
+
+		let distance = self.linear_velocity() * dt_secs;
+		let turn = self.angular_velocity(params) * dt_secs;
+		// Moving along the average heading over the step is a lot closer than the starting one
+		let heading = self.heading.as_radians() + turn / 2.0;
+
+		self.position = self.position + Vec2::from_polar(distance, heading);
+		self.heading = Angle::from_radians(self.heading.as_radians() + turn).wrapped_full();
+		self.forward_travel += distance;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DT: Duration = Duration::from_millis(10);
+
+	fn run(state: &mut DriveState, command: (f64, f64), time: Duration) {
+		for _ in 0..(time.as_millis() / DT.as_millis()) {
+			state.step(&DriveParams::default(), command, DT);
+		}
+	}
+
+	#[test]
+	fn test_drives_straight_along_heading() {
+		let mut state = DriveState::new(Vec2::new(0.0, 0.0), Angle::from_degrees(90.0));
+		run(&mut state, (0.5, 0.5), Duration::from_secs(2));
+
+		assert!(state.position.x.abs() < 1e-9);
+		// Up to speed (30in/s) well within 2s, less a bit for getting there
+		assert!(state.position.y > 55.0 && state.position.y < 60.0, "{}", state.position.y);
+		assert!((state.forward_travel - state.position.y).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_turns_in_place() {
+		let mut state = DriveState::new(Vec2::new(0.0, 0.0), Angle::from_degrees(0.0));
+		run(&mut state, (-0.2, 0.2), Duration::from_millis(500));
+
+		assert!(state.position.length() < 1e-9);
+		assert!(state.heading.as_radians() > 0.0, "positive right side turns counterclockwise");
+	}
+
+	#[test]
+	fn test_speed_lags_command() {
+		let mut state = DriveState::new(Vec2::new(0.0, 0.0), Angle::from_degrees(0.0));
+		run(&mut state, (1.0, 1.0), DriveParams::default().time_constant);
+
+		let fraction = state.linear_velocity() / DriveParams::default().max_speed;
+		assert!((fraction - 0.632).abs() < 0.01, "{fraction}");
+	}
+}
@@ -0,0 +1,63 @@
+//! A stand-in for the bots' `Robot`, so autons written against [`AutonRobot`] run on simulated
+//! time.
+
+use std::time::Duration;
+
+use robot::auton::AutonRobot;
+use subsystems::{
+	copro::tracking::CoproTracking,
+	drivetrain::DrivetrainSubsystem,
+	intake::IntakeState,
+	pnemuatics::{PneumaticError, PneumaticState},
+};
+use vexide::smart::{PortError, motor::BrakeMode};
+
+use crate::{Mechanism, Sim, drivetrain::SimDrivetrain};
+
+/// Returned by [`Sim::robot`]
+pub struct SimRobot {
+	pub(crate) sim: Sim,
+	pub(crate) pistons: Vec<&'static str>,
+	pub drivetrain: DrivetrainSubsystem<SimDrivetrain, CoproTracking>,
+}
+
+impl AutonRobot for SimRobot {
+	type Model = SimDrivetrain;
+	type Tracking = CoproTracking;
+
+	fn drivetrain(&mut self) -> &mut DrivetrainSubsystem<SimDrivetrain, CoproTracking> {
+		&mut self.drivetrain
+	}
+
+	fn brake(&mut self, mode: BrakeMode) {
+		self.sim.brake(matches!(mode, BrakeMode::Brake | BrakeMode::Hold));
+	}
+
+	fn run_intake(&mut self, state: IntakeState) {
+		self.sim.record(Mechanism::Intake(state));
+	}
+
+	/// Jams don't happen in the simulation, so this just runs the intake for `duration`
+	async fn unjam_intake(&mut self, state: IntakeState, duration: Duration) {
+		self.run_intake(state);
+		self.sim.sleep(duration).await;
+	}
+
+	fn set_piston(&mut self, name: &str, state: PneumaticState) -> Result<(), PneumaticError> {
+		if !self.pistons.contains(&name) {
+			return Err(PneumaticError::UnknownPiston(name.to_string()));
+		}
+
+		self.sim.record(Mechanism::Piston(name.to_string(), state));
+		Ok(())
+	}
+
+	fn set_park_piston(&mut self, state: PneumaticState) -> Result<(), PortError> {
+		self.sim.record(Mechanism::ParkPiston(state));
+		Ok(())
+	}
+
+	fn sleep(&self, duration: Duration) -> impl Future<Output = ()> {
+		self.sim.sleep(duration)
+	}
+}